@@ -7,48 +7,48 @@ fn option_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![Option::<Strin
 fn u64_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![0u64; 1024]); }
 fn u32x2_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![(0u32,0u32); 1024]); }
 fn u8_u64_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![(0u8, 0u64); 512]); }
-fn string10_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![format!("grawwwwrr!"); 1024]); }
-fn string20_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![format!("grawwwwrr!!!!!!!!!!!"); 512]); }
-fn vec_u_s_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![vec![(0u64, format!("grawwwwrr!")); 32]; 32]); }
-fn vec_u_vn_s_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![vec![(0u64, vec![(); 1 << 40], format!("grawwwwrr!")); 32]; 32]); }
+fn string10_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec!["grawwwwrr!".to_string(); 1024]); }
+fn string20_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec!["grawwwwrr!!!!!!!!!!!".to_string(); 512]); }
+fn vec_u_s_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![vec![(0u64, "grawwwwrr!".to_string()); 32]; 32]); }
+fn vec_u_vn_s_copy(bencher: &mut Bencher) { _bench_copy(bencher, vec![vec![(0u64, vec![(); 1 << 40], "grawwwwrr!".to_string()); 32]; 32]); }
 
 fn empty_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![(); 1024]); }
 fn option_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![Option::<String>::None; 1024]); }
 fn u64_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![0u64; 1024]); }
 fn u32x2_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![(0u32,0u32); 1024]); }
 fn u8_u64_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![(0u8, 0u64); 512]); }
-fn string10_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![format!("grawwwwrr!"); 1024]); }
-fn string20_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![format!("grawwwwrr!!!!!!!!!!!"); 512]); }
-fn vec_u_s_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![vec![(0u64, format!("grawwwwrr!")); 32]; 32]); }
-fn vec_u_vn_s_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![vec![(0u64, vec![(); 1 << 40], format!("grawwwwrr!")); 32]; 32]); }
+fn string10_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec!["grawwwwrr!".to_string(); 1024]); }
+fn string20_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec!["grawwwwrr!!!!!!!!!!!".to_string(); 512]); }
+fn vec_u_s_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![vec![(0u64, "grawwwwrr!".to_string()); 32]; 32]); }
+fn vec_u_vn_s_extend(bencher: &mut Bencher) { _bench_extend(bencher, vec![vec![(0u64, vec![(); 1 << 40], "grawwwwrr!".to_string()); 32]; 32]); }
 
 fn empty_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![(); 1024]); }
 fn option_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![Option::<String>::None; 1024]); }
 fn u64_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![0u64; 1024]); }
 fn u32x2_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![(0u32,0u32); 1024]); }
 fn u8_u64_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![(0u8, 0u64); 512]); }
-fn string10_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![format!("grawwwwrr!"); 1024]); }
-fn string20_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![format!("grawwwwrr!!!!!!!!!!!"); 512]); }
-fn vec_u_s_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![vec![(0u64, format!("grawwwwrr!")); 32]; 32]); }
-fn vec_u_vn_s_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![vec![(0u64, vec![(); 1 << 40], format!("grawwwwrr!")); 32]; 32]); }
+fn string10_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec!["grawwwwrr!".to_string(); 1024]); }
+fn string20_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec!["grawwwwrr!!!!!!!!!!!".to_string(); 512]); }
+fn vec_u_s_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![vec![(0u64, "grawwwwrr!".to_string()); 32]; 32]); }
+fn vec_u_vn_s_clone(bencher: &mut Bencher) { _bench_clone(bencher, vec![vec![(0u64, vec![(); 1 << 40], "grawwwwrr!".to_string()); 32]; 32]); }
 
 // #[bench] fn empty_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![(); 1024]); }
 // #[bench] fn u64_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![0u64; 1024]); }
 // #[bench] fn u32x2_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![(0u32,0u32); 1024]); }
 // #[bench] fn u8_u64_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![(0u8, 0u64); 512]); }
-// #[bench] fn string10_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![format!("grawwwwrr!"); 1024]); }
-// #[bench] fn string20_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![format!("grawwwwrr!!!!!!!!!!!"); 512]); }
-// #[bench] fn vec_u_s_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![vec![(0u64, format!("grawwwwrr!")); 32]; 32]); }
-// #[bench] fn vec_u_vn_s_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![vec![(0u64, vec![(); 1 << 40], format!("grawwwwrr!")); 32]; 32]); }
+// #[bench] fn string10_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec!["grawwwwrr!".to_string(); 1024]); }
+// #[bench] fn string20_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec!["grawwwwrr!!!!!!!!!!!".to_string(); 512]); }
+// #[bench] fn vec_u_s_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![vec![(0u64, "grawwwwrr!".to_string()); 32]; 32]); }
+// #[bench] fn vec_u_vn_s_realloc(bencher: &mut Bencher) { _bench_realloc(bencher, vec![vec![(0u64, vec![(); 1 << 40], "grawwwwrr!".to_string()); 32]; 32]); }
 
 // #[bench] fn empty_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![(); 1024]); }
 // #[bench] fn u64_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![0u64; 1024]); }
 // #[bench] fn u32x2_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![(0u32,0u32); 1024]); }
 // #[bench] fn u8_u64_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![(0u8, 0u64); 512]); }
-// #[bench] fn string10_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![format!("grawwwwrr!"); 1024]); }
-// #[bench] fn string20_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![format!("grawwwwrr!!!!!!!!!!!"); 512]); }
-// #[bench] fn vec_u_s_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![vec![(0u64, format!("grawwwwrr!")); 32]; 32]); }
-// #[bench] fn vec_u_vn_s_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![vec![(0u64, vec![(); 1 << 40], format!("grawwwwrr!")); 32]; 32]); }
+// #[bench] fn string10_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec!["grawwwwrr!".to_string(); 1024]); }
+// #[bench] fn string20_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec!["grawwwwrr!!!!!!!!!!!".to_string(); 512]); }
+// #[bench] fn vec_u_s_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![vec![(0u64, "grawwwwrr!".to_string()); 32]; 32]); }
+// #[bench] fn vec_u_vn_s_prealloc(bencher: &mut Bencher) { _bench_prealloc(bencher, vec![vec![(0u64, vec![(); 1 << 40], "grawwwwrr!".to_string()); 32]; 32]); }
 
 fn _bench_copy<T: Columnar+Eq>(bencher: &mut Bencher, record: T) where T::Container : for<'a> columnar::Push<&'a T> {
     use columnar::Push;
@@ -103,7 +103,7 @@ fn _bench_cols(bencher: &mut Bencher, prog: &[Op]) {
         rows.push((i, i+1, i+2));
     }
 
-    let cols = Columnar::into_columns(rows.into_iter());
+    let cols = Columnar::into_columns(rows);
     let mut cols: Vec<Col> = vec![Ok(cols.0), Ok(cols.1), Ok(cols.2)];
     let len = cols.len();
 
@@ -32,7 +32,7 @@ fn simd_rows_bad(bencher: &mut Bencher) {
 
 fn simd_cols_all(bencher: &mut Bencher) {
     let rows = (0 .. 1024u32).map(|i| (Wrapping(i as u16), i, i as u64)).collect::<Vec<_>>();
-    let cols = Columnar::into_columns(rows.into_iter());
+    let cols = Columnar::into_columns(rows);
     bencher.bytes = 14 * 1024;
     bencher.iter(|| {
         let sum0 = cols.0.iter().sum::<Wrapping<u16>>();
@@ -79,7 +79,7 @@ fn simd_rows_3rd(bencher: &mut Bencher) {
 
 fn simd_cols_1st(bencher: &mut Bencher) {
     let rows = (0 .. 1024u32).map(|i| (Wrapping(i as u16), i, i as u64)).collect::<Vec<_>>();
-    let cols = Columnar::into_columns(rows.into_iter());
+    let cols = Columnar::into_columns(rows);
     bencher.bytes = 2 * 1024;
     bencher.iter(|| {
         let sum = cols.0.iter().sum::<Wrapping<u16>>();
@@ -88,7 +88,7 @@ fn simd_cols_1st(bencher: &mut Bencher) {
 }
 fn simd_cols_2nd(bencher: &mut Bencher) {
     let rows = (0 .. 1024u32).map(|i| (i as u16, i, i as u64)).collect::<Vec<_>>();
-    let cols = Columnar::into_columns(rows.into_iter());
+    let cols = Columnar::into_columns(rows);
     bencher.bytes = 4 * 1024;
     bencher.iter(|| {
         let sum = cols.1.iter().sum::<u32>();
@@ -97,7 +97,7 @@ fn simd_cols_2nd(bencher: &mut Bencher) {
 }
 fn simd_cols_3rd(bencher: &mut Bencher) {
     let rows = (0 .. 1024u32).map(|i| (i as u16, i, i as u64)).collect::<Vec<_>>();
-    let cols = Columnar::into_columns(rows.into_iter());
+    let cols = Columnar::into_columns(rows);
     bencher.bytes = 8 * 1024;
     bencher.iter(|| {
         let sum = cols.2.iter().sum::<u64>();
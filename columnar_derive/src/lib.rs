@@ -26,16 +26,297 @@ pub fn derive(input: TokenStream) -> TokenStream {
     }
 }
 
-fn extract_attr(attrs: &[Attribute]) -> Option<proc_macro2::TokenStream> {
+/// The attributes of a type, from `#[columnar(...)]`.
+#[derive(Default)]
+struct TypeAttrs {
+    /// Marked `derive(...)`, with traits to derive for the reference type.
+    derive: Option<proc_macro2::TokenStream>,
+    /// Marked `reference(...)`, with traits to implement for the reference type as they behave for the owned type.
+    ///
+    /// `PartialEq` compares references with owned values, `Debug` prints references as the owned values
+    /// would print, and `Hash` hashes references as the owned values would hash.
+    reference: Vec<syn::Ident>,
+    /// The integer type of `#[repr(...)]`, if any, which sets the width of enum tags.
+    repr: Option<syn::Ident>,
+}
+
+impl TypeAttrs {
+    /// Whether no attributes are present.
+    fn is_empty(&self) -> bool {
+        self.derive.is_none() && self.reference.is_empty()
+    }
+    /// Whether `reference(...)` lists `name`.
+    fn reference(&self, name: &str) -> bool {
+        self.reference.iter().any(|ident| ident == name)
+    }
+}
+
+fn extract_attr(attrs: &[Attribute]) -> TypeAttrs {
+    let mut result = TypeAttrs::default();
+    for attr in attrs {
+        if attr.path().is_ident("repr") {
+            // Other representation hints, like `C`, do not affect tags.
+            attr.parse_nested_meta(|meta| {
+                if let Some(ident) = meta.path.get_ident() {
+                    if ["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"].iter().any(|int| ident == int) {
+                        result.repr = Some(ident.clone());
+                    }
+                }
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            }).unwrap();
+        }
+        if attr.path().is_ident("columnar") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("derive") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let traits: proc_macro2::TokenStream = content.parse()?;
+                    result.derive = Some(quote! { derive(#traits) });
+                    Ok(())
+                } else if meta.path.is_ident("reference") {
+                    meta.parse_nested_meta(|inner| {
+                        match inner.path.get_ident() {
+                            Some(ident) if ident == "PartialEq" || ident == "Debug" || ident == "Hash" => {
+                                result.reference.push(ident.clone());
+                                Ok(())
+                            }
+                            _ => Err(inner.error("only `PartialEq`, `Debug`, and `Hash` are supported by `reference(...)`")),
+                        }
+                    })
+                } else {
+                    Err(meta.error("unsupported columnar type attribute"))
+                }
+            }).unwrap();
+        }
+    }
+    result
+}
+
+/// The attributes of a field, from `#[columnar(...)]`.
+#[derive(Default)]
+struct FieldAttrs {
+    /// Marked `packed`, to be stored in a bit-packed container.
+    packed: bool,
+    /// Marked `default`, to be read as default values from data that lack it.
+    default: bool,
+    /// Marked `added_in = N`, to be read as default values from data written before version `N`.
+    added_in: Option<u64>,
+    /// Marked `container = C`, to be stored in a `C` rather than in the container of the field type.
+    container: Option<syn::Type>,
+    /// Marked `with = W`, to be stored in a `W<C>` wrapping the container `C` of the field type.
+    with: Option<syn::Path>,
+    /// Marked `skip` or `skip = f`, to not be stored and to be reconstructed by `Default` or `f()`.
+    skip: bool,
+    /// The function named by `skip = f`, if any.
+    skip_with: Option<syn::Path>,
+}
+
+impl FieldAttrs {
+    /// The container for a field of type `ty`, which is `<ty as Columnar>::Container` unless overridden.
+    fn container(&self, ty: &syn::Type) -> proc_macro2::TokenStream {
+        if [self.packed, self.container.is_some(), self.with.is_some()].iter().filter(|set| **set).count() > 1 {
+            panic!("At most one of `#[columnar(packed)]`, `#[columnar(container = ..)]`, and `#[columnar(with = ..)]` may be used on a field");
+        }
+        if self.packed { quote! { ::columnar::packed::Packed<#ty> } }
+        else if let Some(container) = &self.container { quote! { #container } }
+        else if let Some(with) = &self.with { quote! { #with < <#ty as ::columnar::Columnar>::Container > } }
+        else { quote! { <#ty as ::columnar::Columnar>::Container } }
+    }
+    /// Whether the container of the field is not `<ty as Columnar>::Container`.
+    fn overrides(&self) -> bool {
+        self.packed || self.container.is_some() || self.with.is_some()
+    }
+    /// The value of a skipped field, from `Default` or the function it names.
+    fn skipped_value(&self) -> proc_macro2::TokenStream {
+        match &self.skip_with {
+            Some(function) => quote! { #function() },
+            None => quote! { ::core::default::Default::default() },
+        }
+    }
+}
+
+/// How a field holds values of the type that contains it, which are stored as nodes linked by their indexes.
+#[derive(Copy, Clone, PartialEq)]
+enum Recursion {
+    /// The field does not hold values of the type.
+    None,
+    /// The field is a `Box<Self>`, stored as the index of its node.
+    Box,
+    /// The field is a `Vec<Self>`, stored as the indexes of its nodes.
+    Vec,
+    /// The field is an `Option<Box<Self>>`, stored as the index of its node if any.
+    Option,
+}
+
+impl Recursion {
+    /// How a field of type `ty` holds values of the type `name`, which may also be written `Self`.
+    ///
+    /// Only unqualified `Self` or `name` is recognized, so that other types of the same name are not recursive.
+    /// Fields of any other type are not recursive, and are stored in their own containers.
+    fn of(name: &syn::Ident, ty: &syn::Type) -> Self {
+        if wrapped(ty, "Box").is_some_and(|inner| names_self(name, inner)) { Recursion::Box }
+        else if wrapped(ty, "Vec").is_some_and(|inner| names_self(name, inner)) { Recursion::Vec }
+        else if wrapped(ty, "Option").and_then(|inner| wrapped(inner, "Box")).is_some_and(|inner| names_self(name, inner)) { Recursion::Option }
+        else { Recursion::None }
+    }
+    /// The container for the links of the field.
+    fn container(&self) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { <u64 as ::columnar::Columnar>::Container },
+            Recursion::Vec => quote! { ::columnar::Vecs<<u64 as ::columnar::Columnar>::Container> },
+            Recursion::Option => quote! { <::core::option::Option<u64> as ::columnar::Columnar>::Container },
+        }
+    }
+    /// The bound for the container of the links of the field to accept them.
+    fn push_bound(&self) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { ::columnar::Push<u64> },
+            Recursion::Vec => quote! { for<'columnar_links> ::columnar::Push<&'columnar_links [u64]> },
+            Recursion::Option => quote! { ::columnar::Push<::core::option::Option<u64>> },
+        }
+    }
+    /// The reference type of the field, for nodes of type `nodes` and links of type `links`.
+    fn reference(&self, nodes: proc_macro2::TokenStream, links: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { ::columnar::recursive::Child<#nodes> },
+            Recursion::Vec => quote! { ::columnar::recursive::Children<#nodes, #links> },
+            Recursion::Option => quote! { ::core::option::Option<::columnar::recursive::Child<#nodes>> },
+        }
+    }
+    /// The reference of the field, from the nodes and the links.
+    fn get(&self, nodes: proc_macro2::TokenStream, links: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { ::columnar::recursive::Child::new(#nodes, #links) },
+            Recursion::Vec => quote! { ::columnar::recursive::Children::new(#nodes, #links) },
+            Recursion::Option => quote! { ::columnar::recursive::Child::optional(#nodes, #links) },
+        }
+    }
+    /// Pushes the values the field `field` refers to into `self`, producing their links.
+    fn push_owned(&self, field: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { ::columnar::recursive::push(self, &**#field) },
+            Recursion::Vec => quote! { ::columnar::recursive::push_all(self, #field.iter()) },
+            Recursion::Option => quote! { #field.as_deref().map(|#field| ::columnar::recursive::push(self, #field)) },
+        }
+    }
+    /// Pushes the references the field `field` refers to into `self`, producing their links.
+    fn push_reference(&self, field: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { ::columnar::recursive::push(self, #field.get()) },
+            Recursion::Vec => quote! { ::columnar::recursive::push_all(self, #field.into_iter()) },
+            Recursion::Option => quote! { #field.map(|#field| ::columnar::recursive::push(self, #field.get())) },
+        }
+    }
+    /// Pushes the links `field` into `container`, the container of the field.
+    fn push_links(&self, container: proc_macro2::TokenStream, field: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::Vec => quote! { #container.push(&#field[..]); },
+            _ => quote! { #container.push(#field); },
+        }
+    }
+    /// Compares the reference `field` of the field with the owned field bound to `owned`, in an implementation of `PartialEq`.
+    fn eq(&self, field: proc_macro2::TokenStream, owned: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { #field.get() == **#owned },
+            Recursion::Vec => quote! { ::columnar::Len::len(&#field) == #owned.len() && #field.into_iter().zip(#owned.iter()).all(|(child, owned)| child == *owned) },
+            Recursion::Option => quote! {
+                match (#field, #owned) {
+                    (::core::option::Option::Some(child), ::core::option::Option::Some(owned)) => child.get() == **owned,
+                    (::core::option::Option::None, ::core::option::Option::None) => true,
+                    _ => false,
+                }
+            },
+        }
+    }
+    /// Converts the reference `field` of the field to the owned field, in an implementation of `Columnar` for `Self`.
+    fn owned(&self, field: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { Box::new(<Self as ::columnar::Columnar>::into_owned(#field.get())) },
+            Recursion::Vec => quote! { #field.into_iter().map(<Self as ::columnar::Columnar>::into_owned).collect() },
+            Recursion::Option => quote! { #field.map(|child| Box::new(<Self as ::columnar::Columnar>::into_owned(child.get()))) },
+        }
+    }
+    /// Reborrows the reference `field` of the field, whose links are in a `links`, in an implementation of `Borrow` for `Self`.
+    fn reborrow(&self, field: proc_macro2::TokenStream, links: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Recursion::None => unreachable!(),
+            Recursion::Box => quote! { #field.map(<Self as ::columnar::Borrow>::reborrow) },
+            Recursion::Vec => quote! { #field.map(<Self as ::columnar::Borrow>::reborrow, <#links as ::columnar::Borrow>::reborrow_ref) },
+            Recursion::Option => quote! { #field.map(|child| child.map(<Self as ::columnar::Borrow>::reborrow)) },
+        }
+    }
+}
+
+/// The type argument of `ty`, if it is `wrapper<T>` for some `T`.
+fn wrapped<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) if path.qself.is_none() && segment.ident == wrapper && arguments.args.len() == 1 => {
+            match &arguments.args[0] {
+                syn::GenericArgument::Type(inner) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether `ty` is the type `name`, perhaps with type arguments, or `Self`.
+fn names_self(name: &syn::Ident, ty: &syn::Type) -> bool {
+    let syn::Type::Path(path) = ty else { return false };
+    path.qself.is_none() && (path.path.is_ident("Self") || (path.path.segments.len() == 1 && path.path.segments[0].ident == *name))
+}
+
+fn field_attrs(attrs: &[Attribute]) -> FieldAttrs {
+    let mut field = FieldAttrs::default();
     for attr in attrs {
         if attr.path().is_ident("columnar") {
-            return Some(attr.parse_args().unwrap());
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("packed") {
+                    field.packed = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    field.default = true;
+                    Ok(())
+                } else if meta.path.is_ident("added_in") {
+                    let version: syn::LitInt = meta.value()?.parse()?;
+                    field.added_in = Some(version.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("container") {
+                    field.container = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    field.with = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    field.skip = true;
+                    if meta.input.peek(syn::Token![=]) {
+                        field.skip_with = Some(meta.value()?.parse()?);
+                    }
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported columnar field attribute"))
+                }
+            }).unwrap();
         }
     }
-    None
+    field
 }
 
-fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::DataStruct, vis: syn::Visibility, attr: Option<proc_macro2::TokenStream>) -> proc_macro::TokenStream {
+fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::DataStruct, vis: syn::Visibility, attr: TypeAttrs) -> proc_macro::TokenStream {
 
     let c_name = format!("{}Container", name);
     let c_ident = syn::Ident::new(&c_name, name.span());
@@ -49,18 +330,65 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
         _ => unimplemented!(),
     };
 
-    let names: &Vec<_> = &match &data_struct.fields {
+    let all_names: &Vec<_> = &match &data_struct.fields {
         syn::Fields::Named(fields) => fields.named.iter().map(|field| field.ident.clone().unwrap()).collect(),
         syn::Fields::Unnamed(fields) => (0 .. fields.unnamed.len()).map(|index| syn::Ident::new(&format!("f{}", index), name.span())).collect(),
         _ => unimplemented!(),
     };
 
-    let types: &Vec<_> = &match &data_struct.fields {
-        syn::Fields::Named(fields) => fields.named.iter().map(|field| &field.ty).collect(),
-        syn::Fields::Unnamed(fields) => fields.unnamed.iter().map(|field| &field.ty).collect(),
-        _ => unimplemented!(),
+    let all_attrs: Vec<FieldAttrs> = data_struct.fields.iter().map(|field| field_attrs(&field.attrs)).collect();
+    if all_attrs.iter().any(|attrs| attrs.skip && (attrs.overrides() || attrs.default || attrs.added_in.is_some())) {
+        panic!("`#[columnar(skip)]` cannot be combined with other field attributes");
+    }
+    if all_attrs.iter().all(|attrs| attrs.skip) {
+        panic!("`#[columnar(skip)]` cannot be used on every field");
+    }
+
+    // Fields marked `#[columnar(skip)]` are not stored, and all code below concerns only the stored fields,
+    // other than where values of the struct are destructured or constructed.
+    let stored: Vec<usize> = (0 .. all_attrs.len()).filter(|index| !all_attrs[*index].skip).collect();
+
+    let names: &Vec<_> = &stored.iter().map(|index| all_names[*index].clone()).collect();
+
+    let types: &Vec<_> = &stored.iter().map(|index| &data_struct.fields.iter().nth(*index).unwrap().ty).collect();
+
+    // The fields of tuple structs are named by their position, rather than by `f0`, `f1`, ...
+    let labels: &Vec<String> = &stored.iter().map(|index| {
+        if named { all_names[*index].to_string() } else { index.to_string() }
+    }).collect();
+
+    // Patterns that bind the stored fields of a value of the struct, ignoring skipped fields.
+    let destructure = if named {
+        if stored.len() < all_names.len() { quote! { #name { #(#names,)* .. } } }
+        else { quote! { #name { #(#names),* } } }
+    }
+    else {
+        let patterns = all_names.iter().zip(all_attrs.iter()).map(|(name, attrs)| if attrs.skip { quote! { _ } } else { quote! { #name } });
+        quote! { #name ( #(#patterns),* ) }
     };
 
+    let attrs: &Vec<&FieldAttrs> = &stored.iter().map(|index| &all_attrs[*index]).collect();
+
+    // Fields that hold values of the struct store links to their nodes, all in one container.
+    let recursions: &Vec<Recursion> = &types.iter().map(|ty| Recursion::of(name, ty)).collect();
+    let recursive = recursions.iter().any(|recursion| *recursion != Recursion::None);
+    if recursions.iter().zip(attrs.iter()).any(|(recursion, attrs)| *recursion != Recursion::None && (attrs.overrides() || attrs.default || attrs.added_in.is_some())) {
+        panic!("Fields holding `Box<Self>`, `Vec<Self>`, or `Option<Box<Self>>` cannot have field attributes");
+    }
+
+    // Fields marked `#[columnar(packed)]` use `Packed<T>` rather than `T::Container`.
+    let packed: &Vec<bool> = &attrs.iter().map(|attrs| attrs.packed).collect();
+
+    // Packed fields are converted by functions in `columnar::packed`, rather than by `Columnar`.
+    let field_traits = &packed.iter().map(|packed| {
+        if *packed { quote! { ::columnar::packed::Packable } }
+        else { quote! { ::columnar::Columnar } }
+    }).collect::<Vec<_>>();
+    let field_paths = &packed.iter().map(|packed| {
+        if *packed { quote! { ::columnar::packed } }
+        else { quote! { ::columnar::Columnar } }
+    }).collect::<Vec<_>>();
+
     // Generic type parameters for the containers for the struct fields.
     let container_types = &names.iter().enumerate().map(|(index, name)| {
         let new_name = format!("C{}", index);
@@ -95,16 +423,17 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
 
         let ty_gen = quote! { < #(#reference_types),* > };
 
-        let attr = if let Some(attr) = attr {
-            quote! { #[#attr] }
-        } else {
-            quote! {}
+        let derive = match &attr.derive {
+            Some(derive) => quote! { #[#derive] },
+            None => quote! {},
         };
+        // References print as the owned type when asked, and otherwise as themselves.
+        let debug = if attr.reference("Debug") { quote! {} } else { quote! { Debug } };
 
         quote! {
             /// Derived columnar reference for a struct.
-            #[derive(Copy, Clone, Debug)]
-            #attr
+            #[derive(Copy, Clone, #debug)]
+            #derive
             #vis struct #r_ident #ty_gen {
                 #(
                     /// Field for #names.
@@ -121,31 +450,92 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
             syn::Ident::new(&new_name, name.span())
         }).collect::<Vec<_>>();
 
-        let (_impl_gen, ty_gen, _where_clause) = generics.split_for_impl();
+        let (_impl_gen, ty_gen, where_clause) = generics.split_for_impl();
 
         let struct_generics = generics.params.iter();
-        let impl_gen = quote! { < #(#struct_generics,)* #(#reference_types),* > };
 
-        let where_clause = quote! { where #(#reference_types: PartialEq<#types>),* };
+        let destructure_self = quote! { let #destructure = other; };
+
+        if recursive {
+            // Children are compared by this implementation, as references to nodes of a `Nodes`.
+            let reference = attr.reference("PartialEq");
+            let link_types = reference_types.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion == Recursion::Vec).map(|(ty, _)| ty).collect::<Vec<_>>();
+            let field_types = reference_types.iter().zip(types.iter()).zip(packed.iter()).zip(recursions.iter()).map(|(((r, ty), packed), recursion)| {
+                if *recursion != Recursion::None { recursion.reference(quote! { Nodes }, quote! { #r }) }
+                else if !reference { quote! { #r } }
+                else if *packed { quote! { #ty } }
+                else { quote! { ::columnar::Ref<'columnar, #ty> } }
+            });
+            let fields = names.iter().zip(types.iter()).zip(packed.iter()).zip(recursions.iter()).map(|(((name, ty), packed), recursion)| {
+                if *recursion != Recursion::None { recursion.eq(quote! { self.#name }, name) }
+                else if !reference { quote! { self.#name == *#name } }
+                else if *packed { quote! { ::columnar::packed::into_owned::<#ty>(self.#name) == *#name } }
+                else { quote! { <#ty as ::columnar::Columnar>::into_owned(self.#name) == *#name } }
+            });
+            let (params, bounds): (Vec<_>, Vec<_>) = reference_types.iter().zip(types.iter()).zip(field_traits.iter()).zip(recursions.iter()).filter(|(_, recursion)| **recursion == Recursion::None).map(|(((r, ty), traits), _)| {
+                if reference { (quote! { }, quote! { #ty: #traits + PartialEq, }) }
+                else { (quote! { #r, }, quote! { #r: PartialEq<#ty>, }) }
+            }).unzip();
+            let lifetime = if reference { quote! { 'columnar, } } else { quote! { } };
+            let struct_params = where_clause.map(|clause| clause.predicates.iter().collect::<Vec<_>>()).unwrap_or_default();
+            quote! {
+                impl < #lifetime #(#struct_generics,)* #(#params)* #(#link_types,)* Nodes > PartialEq<#name #ty_gen> for #r_ident < #(#field_types),* >
+                where
+                    Nodes: ::columnar::Index<Ref = Self> + Copy,
+                    #(#link_types: ::columnar::recursive::Links,)*
+                    #(#bounds)*
+                    #(#struct_params),*
+                {
+                    #[inline(always)]
+                    fn eq(&self, other: &#name #ty_gen) -> bool {
+                        #destructure_self
+                        #(#fields) &&*
+                    }
+                }
+            }
+        }
+        else if attr.reference("PartialEq") {
+            // References are compared with owned values by converting each field to its owned type.
+            let field_refs = types.iter().zip(packed.iter()).map(|(ty, packed)| {
+                if *packed { quote! { #ty } }
+                else { quote! { ::columnar::Ref<'columnar, #ty> } }
+            });
+            let into_owned = types.iter().zip(packed.iter()).map(|(ty, packed)| {
+                if *packed { quote! { ::columnar::packed::into_owned::<#ty> } }
+                else { quote! { <#ty as ::columnar::Columnar>::into_owned } }
+            });
+            let params = where_clause.map(|clause| clause.predicates.iter().collect::<Vec<_>>()).unwrap_or_default();
+            quote! {
+                impl < 'columnar, #(#struct_generics),* > PartialEq<#name #ty_gen> for #r_ident < #(#field_refs),* > where #(#types: #field_traits + PartialEq,)* #(#params),* {
+                    #[inline(always)]
+                    fn eq(&self, other: &#name #ty_gen) -> bool {
+                        #destructure_self
+                        #(#into_owned(self.#names) == *#names) &&*
+                    }
+                }
+            }
+        }
+        else {
+            let impl_gen = quote! { < #(#struct_generics,)* #(#reference_types),* > };
 
-        // Either use curly braces or parentheses to destructure the item.
-        let destructure_self =
-        if named { quote! { let #name { #(#names),* } = other; } }
-        else     { quote! { let #name ( #(#names),* ) = other; } };
+            let where_clause = quote! { where #(#reference_types: PartialEq<#types>),* };
 
-        quote! {
-            impl #impl_gen PartialEq<#name #ty_gen> for #r_ident < #(#reference_types),* >  #where_clause {
-                #[inline(always)]
-                fn eq(&self, other: &#name #ty_gen) -> bool {
-                    #destructure_self
-                    #(self.#names == *#names) &&*
+            quote! {
+                impl #impl_gen PartialEq<#name #ty_gen> for #r_ident < #(#reference_types),* >  #where_clause {
+                    #[inline(always)]
+                    fn eq(&self, other: &#name #ty_gen) -> bool {
+                        #destructure_self
+                        #(self.#names == *#names) &&*
+                    }
                 }
             }
         }
-
     };
 
-    let push_own = {
+    let debug_hash = reference_traits(name, generics, &attr);
+
+    // Recursive types are pushed by reference, as their children are.
+    let push_own = if recursive { quote! { } } else {
         let (_impl_gen, ty_gen, _where_clause) = generics.split_for_impl();
         let push = names.iter().map(|name| { quote! { self.#name.push(#name); } });
 
@@ -154,10 +544,7 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
 
         let where_clause2 = quote! { where #(#container_types: ::columnar::Push<#types>),* };
 
-        // Either use curly braces or parentheses to destructure the item.
-        let destructure_self =
-        if named { quote! { let #name { #(#names),* } = item; } }
-        else     { quote! { let #name ( #(#names),* ) = item; } };
+        let destructure_self = quote! { let #destructure = item; };
 
         quote! {
             impl #impl_gen ::columnar::Push<#name #ty_gen> for #c_ident < #(#container_types),* >  #where_clause2 {
@@ -172,22 +559,38 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
 
     let push_ref = {
         let (_impl_gen, ty_gen, _where_clause) = generics.split_for_impl();
-        let push = names.iter().map(|name| { quote! { self.#name.push(#name); } });
+        // Children are pushed first, and recursive fields then hold their links.
+        let links = names.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(name, recursion)| {
+            let push = recursion.push_owned(name);
+            quote! { let #name = #push; }
+        });
+        let push = names.iter().zip(recursions.iter()).map(|(name, recursion)| {
+            if *recursion == Recursion::None { quote! { self.#name.push(#name); } }
+            else { recursion.push_links(quote! { self.#name }, name) }
+        });
 
         let struct_generics = generics.params.iter();
         let impl_gen = quote! { < 'columnar, #(#struct_generics,)* #(#container_types),* > };
 
-        let where_clause2 = quote! { where #(#container_types: ::columnar::Push<&'columnar #types>),* };
+        let bounds = types.iter().zip(recursions.iter()).map(|(ty, recursion)| {
+            if *recursion == Recursion::None { quote! { ::columnar::Push<&'columnar #ty> } }
+            else { recursion.push_bound() }
+        });
+        let where_clause2 = if recursive {
+            quote! { where #(#container_types: #bounds + ::columnar::Len),* }
+        }
+        else {
+            quote! { where #(#container_types: #bounds),* }
+        };
 
-        let destructure_self =
-        if named { quote! { let #name { #(#names),* } = item; } }
-        else     { quote! { let #name ( #(#names),* ) = item; } };
+        let destructure_self = quote! { let #destructure = item; };
 
         quote! {
             impl #impl_gen ::columnar::Push<&'columnar #name #ty_gen> for #c_ident < #(#container_types),* >  #where_clause2 {
                 #[inline]
                 fn push(&mut self, item: &'columnar #name #ty_gen) {
                     #destructure_self
+                    #(#links)*
                     #(#push)*
                 }
             }
@@ -195,7 +598,57 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
     };
 
     // Implementation of `Push<#r_ident>`
-    let push_new = {
+    let push_new = if recursive {
+
+        // The children of references are nodes of a `Nodes`, whose references are again of this type.
+        let reference_types = &names.iter().enumerate().map(|(index, name)| {
+            let new_name = format!("R{}", index);
+            syn::Ident::new(&new_name, name.span())
+        }).collect::<Vec<_>>();
+        let link_types = reference_types.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion == Recursion::Vec).map(|(ty, _)| ty).collect::<Vec<_>>();
+        let reference_params = reference_types.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion == Recursion::None).map(|(ty, _)| ty).chain(link_types.iter().copied());
+
+        let field_types = reference_types.iter().zip(recursions.iter()).map(|(ty, recursion)| {
+            if *recursion == Recursion::None { quote! { #ty } }
+            else { recursion.reference(quote! { Nodes }, quote! { #ty }) }
+        });
+        let index_type = quote! { #r_ident < #(#field_types,)* > };
+
+        let links = names.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(name, recursion)| {
+            let push = recursion.push_reference(name);
+            quote! { let #name = #push; }
+        });
+        let push = names.iter().zip(recursions.iter()).map(|(name, recursion)| {
+            if *recursion == Recursion::None { quote! { self.#name.push(#name); } }
+            else { recursion.push_links(quote! { self.#name }, name) }
+        });
+
+        let bounds = reference_types.iter().zip(recursions.iter()).map(|(ty, recursion)| {
+            if *recursion == Recursion::None { quote! { ::columnar::Push<#ty> } }
+            else { recursion.push_bound() }
+        });
+
+        let impl_gen = quote! { < #(#container_types,)* #(#reference_params,)* Nodes > };
+
+        let where_clause = quote! {
+            where #(#container_types: #bounds + ::columnar::Len,)*
+                  Nodes: ::columnar::Index<Ref = #index_type> + Copy,
+                  #(#link_types: ::columnar::recursive::Links,)*
+        };
+
+        let destructure_self = quote! { let #r_ident { #(#names),* } = item; };
+
+        quote! {
+            impl #impl_gen ::columnar::Push<#index_type> for #c_ident < #(#container_types),* > #where_clause {
+                #[inline]
+                fn push(&mut self, item: #index_type) {
+                    #destructure_self
+                    #(#links)*
+                    #(#push)*
+                }
+            }
+        }
+    } else {
 
         let reference_types = &names.iter().enumerate().map(|(index, name)| {
             let new_name = format!("R{}", index);
@@ -222,19 +675,34 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
         }
     };
 
+    // References to recursive types hold a copy of the nodes, to find the children their links point to.
     let index_own = {
         let impl_gen = quote! { < #(#container_types),* > };
         let ty_gen = quote! { < #(#container_types),* > };
-        let where_clause = quote! { where #(#container_types: ::columnar::Index),* };
+        let where_clause = if recursive {
+            let links = container_types.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(ty, _)| ty);
+            quote! { where #(#container_types: ::columnar::Index + Copy,)* #(<#links as ::columnar::Index>::Ref: ::columnar::recursive::Links,)* }
+        }
+        else {
+            quote! { where #(#container_types: ::columnar::Index),* }
+        };
 
-        let index_type = quote! { #r_ident < #(<#container_types as ::columnar::Index>::Ref,)* > };
+        let field_types = container_types.iter().zip(recursions.iter()).map(|(ty, recursion)| {
+            if *recursion == Recursion::None { quote! { <#ty as ::columnar::Index>::Ref } }
+            else { recursion.reference(quote! { Self }, quote! { <#ty as ::columnar::Index>::Ref }) }
+        });
+        let index_type = quote! { #r_ident < #(#field_types,)* > };
+        let fields = names.iter().zip(recursions.iter()).map(|(name, recursion)| {
+            if *recursion == Recursion::None { quote! { self.#name.get(index) } }
+            else { recursion.get(quote! { *self }, quote! { self.#name.get(index) }) }
+        });
 
         quote! {
             impl #impl_gen ::columnar::Index for #c_ident #ty_gen #where_clause {
                 type Ref = #index_type;
                 #[inline(always)]
                 fn get(&self, index: usize) -> Self::Ref {
-                    #r_ident { #(#names: self.#names.get(index),)* }
+                    #r_ident { #(#names: #fields,)* }
                 }
             }
         }
@@ -243,16 +711,30 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
     let index_ref = {
         let impl_gen = quote! { < 'columnar, #(#container_types),* > };
         let ty_gen = quote! { < #(#container_types),* > };
-        let where_clause = quote! { where #(&'columnar #container_types: ::columnar::Index),* };
+        let where_clause = if recursive {
+            let links = container_types.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(ty, _)| ty);
+            quote! { where #(&'columnar #container_types: ::columnar::Index,)* #(<&'columnar #links as ::columnar::Index>::Ref: ::columnar::recursive::Links,)* }
+        }
+        else {
+            quote! { where #(&'columnar #container_types: ::columnar::Index),* }
+        };
 
-        let index_type = quote! { #r_ident < #(<&'columnar #container_types as ::columnar::Index>::Ref,)* > };
+        let field_types = container_types.iter().zip(recursions.iter()).map(|(ty, recursion)| {
+            if *recursion == Recursion::None { quote! { <&'columnar #ty as ::columnar::Index>::Ref } }
+            else { recursion.reference(quote! { &'columnar #c_ident #ty_gen }, quote! { <&'columnar #ty as ::columnar::Index>::Ref }) }
+        });
+        let index_type = quote! { #r_ident < #(#field_types,)* > };
+        let fields = names.iter().zip(recursions.iter()).map(|(name, recursion)| {
+            if *recursion == Recursion::None { quote! { (&self.#name).get(index) } }
+            else { recursion.get(quote! { *self }, quote! { (&self.#name).get(index) }) }
+        });
 
         quote! {
             impl #impl_gen ::columnar::Index for &'columnar #c_ident #ty_gen #where_clause {
                 type Ref = #index_type;
                 #[inline(always)]
                 fn get(&self, index: usize) -> Self::Ref {
-                    #r_ident { #(#names: (&self.#names).get(index),)* }
+                    #r_ident { #(#names: #fields,)* }
                 }
             }
         }
@@ -319,7 +801,26 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
 
         let impl_gen = quote! { < 'columnar, #(#container_types),* > };
         let ty_gen = quote! { < #(#container_types),* > };
-        let where_clause = quote! { where #(#container_types: ::columnar::FromBytes<'columnar>),* };
+
+        // The links of recursive types must point to earlier nodes, so that navigation cannot loop.
+        let links = &container_types.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(ty, _)| ty).collect::<Vec<_>>();
+        let link_names = names.iter().zip(recursions.iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(name, _)| name);
+        let where_clause = if recursive {
+            quote! { where #(#container_types: ::columnar::FromBytes<'columnar>,)* #(#links: ::columnar::Index, <#links as ::columnar::Index>::Ref: ::columnar::recursive::Links,)* }
+        }
+        else {
+            quote! { where #(#container_types: ::columnar::FromBytes<'columnar>),* }
+        };
+        let validate = if recursive {
+            quote! {
+                let len = ::columnar::common::validate_lengths(stringify!(#c_ident), &[#(::columnar::FromBytes::validate_deep(&self.#names)?),*])?;
+                #( ::columnar::recursive::validate_links(stringify!(#c_ident), &self.#link_names, len)?; )*
+                Ok(len)
+            }
+        }
+        else {
+            quote! { ::columnar::common::validate_lengths(stringify!(#c_ident), &[#(::columnar::FromBytes::validate_deep(&self.#names)?),*]) }
+        };
 
         quote! {
             impl #impl_gen ::columnar::FromBytes<'columnar> for #c_ident #ty_gen #where_clause {
@@ -336,48 +837,230 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
                     #(<#container_types>::element_sizes(sizes)?;)*
                     Ok(())
                 }
+                fn validate_deep(&self) -> ::core::result::Result<usize, String> {
+                    #validate
+                }
+            }
+        }
+    };
+
+    let schema = {
+
+        quote! {
+            impl < #(#container_types: ::columnar::schema::Schema),* > ::columnar::schema::Schema for #c_ident < #(#container_types),* > {
+                fn schema() -> ::columnar::schema::Node {
+                    ::columnar::schema::Node::structure(stringify!(#name), [#((#labels, <#container_types as ::columnar::schema::Schema>::schema())),*])
+                }
+            }
+        }
+    };
+
+    #[cfg(feature = "arrow")]
+    let arrow = {
+
+        let indices = 0 .. names.len();
+        let first_name = &names[0];
+
+        quote! {
+            impl < #(#container_types: ::columnar::arrow::IntoArrow + ::columnar::Len),* > ::columnar::arrow::IntoArrow for #c_ident < #(#container_types),* > {
+                fn data_type() -> ::columnar::arrow::DataType {
+                    ::columnar::arrow::struct_type([#((#labels, <#container_types as ::columnar::arrow::IntoArrow>::data_type())),*])
+                }
+                fn into_arrow(self) -> ::core::result::Result<::columnar::arrow::ArrayData, String> {
+                    let len = ::columnar::Len::len(&self.#first_name);
+                    ::columnar::arrow::structure(len, [#((#labels, ::columnar::arrow::IntoArrow::into_arrow(self.#names))),*])
+                }
+            }
+            impl < #(#container_types: ::columnar::arrow::FromArrow),* > ::columnar::arrow::FromArrow for #c_ident < #(#container_types),* > {
+                fn from_arrow(data: &::columnar::arrow::ArrayData) -> ::core::result::Result<Self, String> {
+                    let fields = ::columnar::arrow::struct_fields(data, &[#(#labels),*])?;
+                    Ok(Self { #(#names: ::columnar::arrow::FromArrow::from_arrow(&fields[#indices])?,)* })
+                }
+            }
+        }
+    };
+    #[cfg(not(feature = "arrow"))]
+    let arrow = quote! { };
+
+    #[cfg(feature = "parquet")]
+    let parquet = {
+
+        quote! {
+            impl < #(#container_types: ::columnar::parquet::Shred),* > ::columnar::parquet::Shred for #c_ident < #(#container_types),* > {
+                fn parquet_type(name: &str, repetition: ::columnar::parquet::Repetition) -> ::columnar::parquet::TypePtr {
+                    ::columnar::parquet::group(name, repetition, None, [#(<#container_types as ::columnar::parquet::Shred>::parquet_type(#labels, ::columnar::parquet::Repetition::REQUIRED)),*])
+                }
+                fn shred(&self, slots: &[::columnar::parquet::Slot], definition: i16, repetition: i16, columns: &mut Vec<::columnar::parquet::Column>) -> ::core::result::Result<(), String> {
+                    #( ::columnar::parquet::Shred::shred(&self.#names, slots, definition, repetition, columns)?; )*
+                    Ok(())
+                }
             }
         }
     };
+    #[cfg(not(feature = "parquet"))]
+    let parquet = quote! { };
 
     let columnar_impl = {
 
         let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
 
+        // Fields may name their container, which must present the same references as the field type's container.
+        let field_containers = types.iter().zip(attrs.iter()).zip(recursions.iter()).map(|((ty, attrs), recursion)| {
+            if *recursion == Recursion::None { attrs.container(ty) }
+            else { recursion.container() }
+        }).collect::<Vec<_>>();
+
+        // Recursive fields are not bound, as their bounds would require the bounds being established.
+        let (bound_types, bound_traits): (Vec<&&syn::Type>, Vec<&proc_macro2::TokenStream>) = types.iter().zip(field_traits.iter()).zip(recursions.iter()).filter(|(_, recursion)| **recursion == Recursion::None).map(|(pair, _)| pair).unzip();
+
+        // Skipped fields without a named function are reconstructed by `Default`.
+        let skip_defaults = &all_attrs.iter().zip(data_struct.fields.iter()).filter(|(attrs, _)| attrs.skip && attrs.skip_with.is_none()).map(|(_, field)| &field.ty).collect::<Vec<_>>();
+
         let where_clause2 = if let Some(struct_where) = where_clause {
             let params = struct_where.predicates.iter();
-            quote! {  where #(#types : ::columnar::Columnar,)* #(#params),* }
+            quote! {  where #(#bound_types : #bound_traits,)* #(#skip_defaults : Default,)* #(#params),* }
         }
         else {
-            quote! { where #(#types : ::columnar::Columnar,)* }
+            quote! { where #(#bound_types : #bound_traits,)* #(#skip_defaults : Default,)* }
+        };
+
+        // Fields marked `default` or `added_in = N` may be absent from data written by earlier versions.
+        let evolving = attrs.iter().enumerate().filter(|(_, attrs)| attrs.default || attrs.added_in.is_some()).map(|(index, _)| index).collect::<Vec<_>>();
+        let version = attrs.iter().filter_map(|attrs| attrs.added_in).max().unwrap_or(0);
+        let evolve_labels = evolving.iter().map(|index| &labels[*index]);
+        let evolve_conditions = evolving.iter().map(|index| {
+            match attrs[*index].added_in {
+                Some(added_in) if !attrs[*index].default => quote! { version < #added_in },
+                _ => quote! { true },
+            }
+        });
+        let evolve_containers = evolving.iter().map(|index| &field_containers[*index]);
+        let evolve_types = &evolving.iter().map(|index| types[*index]).collect::<Vec<_>>();
+        let where_evolve = if let Some(struct_where) = where_clause {
+            let params = struct_where.predicates.iter();
+            quote! {  where #(#bound_types : #bound_traits,)* #(#skip_defaults : Default,)* #(#evolve_types : Default,)* #(#params),* }
+        }
+        else {
+            quote! { where #(#bound_types : #bound_traits,)* #(#skip_defaults : Default,)* #(#evolve_types : Default,)* }
+        };
+
+        // Skipped fields are reset by `copy_from`, through their members rather than bindings that could shadow functions.
+        let skipped_members = &data_struct.fields.members().zip(all_attrs.iter()).filter(|(_, attrs)| attrs.skip).map(|(member, _)| member).collect::<Vec<_>>();
+        let skipped_values = &all_attrs.iter().filter(|attrs| attrs.skip).map(|attrs| attrs.skipped_value()).collect::<Vec<_>>();
+
+        let destructure_self = quote! { let #destructure = self; };
+
+        // Stored fields are converted from the reference, and skipped fields reconstructed.
+        let owned_fields = all_names.iter().zip(all_attrs.iter()).map(|(name, attrs)| {
+            if attrs.skip { attrs.skipped_value() }
+            else {
+                let position = names.iter().position(|n| n == name).unwrap();
+                if recursions[position] != Recursion::None { recursions[position].owned(quote! { other.#name }) }
+                else {
+                    let path = &field_paths[position];
+                    quote! { #path::into_owned(other.#name) }
+                }
+            }
+        });
+        let copy_fields = names.iter().zip(field_paths.iter()).zip(recursions.iter()).map(|((name, path), recursion)| {
+            if *recursion == Recursion::None { quote! { #path::copy_from(#name, other.#name); } }
+            else {
+                let owned = recursion.owned(quote! { other.#name });
+                quote! { *#name = #owned; }
+            }
+        });
+
+        let container = if recursive {
+            quote! { ::columnar::recursive::Recursive<#c_ident < #(#field_containers),* >> }
+        }
+        else {
+            quote! { #c_ident < #(#field_containers),* > }
         };
 
-        // Either use curly braces or parentheses to destructure the item.
-        let destructure_self =
-        if named { quote! { let #name { #(#names),* } = self; } }
-        else     { quote! { let #name ( #(#names),* ) = self; } };
+        // The references of recursive fields hold the borrowed nodes, and their links.
+        let borrow_bounds = recursions.iter().map(|recursion| {
+            if *recursion == Recursion::None { quote! { ::columnar::Borrow } }
+            else { quote! { for<'a> ::columnar::Borrow<Ref<'a>: ::columnar::recursive::Links> } }
+        }).collect::<Vec<_>>();
+        let borrowed_nodes = quote! { #c_ident < #(<#container_types as ::columnar::Borrow>::Borrowed<'a> ),* > };
+        let ref_types = container_types.iter().zip(recursions.iter()).map(|(ty, recursion)| {
+            if *recursion == Recursion::None { quote! { <#ty as ::columnar::Borrow>::Ref<'a> } }
+            else { recursion.reference(borrowed_nodes.clone(), quote! { <#ty as ::columnar::Borrow>::Ref<'a> }) }
+        });
+        let reborrow_refs = names.iter().zip(container_types.iter()).zip(recursions.iter()).map(|((name, ty), recursion)| {
+            if *recursion == Recursion::None { quote! { <#ty as ::columnar::Borrow>::reborrow_ref(thing.#name) } }
+            else { recursion.reborrow(quote! { thing.#name }, ty) }
+        });
 
-        // Either use curly braces or parentheses to destructure the item.
+        // Nodes of recursive types are copied by pushing references, which pushes their children.
+        let container_impl = if recursive {
+            let bounds = recursions.iter().map(|recursion| {
+                if *recursion == Recursion::None { quote! { } }
+                else {
+                    let push = recursion.push_bound();
+                    quote! { + for<'a> ::columnar::Borrow<Ref<'a>: ::columnar::recursive::Links> + #push }
+                }
+            });
+            quote! {
+                impl < #( #container_types: ::columnar::Container + ::columnar::Len #bounds ),* > ::columnar::Container for #c_ident < #( #container_types ),* > {
+                    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+                        #( self.#names.reserve_for(selves.clone().map(|x| x.#names)); )*
+                    }
+                }
+            }
+        }
+        else {
+            quote! {
+                impl < #( #container_types: ::columnar::Container ),* > ::columnar::Container for #c_ident < #( #container_types ),* > {
+                    #[inline(always)]
+                    fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: std::ops::Range<usize>) {
+                        #( self.#names.extend_from_self(other.#names, range.clone()); )*
+                    }
+                    #[inline(always)]
+                    fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+                        #( self.#names.gather(other.#names, indices); )*
+                    }
+
+                    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+                        #( self.#names.reserve_for(selves.clone().map(|x| x.#names)); )*
+                    }
+                }
+            }
+        };
         let into_self =
-        if named { quote! { #name { #(#names: ::columnar::Columnar::into_owned(other.#names)),* } } }
-        else     { quote! { #name ( #(::columnar::Columnar::into_owned(other.#names)),* ) } };
+        if named { quote! { #name { #(#all_names: #owned_fields),* } } }
+        else     { quote! { #name ( #(#owned_fields),* ) } };
 
         quote! {
             impl #impl_gen ::columnar::Columnar for #name #ty_gen #where_clause2 {
                 #[inline(always)]
                 fn copy_from<'a>(&mut self, other: ::columnar::Ref<'a, Self>) {
                     #destructure_self
-                    #( ::columnar::Columnar::copy_from(#names, other.#names); )*
+                    #( #copy_fields )*
+                    #( self.#skipped_members = #skipped_values; )*
                 }
                 #[inline(always)]
                 fn into_owned<'a>(other: ::columnar::Ref<'a, Self>) -> Self {
                     #into_self
                 }
-                type Container = #c_ident < #(<#types as ::columnar::Columnar>::Container ),* >;
+                type Container = #container;
             }
 
-            impl < #( #container_types: ::columnar::Borrow ),* > ::columnar::Borrow for #c_ident < #( #container_types ),* > {
-                type Ref<'a> = #r_ident < #(<#container_types as ::columnar::Borrow>::Ref<'a>,)* > where #(#container_types: 'a,)*;
+            impl #impl_gen ::columnar::schema::Evolve for #name #ty_gen #where_evolve {
+                const VERSION: u64 = #version;
+                #[allow(unused_variables)]
+                fn default_field(field: &str, version: u64, len: usize) -> ::core::option::Option<Vec<u64>> {
+                    #(
+                        if field == #evolve_labels && #evolve_conditions {
+                            return ::core::option::Option::Some(::columnar::schema::defaults::<#evolve_containers, #evolve_types>(len));
+                        }
+                    )*
+                    ::core::option::Option::None
+                }
+            }
+
+            impl < #( #container_types: #borrow_bounds ),* > ::columnar::Borrow for #c_ident < #( #container_types ),* > {
+                type Ref<'a> = #r_ident < #(#ref_types,)* > where #(#container_types: 'a,)*;
                 type Borrowed<'a> = #c_ident < #(<#container_types as ::columnar::Borrow>::Borrowed<'a> ),* > where #(#container_types: 'a,)*;
                 #[inline(always)]
                 fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
@@ -394,21 +1077,12 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
                 #[inline(always)]
                 fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> {
                     #r_ident {
-                        #( #names: <#container_types as ::columnar::Borrow>::reborrow_ref(thing.#names), )*
+                        #( #names: #reborrow_refs, )*
                     }
                 }
             }
 
-            impl < #( #container_types: ::columnar::Container ),* > ::columnar::Container for #c_ident < #( #container_types ),* > {
-                #[inline(always)]
-                fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: std::ops::Range<usize>) {
-                    #( self.#names.extend_from_self(other.#names, range.clone()); )*
-                }
-
-                fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
-                    #( self.#names.reserve_for(selves.clone().map(|x| x.#names)); )*
-                }
-            }
+            #container_impl
         }
     };
 
@@ -419,6 +1093,7 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
         #reference_struct
 
         #partial_eq
+        #debug_hash
 
         #push_own
         #push_ref
@@ -431,19 +1106,57 @@ fn derive_struct(name: &syn::Ident, generics: &syn::Generics, data_struct: syn::
 
         #as_bytes
         #from_bytes
+        #schema
+        #arrow
+        #parquet
 
         #columnar_impl
 
     }.into()
 }
 
+/// Implementations of `Debug` and `Hash` for the reference type, through the owned type, if asked for.
+///
+/// The reference type for a generic type does not determine the type parameters, and so these are only
+/// supported for types without them.
+fn reference_traits(name: &syn::Ident, generics: &syn::Generics, attr: &TypeAttrs) -> proc_macro2::TokenStream {
+
+    if (attr.reference("Debug") || attr.reference("Hash")) && !generics.params.is_empty() {
+        panic!("`reference(Debug)` and `reference(Hash)` are only supported for types without generic parameters");
+    }
+
+    let debug = if attr.reference("Debug") {
+        quote! {
+            impl<'columnar> ::core::fmt::Debug for ::columnar::Ref<'columnar, #name> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Debug::fmt(&<#name as ::columnar::Columnar>::into_owned(*self), f)
+                }
+            }
+        }
+    }
+    else { quote! {} };
+
+    let hash = if attr.reference("Hash") {
+        quote! {
+            impl<'columnar> ::core::hash::Hash for ::columnar::Ref<'columnar, #name> {
+                fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                    ::core::hash::Hash::hash(&<#name as ::columnar::Columnar>::into_owned(*self), state)
+                }
+            }
+        }
+    }
+    else { quote! {} };
+
+    quote! { #debug #hash }
+}
+
 // TODO: Do we need to use the generics?
-fn derive_unit_struct(name: &syn::Ident, _generics: &syn::Generics, vis: syn::Visibility, attr: Option<proc_macro2::TokenStream>) -> proc_macro::TokenStream {
+fn derive_unit_struct(name: &syn::Ident, _generics: &syn::Generics, vis: syn::Visibility, attr: TypeAttrs) -> proc_macro::TokenStream {
 
     let c_name = format!("{}Container", name);
     let c_ident = syn::Ident::new(&c_name, name.span());
 
-    if attr.is_some() {
+    if !attr.is_empty() {
         panic!("Unit structs do not support attributes");
     }
 
@@ -452,8 +1165,45 @@ fn derive_unit_struct(name: &syn::Ident, _generics: &syn::Generics, vis: syn::Vi
     #[cfg(not(feature = "serde"))]
     let derive = quote! { #[derive(Copy, Clone, Debug, Default)] };
 
+    // Unit structs are Arrow null arrays, as are `()` values.
+    #[cfg(feature = "arrow")]
+    let arrow = quote! {
+        impl<CW: ::columnar::common::index::CopyAs<u64>> ::columnar::arrow::IntoArrow for #c_ident <CW> {
+            fn data_type() -> ::columnar::arrow::DataType { ::columnar::arrow::DataType::Null }
+            fn into_arrow(self) -> ::core::result::Result<::columnar::arrow::ArrayData, String> {
+                ::columnar::arrow::IntoArrow::into_arrow(::columnar::primitive::Empties { count: self.count, empty: () })
+            }
+        }
+        impl ::columnar::arrow::FromArrow for #c_ident {
+            fn from_arrow(data: &::columnar::arrow::ArrayData) -> ::core::result::Result<Self, String> {
+                let empties: ::columnar::primitive::Empties = ::columnar::arrow::FromArrow::from_arrow(data)?;
+                Ok(Self { count: empties.count })
+            }
+        }
+    };
+    #[cfg(not(feature = "arrow"))]
+    let arrow = quote! { };
+
+    // Unit structs are Parquet columns of `true`, as are `()` values.
+    #[cfg(feature = "parquet")]
+    let parquet = quote! {
+        impl<CW> ::columnar::parquet::Shred for #c_ident <CW> {
+            fn parquet_type(name: &str, repetition: ::columnar::parquet::Repetition) -> ::columnar::parquet::TypePtr {
+                <::columnar::primitive::Empties as ::columnar::parquet::Shred>::parquet_type(name, repetition)
+            }
+            fn shred(&self, slots: &[::columnar::parquet::Slot], definition: i16, repetition: i16, columns: &mut Vec<::columnar::parquet::Column>) -> ::core::result::Result<(), String> {
+                ::columnar::parquet::Shred::shred(&::columnar::primitive::Empties::<u64>::default(), slots, definition, repetition, columns)
+            }
+        }
+    };
+    #[cfg(not(feature = "parquet"))]
+    let parquet = quote! { };
+
     quote! {
 
+        #arrow
+        #parquet
+
         /// Derived columnar container for a unit struct.
         #derive
         #vis struct #c_ident<CW = u64> {
@@ -531,6 +1281,15 @@ fn derive_unit_struct(name: &syn::Ident, _generics: &syn::Generics, vis: syn::Vi
                 sizes.push(8);
                 Ok(())
             }
+            fn validate_deep(&self) -> ::core::result::Result<usize, String> {
+                usize::try_from(*self.count).map_err(|_| String::from(concat!(stringify!(#c_ident), ": count does not fit in `usize`")))
+            }
+        }
+
+        impl<CW> ::columnar::schema::Schema for #c_ident <CW> {
+            fn schema() -> ::columnar::schema::Node {
+                ::columnar::schema::Node::container(stringify!(#name), [("count", ::columnar::schema::Node::slice::<u64>())])
+            }
         }
 
         impl ::columnar::Columnar for #name {
@@ -561,6 +1320,10 @@ fn derive_unit_struct(name: &syn::Ident, _generics: &syn::Generics, vis: syn::Vi
             fn extend_from_self(&mut self, _other: Self::Borrowed<'_>, range: std::ops::Range<usize>) {
                 self.count += range.len() as u64;
             }
+            #[inline(always)]
+            fn gather(&mut self, _other: Self::Borrowed<'_>, indices: &[usize]) {
+                self.count += indices.len() as u64;
+            }
 
             fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone { }
         }
@@ -571,12 +1334,19 @@ fn derive_unit_struct(name: &syn::Ident, _generics: &syn::Generics, vis: syn::Vi
 /// The derived container for an `enum` type will be a struct with containers for each field of each variant, plus an offset container and a discriminant container.
 /// Its index `Ref` type will be an enum with parallel variants, each containing the index `Ref` types of the corresponding variant containers.
 #[allow(unused)]
-fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::DataEnum, vis: syn::Visibility, attr: Option<proc_macro2::TokenStream>) -> proc_macro::TokenStream {
+fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::DataEnum, vis: syn::Visibility, attr: TypeAttrs) -> proc_macro::TokenStream {
 
     if data_enum.variants.iter().all(|variant| variant.fields.is_empty()) {
-        return derive_tags(name, generics, data_enum, vis);
+        return derive_tags(name, generics, data_enum, vis, attr);
+    }
+
+    if data_enum.variants.iter().flat_map(|variant| variant.fields.iter()).any(|field| { let attrs = field_attrs(&field.attrs); attrs.packed || attrs.default || attrs.added_in.is_some() || attrs.skip }) {
+        panic!("`#[columnar(packed)]`, `#[columnar(default)]`, `#[columnar(added_in = N)]`, and `#[columnar(skip)]` are only supported on struct fields");
     }
 
+    // Variants with fields that name their containers use a tuple of the field containers.
+    let variant_attrs: Vec<Vec<FieldAttrs>> = data_enum.variants.iter().map(|variant| variant.fields.iter().map(|field| field_attrs(&field.attrs)).collect()).collect();
+
     let c_name = format!("{}Container", name);
     let c_ident = syn::Ident::new(&c_name, name.span());
 
@@ -594,16 +1364,42 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
         ))
         .collect();
 
-    // Bit silly, but to help us fit in a byte and reign in bloat.
-    assert!(variants.len() <= 256, "Too many variants for enum");
+    // The discriminants record variants as tags, which are matched against constants `TAG0`, `TAG1`, ...
+    let (tag, tags, tag_check) = enum_tags(name, &data_enum, attr.repr.as_ref());
+    let tags = &tags;
+    let tag_idents = &(0 .. variants.len()).map(|index| syn::Ident::new(&format!("TAG{}", index), name.span())).collect::<Vec<_>>();
+    let tag_consts = &quote! { #( const #tag_idents: #tag = #tags; )* };
 
     let names = &variants.iter().map(|(ident, _)| ident).collect::<Vec<_>>();
 
-    // Generic type parameters for the containers for the struct fields.
-    let container_types = &names.iter().enumerate().map(|(index, name)| {
-        let new_name = format!("C{}", index);
-        syn::Ident::new(&new_name, name.span())
+    // Fields that hold values of the enum store links to their nodes, all in one container.
+    let recursions: &Vec<Vec<Recursion>> = &variants.iter().map(|(_, types)| types.iter().map(|ty| Recursion::of(name, ty)).collect()).collect();
+    let recursive = recursions.iter().flatten().any(|recursion| *recursion != Recursion::None);
+    if recursions.iter().flatten().zip(variant_attrs.iter().flatten()).any(|(recursion, attrs)| *recursion != Recursion::None && attrs.overrides()) {
+        panic!("Fields holding `Box<Self>`, `Vec<Self>`, or `Option<Box<Self>>` cannot have field attributes");
+    }
+    // Variants with recursive fields are linked, and have a container for each field, to present some fields as children.
+    let linked: &Vec<bool> = &recursions.iter().map(|recursions| recursions.iter().any(|recursion| *recursion != Recursion::None)).collect();
+
+    // Generic type parameters for the containers for the variants, or for each field of linked variants.
+    let field_params: &Vec<Vec<syn::Ident>> = &names.iter().enumerate().map(|(index, name)| {
+        if linked[index] { (0 .. variants[index].1.len()).map(|field| syn::Ident::new(&format!("C{}_{}", index, field), name.span())).collect() }
+        else { vec![syn::Ident::new(&format!("C{}", index), name.span())] }
+    }).collect();
+    let container_params = &field_params.iter().flatten().collect::<Vec<_>>();
+    let container_types = &field_params.iter().map(|params| {
+        if params.len() == 1 { let param = &params[0]; quote! { #param } }
+        else { quote! { (#(#params),*) } }
     }).collect::<Vec<_>>();
+    // The container of a field of a linked variant, which is the container of the variant if it has one field.
+    let field_container = |index: usize, field: usize| {
+        let variant = names[index];
+        if variants[index].1.len() == 1 { quote! { self.#variant } }
+        else {
+            let field = syn::Index::from(field);
+            quote! { self.#variant.#field }
+        }
+    };
 
     #[cfg(feature = "serde")]
     let derive = quote! { #[derive(Copy, Clone, Debug, Default, serde::Serialize, serde::Deserialize)] };
@@ -615,7 +1411,7 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
             /// Derived columnar container for an enum.
             #derive
             #[allow(non_snake_case)]
-            #vis struct #c_ident < #(#container_types,)* CVar = Vec<u8>, COff = Vec<u64>, >{
+            #vis struct #c_ident < #(#container_params,)* CVar = Vec<#tag>, COff = Vec<u64>, >{
                 #(
                     /// Container for #names.
                     pub #names : #container_types,
@@ -635,17 +1431,17 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
         let ty_gen = quote! { < #(#reference_types),* > };
 
-        let attr = if let Some(attr) = attr {
-            quote! { #[#attr] }
-        } else {
-            quote! {}
+        let derive = match &attr.derive {
+            Some(derive) => quote! { #[#derive] },
+            None => quote! {},
         };
-
+        // References print as the owned type when asked, and otherwise as themselves.
+        let debug = if attr.reference("Debug") { quote! {} } else { quote! { Debug } };
 
         quote! {
             /// Reference for an enum.
-            #[derive(Copy, Clone, Debug)]
-            #attr
+            #[derive(Copy, Clone, #debug)]
+            #derive
             #vis enum #r_ident #ty_gen {
                 #(
                     /// Enum variant for #names.
@@ -655,17 +1451,88 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
         }
     };
 
-    let push_own = {
+    let partial_eq = if attr.reference("PartialEq") {
+
+        let (_impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+        let struct_generics = generics.params.iter();
+        let params = where_clause.map(|clause| clause.predicates.iter().collect::<Vec<_>>()).unwrap_or_default();
+        let field_types = variants.iter().zip(recursions.iter()).flat_map(|((_, types), recursions)| types.iter().zip(recursions.iter())).filter(|(_, recursion)| **recursion == Recursion::None).map(|(ty, _)| ty);
+
+        // References are compared with owned values by converting each field to its owned type,
+        // and children of linked variants are compared by this implementation, as references to nodes of a `Nodes`.
+        let link_types = &variants.iter().enumerate().flat_map(|(index, (variant, _))| recursions[index].iter().enumerate().filter(|(_, recursion)| **recursion == Recursion::Vec).map(move |(field, _)| syn::Ident::new(&format!("R{}_{}", index, field), variant.span()))).collect::<Vec<_>>();
+        let variant_refs = variants.iter().enumerate().map(|(index, (variant, types))| {
+            if !linked[index] { quote! { ::columnar::Ref<'columnar, (#(#types),*)> } }
+            else {
+                let fields = types.iter().zip(recursions[index].iter()).enumerate().map(|(field, (ty, recursion))| {
+                    if *recursion == Recursion::None { quote! { ::columnar::Ref<'columnar, #ty> } }
+                    else {
+                        let links = syn::Ident::new(&format!("R{}_{}", index, field), variant.span());
+                        recursion.reference(quote! { Nodes }, quote! { #links })
+                    }
+                });
+                quote! { (#(#fields),*) }
+            }
+        });
+        let nodes = if recursive {
+            quote! { #(#link_types,)* Nodes }
+        }
+        else { quote! { } };
+        let nodes_bounds = if recursive {
+            quote! { Nodes: ::columnar::Index<Ref = Self> + Copy, #(#link_types: ::columnar::recursive::Links,)* }
+        }
+        else { quote! { } };
+        // References of variants are tuples of references to their fields, bound as `r0`, `r1`, ...
+        // and owned variants bind their fields as `t0`, `t1`, ..., in patterns appropriate to the variant.
+        let arms = variants.iter().enumerate().map(|(index, (variant, types))| {
+            let bindings = &(0 .. types.len()).map(|index| syn::Ident::new(&format!("r{}", index), variant.span())).collect::<Vec<_>>();
+            let temp_names = &(0 .. types.len()).map(|index| syn::Ident::new(&format!("t{}", index), variant.span())).collect::<Vec<_>>();
+            let pattern = match &data_enum.variants[index].fields {
+                syn::Fields::Unit => quote! { #name::#variant },
+                syn::Fields::Unnamed(_) => quote! { #name::#variant( #(#temp_names),* ) },
+                syn::Fields::Named(fields) => {
+                    let field_names = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
+                    quote! { #name::#variant { #(#field_names: #temp_names),* } }
+                }
+            };
+            let fields = types.iter().zip(recursions[index].iter()).zip(bindings.iter().zip(temp_names.iter())).map(|((ty, recursion), (binding, temp_name))| {
+                if *recursion == Recursion::None { quote! { <#ty as ::columnar::Columnar>::into_owned(*#binding) == *#temp_name } }
+                else { recursion.eq(quote! { (*#binding) }, temp_name) }
+            });
+            quote! {
+                (#r_ident::#variant((#(#bindings),*)), #pattern) => true #(&& #fields)*,
+            }
+        });
+
+        quote! {
+            impl < 'columnar, #(#struct_generics,)* #nodes > PartialEq<#name #ty_gen> for #r_ident < #(#variant_refs),* > where #nodes_bounds #(#field_types: ::columnar::Columnar + PartialEq,)* #(#params),* {
+                #[allow(unreachable_patterns)]
+                fn eq(&self, other: &#name #ty_gen) -> bool {
+                    match (self, other) {
+                        #(#arms)*
+                        _ => false,
+                    }
+                }
+            }
+        }
+    }
+    else { quote! {} };
+
+    let debug_hash = reference_traits(name, generics, &attr);
+
+    // Recursive types are pushed by reference, as their children are.
+    let push_own = if recursive { quote! { } } else {
 
         let (_impl_gen, ty_gen, _where_clause) = generics.split_for_impl();
 
         let push = variants.iter().enumerate().map(|(index, (variant, types))| {
 
+            let variant_tag = &tags[index];
             match &data_enum.variants[index].fields {
                 syn::Fields::Unit => {
                     quote! {
                         #name::#variant => {
-                            self.indexes.push(#index as u8, self.#variant.len() as u64);
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
                             self.#variant.push(());
                         }
                     }
@@ -678,7 +1545,7 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
                     quote! {
                         #name::#variant( #(#temp_names),* ) => {
-                            self.indexes.push(#index as u8, self.#variant.len() as u64);
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
                             self.#variant.push((#(#temp_names),*));
                         },
                     }
@@ -688,7 +1555,7 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
                     quote! {
                         #name::#variant { #(#field_names),* } => {
-                            self.indexes.push(#index as u8, self.#variant.len() as u64);
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
                             self.#variant.push((#(#field_names),*));
                         },
                     }
@@ -697,14 +1564,14 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
         });
 
         let struct_generics = generics.params.iter();
-        let impl_gen = quote! { < #(#struct_generics,)* #(#container_types),* > };
+        let impl_gen = quote! { < #(#struct_generics,)* #(#container_params),* > };
 
         let push_types = variants.iter().map(|(_, types)| quote! { (#(#types),*) });
 
         let where_clause = quote! { where #(#container_types: ::columnar::Len + ::columnar::Push<#push_types>),* };
 
         quote! {
-            impl #impl_gen ::columnar::Push<#name #ty_gen> for #c_ident < #(#container_types),* > #where_clause {
+            impl #impl_gen ::columnar::Push<#name #ty_gen> for #c_ident < #(#container_params),* > #where_clause {
                 #[inline]
                 fn push(&mut self, item: #name #ty_gen) {
                     match item {
@@ -721,15 +1588,47 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
         let push = variants.iter().enumerate().map(|(index, (variant, types))| {
 
+            let variant_tag = &tags[index];
             match &data_enum.variants[index].fields {
                 syn::Fields::Unit => {
                     quote! {
                         #name::#variant => {
-                            self.indexes.push(#index as u8, self.#variant.len() as u64);
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
                             self.#variant.push(());
                         }
                     }
                 }
+                // Linked variants first push their children, and then push each field to its own container.
+                fields if linked[index] => {
+                    let temp_names = &types.iter().enumerate().map(|(index, _)| {
+                        let new_name = format!("t{}", index);
+                        syn::Ident::new(&new_name, variant.span())
+                    }).collect::<Vec<_>>();
+                    let pattern = match fields {
+                        syn::Fields::Named(fields) => {
+                            let field_names = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
+                            quote! { #name::#variant { #(#field_names: #temp_names),* } }
+                        }
+                        _ => quote! { #name::#variant( #(#temp_names),* ) },
+                    };
+                    let links = temp_names.iter().zip(recursions[index].iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(temp_name, recursion)| {
+                        let push = recursion.push_owned(temp_name);
+                        quote! { let #temp_name = #push; }
+                    });
+                    let push = temp_names.iter().zip(recursions[index].iter()).enumerate().map(|(field, (temp_name, recursion))| {
+                        let container = field_container(index, field);
+                        if *recursion == Recursion::None { quote! { #container.push(#temp_name); } }
+                        else { recursion.push_links(container, temp_name) }
+                    });
+
+                    quote! {
+                        #pattern => {
+                            #(#links)*
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, ::columnar::Len::len(&self.#variant) as u64));
+                            #(#push)*
+                        },
+                    }
+                }
                 syn::Fields::Unnamed(_) => {
                     let temp_names = &types.iter().enumerate().map(|(index, _)| {
                         let new_name = format!("t{}", index);
@@ -738,7 +1637,7 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
                     quote! {
                         #name::#variant( #(#temp_names),* ) => {
-                            self.indexes.push(#index as u8, self.#variant.len() as u64);
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
                             self.#variant.push((#(#temp_names),*));
                         },
                     }
@@ -748,7 +1647,7 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
                     quote! {
                         #name::#variant { #(#field_names),* } => {
-                            self.indexes.push(#index as u8, self.#variant.len() as u64);
+                            ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
                             self.#variant.push((#(#field_names),*));
                         },
                     }
@@ -757,14 +1656,30 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
         });
 
         let struct_generics = generics.params.iter();
-        let impl_gen = quote! { < 'columnar, #(#struct_generics,)* #(#container_types),* > };
+        let impl_gen = quote! { < 'columnar, #(#struct_generics,)* #(#container_params),* > };
 
-        let push_types = variants.iter().map(|(_, types)| quote! { (#(&'columnar #types),*) });
+        // Linked variants bound the container of each field, and the enum container pushes their children.
+        let bounds = variants.iter().enumerate().map(|(index, (_, types))| {
+            if !linked[index] {
+                let container = &container_types[index];
+                quote! { #container: ::columnar::Len + ::columnar::Push<(#(&'columnar #types),*)>, }
+            }
+            else {
+                let bounds = types.iter().zip(recursions[index].iter()).zip(field_params[index].iter()).map(|((ty, recursion), param)| {
+                    if *recursion == Recursion::None { quote! { #param: ::columnar::Len + ::columnar::Push<&'columnar #ty>, } }
+                    else {
+                        let push = recursion.push_bound();
+                        quote! { #param: ::columnar::Len + #push, }
+                    }
+                });
+                quote! { #(#bounds)* }
+            }
+        });
 
-        let where_clause = quote! { where #(#container_types: ::columnar::Len + ::columnar::Push<#push_types>),* };
+        let where_clause = quote! { where #(#bounds)* };
 
         quote! {
-            impl #impl_gen ::columnar::Push<&'columnar #name #ty_gen> for #c_ident < #(#container_types),* > #where_clause {
+            impl #impl_gen ::columnar::Push<&'columnar #name #ty_gen> for #c_ident < #(#container_params),* > #where_clause {
                 #[inline]
                 fn push(&mut self, item: &'columnar #name #ty_gen) {
                     match item {
@@ -783,48 +1698,162 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
             syn::Ident::new(&new_name, name.span())
         }).collect::<Vec<_>>();
 
-        let impl_gen = quote! { < #(#container_types,)* #(#reference_types),* > };
+        // Linked variants present each field as its reference, or as children among nodes of a `Nodes`.
+        let field_references = &variants.iter().enumerate().map(|(index, (variant, types))| {
+            (0 .. types.len()).map(|field| syn::Ident::new(&format!("R{}_{}", index, field), variant.span())).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        let variant_types = variants.iter().enumerate().map(|(index, _)| {
+            if !linked[index] { let reference = &reference_types[index]; quote! { #reference } }
+            else {
+                let fields = field_references[index].iter().zip(recursions[index].iter()).map(|(reference, recursion)| {
+                    if *recursion == Recursion::None { quote! { #reference } }
+                    else { recursion.reference(quote! { Nodes }, quote! { #reference }) }
+                });
+                quote! { (#(#fields),*) }
+            }
+        });
+        let index_type = quote! { #r_ident < #(#variant_types,)* > };
+
+        let reference_params = variants.iter().enumerate().flat_map(|(index, _)| {
+            if !linked[index] { vec![&reference_types[index]] }
+            else { field_references[index].iter().zip(recursions[index].iter()).filter(|(_, recursion)| **recursion == Recursion::None || **recursion == Recursion::Vec).map(|(reference, _)| reference).collect() }
+        });
+        let link_types = variants.iter().enumerate().flat_map(|(index, _)| {
+            field_references[index].iter().zip(recursions[index].iter()).filter(|(_, recursion)| **recursion == Recursion::Vec).map(|(reference, _)| reference)
+        });
+        let nodes = if recursive { quote! { Nodes } } else { quote! { } };
+        let nodes_bounds = if recursive {
+            quote! { Nodes: ::columnar::Index<Ref = #index_type> + Copy, #(#link_types: ::columnar::recursive::Links,)* }
+        }
+        else { quote! { } };
 
-        let where_clause = quote! { where #(#container_types: ::columnar::Len + ::columnar::Push<#reference_types>),* };
+        let bounds = variants.iter().enumerate().map(|(index, _)| {
+            if !linked[index] {
+                let container = &container_types[index];
+                let reference = &reference_types[index];
+                quote! { #container: ::columnar::Len + ::columnar::Push<#reference>, }
+            }
+            else {
+                let bounds = field_references[index].iter().zip(recursions[index].iter()).zip(field_params[index].iter()).map(|((reference, recursion), param)| {
+                    if *recursion == Recursion::None { quote! { #param: ::columnar::Len + ::columnar::Push<#reference>, } }
+                    else {
+                        let push = recursion.push_bound();
+                        quote! { #param: ::columnar::Len + #push, }
+                    }
+                });
+                quote! { #(#bounds)* }
+            }
+        });
 
-        let index_type = quote! { #r_ident < #(#reference_types,)* > };
-        let numbers = (0 .. variants.len());
+        let impl_gen = quote! { < #(#container_params,)* #(#reference_params,)* #nodes > };
+
+        let where_clause = quote! { where #(#bounds)* #nodes_bounds };
+
+        let arms = variants.iter().enumerate().map(|(index, (variant, types))| {
+            let variant_tag = &tags[index];
+            if !linked[index] {
+                quote! {
+                    #r_ident::#variant(x) => {
+                        ::columnar::Push::push(&mut self.indexes, (#variant_tag, self.#variant.len() as u64));
+                        self.#variant.push(x);
+                    },
+                }
+            }
+            else {
+                let temp_names = &(0 .. types.len()).map(|index| syn::Ident::new(&format!("t{}", index), variant.span())).collect::<Vec<_>>();
+                let links = temp_names.iter().zip(recursions[index].iter()).filter(|(_, recursion)| **recursion != Recursion::None).map(|(temp_name, recursion)| {
+                    let push = recursion.push_reference(temp_name);
+                    quote! { let #temp_name = #push; }
+                });
+                let push = temp_names.iter().zip(recursions[index].iter()).enumerate().map(|(field, (temp_name, recursion))| {
+                    let container = field_container(index, field);
+                    if *recursion == Recursion::None { quote! { #container.push(#temp_name); } }
+                    else { recursion.push_links(container, temp_name) }
+                });
+                quote! {
+                    #r_ident::#variant((#(#temp_names),*)) => {
+                        #(#links)*
+                        ::columnar::Push::push(&mut self.indexes, (#variant_tag, ::columnar::Len::len(&self.#variant) as u64));
+                        #(#push)*
+                    },
+                }
+            }
+        });
 
         quote! {
-            impl #impl_gen ::columnar::Push<#index_type> for #c_ident < #(#container_types),* > #where_clause {
+            impl #impl_gen ::columnar::Push<#index_type> for #c_ident < #(#container_params),* > #where_clause {
                 #[inline]
                 fn push(&mut self, item: #index_type) {
                     match item {
-                        #(
-                            #r_ident::#names(x) => {
-                                self.indexes.push(#numbers as u8, self.#names.len() as u64);
-                                self.#names.push(x);
-                            },
-                        )*
+                        #(#arms)*
                     }
                 }
             }
         }
     };
 
+    // The references of linked variants present their fields separately, with children among copies of the nodes.
+    let linked_references = |nodes: proc_macro2::TokenStream, reference: &dyn Fn(&syn::Ident) -> proc_macro2::TokenStream| {
+        variants.iter().enumerate().map(|(index, _)| {
+            if !linked[index] { reference(&field_params[index][0]) }
+            else {
+                let fields = field_params[index].iter().zip(recursions[index].iter()).map(|(param, recursion)| {
+                    if *recursion == Recursion::None { reference(param) }
+                    else { recursion.reference(nodes.clone(), reference(param)) }
+                });
+                quote! { (#(#fields),*) }
+            }
+        }).collect::<Vec<_>>()
+    };
+    // The arms that present the fields of linked variants, whose containers return `field` for offset `offset`.
+    let linked_arms = |nodes: proc_macro2::TokenStream, field: &dyn Fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream| {
+        variants.iter().enumerate().map(|(index, (variant, types))| {
+            let tag = &tag_idents[index];
+            let get = field(quote! { self.#variant });
+            if !linked[index] { quote! { #tag => #r_ident::#variant(#get), } }
+            else {
+                let temp_names = &(0 .. types.len()).map(|index| syn::Ident::new(&format!("t{}", index), variant.span())).collect::<Vec<_>>();
+                let fields = temp_names.iter().zip(recursions[index].iter()).map(|(temp_name, recursion)| {
+                    if *recursion == Recursion::None { quote! { #temp_name } }
+                    else { recursion.get(nodes.clone(), quote! { #temp_name }) }
+                });
+                quote! {
+                    #tag => {
+                        let (#(#temp_names),*) = #get;
+                        #r_ident::#variant((#(#fields),*))
+                    }
+                }
+            }
+        }).collect::<Vec<_>>()
+    };
+    // The containers of recursive fields, whose references must present links.
+    let link_params = &field_params.iter().zip(recursions.iter()).flat_map(|(params, recursions)| params.iter().zip(recursions.iter())).filter(|(_, recursion)| **recursion != Recursion::None).map(|(param, _)| param).collect::<Vec<_>>();
+
     let index_own = {
-        let impl_gen = quote! { < #(#container_types,)* CVar, COff> };
-        let ty_gen = quote! { < #(#container_types,)* CVar, COff> };
-        let where_clause = quote! { where #(#container_types: ::columnar::Index,)* CVar: ::columnar::Len + ::columnar::IndexAs<u8>, COff: ::columnar::Len + ::columnar::IndexAs<u64>  };
+        let impl_gen = quote! { < #(#container_params,)* CVar, COff> };
+        let ty_gen = quote! { < #(#container_params,)* CVar, COff> };
+        let where_clause = if recursive {
+            quote! { where #(#container_params: ::columnar::Index + Copy,)* #(<#link_params as ::columnar::Index>::Ref: ::columnar::recursive::Links,)* CVar: ::columnar::sums::discriminant::Tags<Tag = #tag> + Copy, COff: ::columnar::Len + ::columnar::IndexAs<u64> + Copy }
+        }
+        else {
+            quote! { where #(#container_params: ::columnar::Index,)* CVar: ::columnar::sums::discriminant::Tags<Tag = #tag>, COff: ::columnar::Len + ::columnar::IndexAs<u64>  }
+        };
 
-        let index_type = quote! { #r_ident < #(<#container_types as ::columnar::Index>::Ref,)* > };
+        let variant_types = linked_references(quote! { Self }, &|param| quote! { <#param as ::columnar::Index>::Ref });
+        let index_type = quote! { #r_ident < #(#variant_types,)* > };
+        let arms = linked_arms(quote! { *self }, &|container| quote! { #container.get(offset as usize) });
 
-        // These numbers must match those in the `Push` implementations.
-        let numbers = (0 .. variants.len());
 
         quote! {
             impl #impl_gen ::columnar::Index for #c_ident #ty_gen #where_clause {
                 type Ref = #index_type;
                 #[inline(always)]
                 fn get(&self, index: usize) -> Self::Ref {
+                    #tag_consts
                     let (variant, offset) = self.indexes.get(index);
-                    match variant as usize {
-                        #( #numbers => #r_ident::#names(self.#names.get(offset as usize)), )*
+                    #[allow(unreachable_patterns)]
+                    match variant {
+                        #( #arms )*
                         x => panic!("Unacceptable discriminant found: {:?}", x),
                     }
                 }
@@ -833,23 +1862,30 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
     };
 
     let index_ref = {
-        let impl_gen = quote! { < 'columnar, #(#container_types,)* CVar, COff> };
-        let ty_gen = quote! { < #(#container_types,)* CVar, COff> };
-        let where_clause = quote! { where #(&'columnar #container_types: ::columnar::Index,)* CVar: ::columnar::Len + ::columnar::IndexAs<u8>, COff: ::columnar::Len + ::columnar::IndexAs<u64>  };
+        let impl_gen = quote! { < 'columnar, #(#container_params,)* CVar, COff> };
+        let ty_gen = quote! { < #(#container_params,)* CVar, COff> };
+        let where_clause = if recursive {
+            quote! { where #(&'columnar #container_params: ::columnar::Index,)* #(<&'columnar #link_params as ::columnar::Index>::Ref: ::columnar::recursive::Links,)* CVar: ::columnar::sums::discriminant::Tags<Tag = #tag>, COff: ::columnar::Len + ::columnar::IndexAs<u64>  }
+        }
+        else {
+            quote! { where #(&'columnar #container_params: ::columnar::Index,)* CVar: ::columnar::sums::discriminant::Tags<Tag = #tag>, COff: ::columnar::Len + ::columnar::IndexAs<u64>  }
+        };
 
-        let index_type = quote! { #r_ident < #(<&'columnar #container_types as ::columnar::Index>::Ref,)* > };
+        let variant_types = linked_references(quote! { &'columnar #c_ident #ty_gen }, &|param| quote! { <&'columnar #param as ::columnar::Index>::Ref });
+        let index_type = quote! { #r_ident < #(#variant_types,)* > };
+        let arms = linked_arms(quote! { *self }, &|container| quote! { (&#container).get(offset as usize) });
 
-        // These numbers must match those in the `Push` implementations.
-        let numbers = (0 .. variants.len());
 
         quote! {
             impl #impl_gen ::columnar::Index for &'columnar #c_ident #ty_gen #where_clause {
                 type Ref = #index_type;
                 #[inline(always)]
                 fn get(&self, index: usize) -> Self::Ref {
+                    #tag_consts
                     let (variant, offset) = self.indexes.get(index);
-                    match variant as usize {
-                        #( #numbers => #r_ident::#names((&self.#names).get(offset as usize)), )*
+                    #[allow(unreachable_patterns)]
+                    match variant {
+                        #( #arms )*
                         x => panic!("Unacceptable discriminant found: {:?}", x),
                     }
                 }
@@ -859,8 +1895,8 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
     let clear = {
 
-        let impl_gen = quote! { < #(#container_types),* > };
-        let ty_gen = quote! { < #(#container_types),* > };
+        let impl_gen = quote! { < #(#container_params),* > };
+        let ty_gen = quote! { < #(#container_params),* > };
         let where_clause = quote! { where #(#container_types: ::columnar::Clear),* };
 
         quote! {
@@ -876,8 +1912,8 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
     let length = {
 
-        let impl_gen = quote! { < #(#container_types,)* CVar, COff> };
-        let ty_gen = quote! { < #(#container_types,)* CVar, COff > };
+        let impl_gen = quote! { < #(#container_params,)* CVar, COff> };
+        let ty_gen = quote! { < #(#container_params,)* CVar, COff > };
 
         quote! {
             impl #impl_gen ::columnar::Len for #c_ident #ty_gen where CVar: ::columnar::Len, COff: ::columnar::Len + ::columnar::IndexAs<u64> {
@@ -891,8 +1927,8 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
     let as_bytes = {
 
-        let impl_gen = quote! { < 'a, #(#container_types,)* CVar, COff> };
-        let ty_gen = quote! { < #(#container_types,)* CVar, COff > };
+        let impl_gen = quote! { < 'a, #(#container_params,)* CVar, COff> };
+        let ty_gen = quote! { < #(#container_params,)* CVar, COff > };
         let where_clause = quote! { where #(#container_types: ::columnar::AsBytes<'a>,)* ::columnar::Discriminant<CVar, COff>: ::columnar::AsBytes<'a> };
 
         quote! {
@@ -916,9 +1952,50 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
     let from_bytes = {
 
-        let impl_gen = quote! { < 'columnar, #(#container_types,)* CVar, COff> };
-        let ty_gen = quote! { < #(#container_types,)* CVar, COff > };
-        let where_clause = quote! { where #(#container_types: ::columnar::FromBytes<'columnar>,)* ::columnar::Discriminant<CVar, COff>: ::columnar::FromBytes<'columnar> };
+        // Tags are positions unless discriminants are explicit, in which case each tag must be one of them.
+        let validate = if data_enum.variants.iter().any(|variant| variant.discriminant.is_some()) {
+            quote! { self.indexes.validate_tags(&[#(#tags),*], &[#(::columnar::FromBytes::validate_deep(&self.#names)?),*]) }
+        }
+        else {
+            quote! { self.indexes.validate_variants(&[#(::columnar::FromBytes::validate_deep(&self.#names)?),*]) }
+        };
+
+        // The links of recursive types must point to earlier nodes, so that navigation cannot loop.
+        let validate = if recursive {
+            let checks = variants.iter().enumerate().filter(|(index, _)| linked[*index]).map(|(index, _)| {
+                let variant_tag = &tag_idents[index];
+                let links = recursions[index].iter().enumerate().filter(|(_, recursion)| **recursion != Recursion::None).map(|(field, _)| {
+                    let container = field_container(index, field);
+                    quote! { ::columnar::recursive::validate_node(stringify!(#c_ident), index, ::columnar::Index::get(&#container, offset as usize))?; }
+                });
+                quote! { #variant_tag => { #(#links)* } }
+            });
+            quote! {
+                let len = #validate?;
+                #tag_consts
+                for index in 0 .. len {
+                    let (variant, offset) = self.indexes.get(index);
+                    #[allow(unreachable_patterns)]
+                    match variant {
+                        #(#checks)*
+                        _ => { }
+                    }
+                }
+                Ok(len)
+            }
+        }
+        else { validate };
+
+        let impl_gen = quote! { < 'columnar, #(#container_params,)* CVar, COff> };
+        let ty_gen = quote! { < #(#container_params,)* CVar, COff > };
+        let where_clause = quote! {
+            where
+                #(#container_types: ::columnar::FromBytes<'columnar>,)*
+                #(#link_params: ::columnar::Index, <#link_params as ::columnar::Index>::Ref: ::columnar::recursive::Links,)*
+                ::columnar::Discriminant<CVar, COff>: ::columnar::FromBytes<'columnar>,
+                CVar: ::columnar::sums::discriminant::Tags<Tag = #tag>,
+                COff: ::columnar::Len + ::columnar::IndexAs<u64>,
+        };
 
         quote! {
             #[allow(non_snake_case)]
@@ -943,15 +2020,89 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
                     <::columnar::Discriminant<CVar, COff>>::element_sizes(sizes)?;
                     Ok(())
                 }
+                fn validate_deep(&self) -> ::core::result::Result<usize, String> {
+                    #validate
+                }
+            }
+        }
+    };
+
+    let schema = {
+
+        let impl_gen = quote! { < #(#container_params,)* CVar: ::columnar::schema::Schema, COff: ::columnar::schema::Schema > };
+        let where_clause = quote! { where #(#container_types: ::columnar::schema::Schema),* };
+        let ty_gen = quote! { < #(#container_params,)* CVar, COff > };
+
+        quote! {
+            #[allow(non_snake_case)]
+            impl #impl_gen ::columnar::schema::Schema for #c_ident #ty_gen #where_clause {
+                fn schema() -> ::columnar::schema::Node {
+                    ::columnar::schema::Node::enumeration(
+                        stringify!(#name),
+                        [#((stringify!(#names), <#container_types as ::columnar::schema::Schema>::schema())),*],
+                        <::columnar::Discriminant<CVar, COff> as ::columnar::schema::Schema>::schema(),
+                    )
+                }
+            }
+        }
+    };
+
+    #[cfg(feature = "arrow")]
+    let arrow = {
+
+        let indices = 0 .. names.len();
+
+        quote! {
+            #[allow(non_snake_case)]
+            impl < #(#container_params,)* CVar: ::columnar::sums::discriminant::Tags<Tag = #tag>, COff: ::columnar::Len + ::columnar::IndexAs<u64> > ::columnar::arrow::IntoArrow for #c_ident < #(#container_params,)* CVar, COff > where #(#container_types: ::columnar::arrow::IntoArrow),* {
+                fn data_type() -> ::columnar::arrow::DataType {
+                    ::columnar::arrow::union_type([#((stringify!(#names), <#container_types as ::columnar::arrow::IntoArrow>::data_type())),*])
+                }
+                fn into_arrow(self) -> ::core::result::Result<::columnar::arrow::ArrayData, String> {
+                    ::columnar::arrow::union(&self.indexes, &[#(#tags),*], [#((stringify!(#names), ::columnar::arrow::IntoArrow::into_arrow(self.#names))),*])
+                }
+            }
+            #[allow(non_snake_case)]
+            impl < #(#container_params),* > ::columnar::arrow::FromArrow for #c_ident < #(#container_params),* > where #(#container_types: ::columnar::arrow::FromArrow),* {
+                fn from_arrow(data: &::columnar::arrow::ArrayData) -> ::core::result::Result<Self, String> {
+                    let (variants, indexes) = ::columnar::arrow::union_variants(data, &[#(stringify!(#names)),*], &[#(#tags),*])?;
+                    Ok(Self { #(#names: ::columnar::arrow::FromArrow::from_arrow(&variants[#indices])?,)* indexes })
+                }
+            }
+        }
+    };
+    #[cfg(not(feature = "arrow"))]
+    let arrow = quote! { };
+
+    // Each variant is an optional field, present for the elements of that variant.
+    #[cfg(feature = "parquet")]
+    let parquet = {
+
+        quote! {
+            #[allow(non_snake_case)]
+            impl < #(#container_params,)* CVar: ::columnar::sums::discriminant::Tags<Tag = #tag>, COff: ::columnar::Len + ::columnar::IndexAs<u64> > ::columnar::parquet::Shred for #c_ident < #(#container_params,)* CVar, COff > where #(#container_types: ::columnar::parquet::Shred),* {
+                fn parquet_type(name: &str, repetition: ::columnar::parquet::Repetition) -> ::columnar::parquet::TypePtr {
+                    ::columnar::parquet::group(name, repetition, None, [#(<#container_types as ::columnar::parquet::Shred>::parquet_type(stringify!(#names), ::columnar::parquet::Repetition::OPTIONAL)),*])
+                }
+                fn shred(&self, slots: &[::columnar::parquet::Slot], definition: i16, repetition: i16, columns: &mut Vec<::columnar::parquet::Column>) -> ::core::result::Result<(), String> {
+                    #(
+                        let variant = ::columnar::parquet::variant_slots(&self.indexes, slots, #tags, definition);
+                        ::columnar::parquet::Shred::shred(&self.#names, &variant, definition + 1, repetition, columns)?;
+                    )*
+                    Ok(())
+                }
             }
         }
     };
+    #[cfg(not(feature = "parquet"))]
+    let parquet = quote! { };
 
     let columnar_impl = {
 
         let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
 
-        let types = &variants.iter().flat_map(|(_, types)| types).collect::<Vec<_>>();
+        // Recursive fields are not bound, as their bounds would require the bounds being established.
+        let types = &variants.iter().zip(recursions.iter()).flat_map(|((_, types), recursions)| types.iter().zip(recursions.iter())).filter(|(_, recursion)| **recursion == Recursion::None).map(|(ty, _)| ty).collect::<Vec<_>>();
 
         let where_clause2 = if let Some(enum_where) = where_clause {
             let params = enum_where.predicates.iter();
@@ -964,12 +2115,26 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
         let variant_types = &variants.iter().map(|(_, types)| quote! { (#(#types),*) }).collect::<Vec<_>>();
 
-        let container_types = &variants.iter().map(|(_, types)| quote! { <(#(#types),*) as ::columnar::Columnar>::Container }).collect::<Vec<_>>();
-        // Generic type parameters for the containers for the struct fields.
-        let container_names = &names.iter().enumerate().map(|(index, name)| {
-            let new_name = format!("C{}", index);
-            syn::Ident::new(&new_name, name.span())
+        // Linked variants have a container for each field, and recursive fields store their links.
+        let field_containers = &variants.iter().zip(variant_attrs.iter()).zip(recursions.iter()).flat_map(|(((_, types), attrs), recursions)| {
+            if recursions.iter().any(|recursion| *recursion != Recursion::None) {
+                types.iter().zip(attrs.iter()).zip(recursions.iter()).map(|((ty, attrs), recursion)| {
+                    if *recursion == Recursion::None { attrs.container(ty) }
+                    else { recursion.container() }
+                }).collect::<Vec<_>>()
+            }
+            else if attrs.iter().any(FieldAttrs::overrides) {
+                let field_containers = types.iter().zip(attrs.iter()).map(|(ty, attrs)| attrs.container(ty));
+                vec![quote! { (#(#field_containers),*) }]
+            }
+            else { vec![quote! { <(#(#types),*) as ::columnar::Columnar>::Container }] }
         }).collect::<Vec<_>>();
+        let container = if recursive {
+            quote! { ::columnar::recursive::Recursive<#c_ident < #(#field_containers),* >> }
+        }
+        else {
+            quote! { #c_ident < #(#field_containers),* > }
+        };
 
         let reference_args = variants.iter().map(|(_, types)| quote! { <(#(#types),*) as ::columnar::Columnar>::Ref<'a> });
         let reference_args2 = reference_args.clone();
@@ -983,6 +2148,8 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
                     (_, #r_ident::#variant(_)) => { *self = #name::#variant; }
                 }
             }
+            // Linked variants are rebuilt from their references, as their children are.
+            else if linked[index] { quote! { } }
             else {
                 let temp_names1 = &types.iter().enumerate().map(|(index, _)| {
                     let new_name = format!("s{}", index);
@@ -1021,12 +2188,16 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
                     syn::Ident::new(&new_name, variant.span())
                 }).collect::<Vec<_>>();
 
+                let owned_fields = &temp_names.iter().zip(recursions[index].iter()).map(|(temp_name, recursion)| {
+                    if *recursion == Recursion::None { quote! { ::columnar::Columnar::into_owned(#temp_name) } }
+                    else { recursion.owned(quote! { #temp_name }) }
+                }).collect::<Vec<_>>();
                 let reconstruct = match &data_enum.variants[index].fields {
                     syn::Fields::Named(fields) => {
                         let field_names: Vec<_> = fields.named.iter().map(|f| f.ident.as_ref().unwrap()).collect();
-                        quote! { #name::#variant { #(#field_names: ::columnar::Columnar::into_owned(#temp_names)),* } }
+                        quote! { #name::#variant { #(#field_names: #owned_fields),* } }
                     }
-                    _ => quote! { #name::#variant( #( ::columnar::Columnar::into_owned(#temp_names) ),* ) }
+                    _ => quote! { #name::#variant( #( #owned_fields ),* ) }
                 };
 
                 quote! {
@@ -1038,19 +2209,101 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
         }).collect::<Vec<_>>();
 
         // For each variant, the reborrow case.
-        let reborrow_ref = variants.iter().enumerate().zip(container_names.iter()).map(|((index, (variant, types)), cname)| {
-            quote! {
-                #r_ident::#variant(( potato )) => {
-                    #r_ident::#variant((  < (#cname) as ::columnar::Borrow >::reborrow_ref::<'b, 'a>( potato ) ))
-                },
+        let reborrow_ref = variants.iter().enumerate().zip(container_types.iter()).map(|((index, (variant, types)), cname)| {
+            if linked[index] {
+                let temp_names = &(0 .. types.len()).map(|index| syn::Ident::new(&format!("t{}", index), variant.span())).collect::<Vec<_>>();
+                let fields = temp_names.iter().zip(field_params[index].iter()).zip(recursions[index].iter()).map(|((temp_name, param), recursion)| {
+                    if *recursion == Recursion::None { quote! { <#param as ::columnar::Borrow>::reborrow_ref::<'b, 'a>(#temp_name) } }
+                    else { recursion.reborrow(quote! { #temp_name }, param) }
+                });
+                quote! {
+                    #r_ident::#variant((#(#temp_names),*)) => {
+                        #r_ident::#variant((#(#fields),*))
+                    },
+                }
             }
+            else {
+                quote! {
+                    #r_ident::#variant(( potato )) => {
+                        #r_ident::#variant((  < (#cname) as ::columnar::Borrow >::reborrow_ref::<'b, 'a>( potato ) ))
+                    },
+                }
+            }
+        }).collect::<Vec<_>>();
+
+        // The references of linked variants hold the borrowed nodes, and the links of their recursive fields.
+        let param_recursions = &recursions.iter().zip(linked.iter()).flat_map(|(recursions, linked)| {
+            if *linked { recursions.clone() } else { vec![Recursion::None] }
         }).collect::<Vec<_>>();
+        let borrow_bounds = param_recursions.iter().map(|recursion| {
+            if *recursion == Recursion::None { quote! { ::columnar::Borrow } }
+            else { quote! { for<'a> ::columnar::Borrow<Ref<'a>: ::columnar::recursive::Links> } }
+        });
+        let borrowed_nodes = quote! { #c_ident < #( <#container_params as ::columnar::Borrow>::Borrowed<'a>, )* &'a [#tag], &'a [u64] > };
+        let ref_types = linked_references(borrowed_nodes, &|param| quote! { <#param as ::columnar::Borrow>::Ref<'a> });
 
         // Helper identifiers for `extend_from_self` local variables.
         let len_idents = &names.iter().map(|n| syn::Ident::new(&format!("len_{}", n.to_string().to_lowercase()), n.span())).collect::<Vec<_>>();
         let count_idents = &names.iter().map(|n| syn::Ident::new(&format!("count_{}", n.to_string().to_lowercase()), n.span())).collect::<Vec<_>>();
         let start_idents = &names.iter().map(|n| syn::Ident::new(&format!("start_{}", n.to_string().to_lowercase()), n.span())).collect::<Vec<_>>();
-        let variant_indices = &(0..variants.len()).map(|i| i as u8).collect::<Vec<_>>();
+
+        // Nodes of recursive types are copied by pushing references, which pushes their children.
+        let container_impl = if recursive {
+            let bounds = param_recursions.iter().map(|recursion| {
+                if *recursion == Recursion::None { quote! { } }
+                else {
+                    let push = recursion.push_bound();
+                    quote! { + for<'a> ::columnar::Borrow<Ref<'a>: ::columnar::recursive::Links> + #push }
+                }
+            });
+            quote! {
+                impl < #(#container_params : ::columnar::Container + ::columnar::Len #bounds),* > ::columnar::Container for #c_ident < #(#container_params),* > {
+                    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+                        #( self.#names.reserve_for(selves.clone().map(|x| x.#names)); )*
+                        ::columnar::Container::reserve_for(&mut self.indexes, selves.map(|x| x.indexes));
+                    }
+                }
+            }
+        }
+        else {
+            quote! {
+                impl < #(#container_params : ::columnar::Container + ::columnar::Len),* > ::columnar::Container for #c_ident < #(#container_params),* > {
+                    #[inline(always)]
+                    fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: std::ops::Range<usize>) {
+                        if !range.is_empty() {
+                            #( let #len_idents = ::columnar::Len::len(&self.#names); )*
+                            #( let mut #count_idents = 0usize; )*
+                            #( let mut #start_idents = 0u64; )*
+                            #tag_consts
+                            for index in range.clone() {
+                                let (variant, offset) = other.indexes.get(index);
+                                #[allow(unreachable_patterns)]
+                                match variant {
+                                    #(
+                                        #tag_idents => {
+                                            if #count_idents == 0 { #start_idents = offset; }
+                                            ::columnar::Push::push(&mut self.indexes, (#tag_idents, (#len_idents + #count_idents) as u64));
+                                            #count_idents += 1;
+                                        }
+                                    )*
+                                    _ => unreachable!(),
+                                }
+                            }
+                            #(
+                                if #count_idents > 0 {
+                                    self.#names.extend_from_self(other.#names, #start_idents as usize .. #start_idents as usize + #count_idents);
+                                }
+                            )*
+                        }
+                    }
+
+                    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+                        #( self.#names.reserve_for(selves.clone().map(|x| x.#names)); )*
+                        ::columnar::Container::reserve_for(&mut self.indexes, selves.map(|x| x.indexes));
+                    }
+                }
+            }
+        };
 
         quote! {
             impl #impl_gen ::columnar::Columnar for #name #ty_gen #where_clause2 {
@@ -1067,12 +2320,12 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
                         #( #into_owned )*
                     }
                 }
-                type Container = #c_ident < #(#container_types),* >;
+                type Container = #container;
             }
 
-            impl < #(#container_names : ::columnar::Borrow ),* > ::columnar::Borrow for #c_ident < #(#container_names),* > {
-                type Ref<'a> = #r_ident < #( <#container_names as ::columnar::Borrow>::Ref<'a> ,)* > where Self: 'a, #(#container_names: 'a,)*;
-                type Borrowed<'a> = #c_ident < #( < #container_names as ::columnar::Borrow >::Borrowed<'a>, )* &'a [u8], &'a [u64] > where #(#container_names: 'a,)*;
+            impl < #(#container_params : #borrow_bounds ),* > ::columnar::Borrow for #c_ident < #(#container_params),* > {
+                type Ref<'a> = #r_ident < #( #ref_types ,)* > where Self: 'a, #(#container_params: 'a,)*;
+                type Borrowed<'a> = #c_ident < #( < #container_params as ::columnar::Borrow >::Borrowed<'a>, )* &'a [#tag], &'a [u64] > where #(#container_params: 'a,)*;
                 #[inline(always)]
                 fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
                     #c_ident {
@@ -1083,8 +2336,8 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
                 #[inline(always)]
                 fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> {
                     #c_ident {
-                        #(#names: <#container_names as ::columnar::Borrow>::reborrow(thing.#names),)*
-                        indexes: <::columnar::Discriminant as ::columnar::Borrow>::reborrow(thing.indexes),
+                        #(#names: <#container_types as ::columnar::Borrow>::reborrow(thing.#names),)*
+                        indexes: <::columnar::Discriminant<Vec<#tag>> as ::columnar::Borrow>::reborrow(thing.indexes),
                     }
                 }
                 #[inline(always)]
@@ -1095,54 +2348,21 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
                 }
             }
 
-            impl < #(#container_names : ::columnar::Container + ::columnar::Len),* > ::columnar::Container for #c_ident < #(#container_names),* > {
-                #[inline(always)]
-                fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: std::ops::Range<usize>) {
-                    if !range.is_empty() {
-                        #( let #len_idents = ::columnar::Len::len(&self.#names); )*
-                        #( let mut #count_idents = 0usize; )*
-                        #( let mut #start_idents = 0u64; )*
-                        for index in range.clone() {
-                            let (variant, offset) = other.indexes.get(index);
-                            match variant {
-                                #(
-                                    #variant_indices => {
-                                        if #count_idents == 0 { #start_idents = offset; }
-                                        self.indexes.push(#variant_indices, (#len_idents + #count_idents) as u64);
-                                        #count_idents += 1;
-                                    }
-                                )*
-                                _ => unreachable!(),
-                            }
-                        }
-                        #(
-                            if #count_idents > 0 {
-                                self.#names.extend_from_self(other.#names, #start_idents as usize .. #start_idents as usize + #count_idents);
-                            }
-                        )*
-                    }
-                }
-
-                fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
-                    #( self.#names.reserve_for(selves.clone().map(|x| x.#names)); )*
-                    self.indexes.reserve_for(selves.map(|x| x.indexes));
-                }
-            }
+            #container_impl
         }
     };
 
     let try_unwrap = {
-        let impl_gen = quote! { < #(#container_types),* > };
-        let ty_gen = quote! { < #(#container_types),* > };
+        let impl_gen = quote! { < #(#container_params),* > };
+        let ty_gen = quote! { < #(#container_params),* > };
 
-        let numbers = (0u8 ..);
-        let methods = names.iter().zip(container_types.iter()).zip(numbers).map(|((name, ctype), index)| {
+        let methods = names.iter().zip(container_types.iter()).zip(tags.iter()).map(|((name, ctype), variant_tag)| {
             let try_name = syn::Ident::new(&format!("try_unwrap_{}", name), name.span());
             quote! {
                 /// Returns the #name container if all elements are #name.
                 #[inline]
                 pub fn #try_name(&self) -> Option<&#ctype> {
-                    if self.indexes.homogeneous() == Some(#index) { Some(&self.#name) } else { None }
+                    if self.indexes.homogeneous() == Some(#variant_tag) { Some(&self.#name) } else { None }
                 }
             }
         });
@@ -1160,6 +2380,9 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
         #container_struct
         #reference_struct
 
+        #partial_eq
+        #debug_hash
+
         #push_own
         #push_ref
         #push_new
@@ -1171,17 +2394,86 @@ fn derive_enum(name: &syn::Ident, generics: &syn:: Generics, data_enum: syn::Dat
 
         #as_bytes
         #from_bytes
+        #schema
+        #arrow
+        #parquet
 
         #columnar_impl
 
         #try_unwrap
 
+        #tag_check
+
     }.into()
 }
 
+/// The tag type of an enum, the tag of each of its variants, and checks that the tags fit the type.
+///
+/// Tags are the positions of variants, unless the enum has explicit discriminants, in which case they are
+/// the discriminants, as the bits of the `#[repr]` type. The tag type is the unsigned integer as wide as
+/// a `#[repr]` type of at most 64 bits, or otherwise the narrowest of `u8`, `u16`, and `u32` with room for every variant.
+fn enum_tags(name: &syn::Ident, data_enum: &syn::DataEnum, repr: Option<&syn::Ident>) -> (syn::Ident, Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream) {
+
+    // Discriminant expressions have the `#[repr]` type, or `isize` without one.
+    let discriminant_type = repr.cloned().unwrap_or_else(|| syn::Ident::new("isize", name.span()));
+    // Tags are at most 64 bits, and 128 bit reprs use the tag the variant count would choose.
+    let wide = repr.is_some_and(|repr| repr == "u128" || repr == "i128");
+    let repr = repr.filter(|_| !wide);
+    let tag = match repr.map(|repr| repr.to_string()) {
+        Some(repr) if repr == "u8" || repr == "i8" => "u8",
+        Some(repr) if repr == "u16" || repr == "i16" => "u16",
+        Some(repr) if repr == "u32" || repr == "i32" => "u32",
+        Some(repr) if repr == "u64" || repr == "i64" || repr == "usize" || repr == "isize" => "u64",
+        Some(repr) => panic!("`#[repr({})]` enums are not supported", repr),
+        None if data_enum.variants.len() <= 1 << 8 => "u8",
+        None if data_enum.variants.len() <= 1 << 16 => "u16",
+        None => "u32",
+    };
+    let tag = syn::Ident::new(tag, name.span());
+
+    if data_enum.variants.iter().all(|variant| variant.discriminant.is_none()) {
+        let tags = (0 .. data_enum.variants.len()).map(|index| {
+            let literal = syn::LitInt::new(&format!("{}{}", index, tag), name.span());
+            quote! { #literal }
+        }).collect();
+        return (tag, tags, quote! { });
+    }
+
+    // Variants without explicit discriminants follow the previous variant, as in Rust.
+    let mut explicit = None;
+    let mut offset = 0u64;
+    let values = data_enum.variants.iter().map(|variant| {
+        if let Some((_, expr)) = &variant.discriminant {
+            explicit = Some(expr);
+            offset = 0;
+        }
+        let value = match explicit {
+            Some(expr) => quote! { ({ let value: #discriminant_type = #expr; value } as i128 + #offset as i128) },
+            None => quote! { (#offset as i128) },
+        };
+        offset += 1;
+        value
+    }).collect::<Vec<_>>();
+
+    match repr {
+        // Rust checks that discriminants fit their `#[repr]` type, whose bits are the tags.
+        Some(repr) => (tag.clone(), values.iter().map(|value| quote! { (#value as #repr as #tag) }).collect(), quote! { }),
+        None => {
+            let messages = data_enum.variants.iter().map(|variant| {
+                if wide { format!("the discriminant of `{}::{}` does not fit in a `{}` tag, as wider reprs size tags by their variants", name, variant.ident, tag) }
+                else { format!("the discriminant of `{}::{}` does not fit in a `{}` tag; add a `#[repr]` to choose a wider tag", name, variant.ident, tag) }
+            });
+            let check = quote! {
+                const _: () = { #( assert!(0 <= #values && #values <= #tag::MAX as i128, #messages); )* };
+            };
+            (tag.clone(), values.iter().map(|value| quote! { (#value as #tag) }).collect(), check)
+        }
+    }
+}
+
 /// A derivation for an enum type with no fields in any of its variants.
 #[allow(unused)]
-fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::DataEnum, vis: syn::Visibility) -> proc_macro::TokenStream {
+fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::DataEnum, vis: syn::Visibility, attr: TypeAttrs) -> proc_macro::TokenStream {
 
     let c_name = format!("{}Container", name);
     let c_ident = syn::Ident::new(&c_name, name.span());
@@ -1193,29 +2485,71 @@ fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::Da
         .map(|variant| &variant.ident)
         .collect();
 
-    let indices: &Vec<u8> = &(0 .. names.len()).map(|x| x as u8).collect();
-
-    // Bit silly, but to help us fit in a byte and reign in bloat.
-    assert!(names.len() <= 256, "Too many variants for enum");
+    let (tag, tags, tag_check) = enum_tags(name, &data_enum, attr.repr.as_ref());
+    let tags = &tags;
+    let tag_idents = &(0 .. names.len()).map(|index| syn::Ident::new(&format!("TAG{}", index), name.span())).collect::<Vec<_>>();
+    let tag_consts = &quote! { #( const #tag_idents: #tag = #tags; )* };
 
     #[cfg(feature = "serde")]
     let derive = quote! { #[derive(Copy, Clone, Debug, Default, serde::Serialize, serde::Deserialize)] };
     #[cfg(not(feature = "serde"))]
     let derive = quote! { #[derive(Copy, Clone, Debug, Default)] };
 
+    // All-unit enums are Arrow dense unions of null arrays.
+    #[cfg(feature = "arrow")]
+    let arrow = quote! {
+        impl<CVar: ::columnar::Len + ::columnar::IndexAs<#tag>> ::columnar::arrow::IntoArrow for #c_ident <CVar> {
+            fn data_type() -> ::columnar::arrow::DataType {
+                ::columnar::arrow::union_type([#((stringify!(#names), ::columnar::arrow::DataType::Null)),*])
+            }
+            fn into_arrow(self) -> ::core::result::Result<::columnar::arrow::ArrayData, String> {
+                let variants = (0 .. ::columnar::Len::len(&self.variant)).map(|index| ::columnar::IndexAs::<#tag>::index_as(&self.variant, index));
+                ::columnar::arrow::units(variants, &[#(#tags),*], [#(stringify!(#names)),*])
+            }
+        }
+        impl ::columnar::arrow::FromArrow for #c_ident {
+            fn from_arrow(data: &::columnar::arrow::ArrayData) -> ::core::result::Result<Self, String> {
+                let (_, indexes) = ::columnar::arrow::union_variants(data, &[#(stringify!(#names)),*], &[#(#tags),*])?;
+                let variant = (0 .. ::columnar::Len::len(&indexes)).map(|index| indexes.get(index).0).collect();
+                Ok(Self { variant })
+            }
+        }
+    };
+    #[cfg(not(feature = "arrow"))]
+    let arrow = quote! { };
+
+    // All-unit enums are Parquet `ENUM` columns of variant names.
+    #[cfg(feature = "parquet")]
+    let parquet = quote! {
+        impl<CVar: ::columnar::IndexAs<#tag>> ::columnar::parquet::Shred for #c_ident <CVar> {
+            fn parquet_type(name: &str, repetition: ::columnar::parquet::Repetition) -> ::columnar::parquet::TypePtr {
+                ::columnar::parquet::tags_type(name, repetition)
+            }
+            fn shred(&self, slots: &[::columnar::parquet::Slot], definition: i16, _repetition: i16, columns: &mut Vec<::columnar::parquet::Column>) -> ::core::result::Result<(), String> {
+                columns.push(::columnar::parquet::tags(&self.variant, &[#(#tags),*], &[#(stringify!(#names)),*], slots, definition)?);
+                Ok(())
+            }
+        }
+    };
+    #[cfg(not(feature = "parquet"))]
+    let parquet = quote! { };
+
     quote! {
+        #arrow
+        #parquet
+
         /// Derived columnar container for all-unit enum.
         #derive
-        #vis struct #c_ident <CVar = Vec<u8>> {
+        #vis struct #c_ident <CVar = Vec<#tag>> {
             /// Container for variant.
             pub variant: CVar,
         }
 
-        impl<CV: ::columnar::common::PushIndexAs<u8>> ::columnar::Push<#name> for #c_ident<CV> {
+        impl<CV: ::columnar::common::PushIndexAs<#tag>> ::columnar::Push<#name> for #c_ident<CV> {
             #[inline]
             fn push(&mut self, item: #name) {
                 match item {
-                    #( #name::#names => self.variant.push(&#indices), )*
+                    #( #name::#names => self.variant.push(&#tags), )*
                 }
             }
         }
@@ -1224,28 +2558,32 @@ fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::Da
             #[inline]
             fn push(&mut self, item: &'columnar #name) {
                 match *item {
-                    #( #name::#names => self.variant.push(#indices), )*
+                    #( #name::#names => self.variant.push(#tags), )*
                 }
             }
         }
 
-        impl<CVar: ::columnar::Len + ::columnar::IndexAs<u8>> ::columnar::Index for #c_ident <CVar> {
+        impl<CVar: ::columnar::Len + ::columnar::IndexAs<#tag>> ::columnar::Index for #c_ident <CVar> {
             type Ref = #name;
             #[inline(always)]
             fn get(&self, index: usize) -> Self::Ref {
-                match self.variant.index_as(index) {
-                    #( #indices => #name::#names, )*
+                #tag_consts
+                #[allow(unreachable_patterns)]
+                match ::columnar::IndexAs::<#tag>::index_as(&self.variant, index) {
+                    #( #tag_idents => #name::#names, )*
                     x => panic!("Unacceptable discriminant found: {:?}", x),
                 }
             }
         }
 
-        impl<'columnar, CVar: ::columnar::Len + ::columnar::IndexAs<u8>> ::columnar::Index for &'columnar #c_ident <CVar> {
+        impl<'columnar, CVar: ::columnar::Len + ::columnar::IndexAs<#tag>> ::columnar::Index for &'columnar #c_ident <CVar> {
             type Ref = #name;
             #[inline(always)]
             fn get(&self, index: usize) -> Self::Ref {
-                match self.variant.index_as(index) {
-                    #( #indices => #name::#names, )*
+                #tag_consts
+                #[allow(unreachable_patterns)]
+                match ::columnar::IndexAs::<#tag>::index_as(&self.variant, index) {
+                    #( #tag_idents => #name::#names, )*
                     x => panic!("Unacceptable discriminant found: {:?}", x),
                 }
             }
@@ -1274,7 +2612,7 @@ fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::Da
             }
         }
 
-        impl<'columnar, CVar: ::columnar::FromBytes<'columnar>> ::columnar::FromBytes<'columnar> for #c_ident <CVar> {
+        impl<'columnar, CVar: ::columnar::FromBytes<'columnar> + ::columnar::IndexAs<#tag>> ::columnar::FromBytes<'columnar> for #c_ident <CVar> {
             const SLICE_COUNT: usize = CVar::SLICE_COUNT;
             #[inline(always)]
             fn from_bytes(bytes: &mut impl Iterator<Item=&'columnar [u8]>) -> Self {
@@ -1287,6 +2625,25 @@ fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::Da
             fn element_sizes(sizes: &mut Vec<usize>) -> ::core::result::Result<(), String> {
                 CVar::element_sizes(sizes)
             }
+            fn validate_deep(&self) -> ::core::result::Result<usize, String> {
+                let len = ::columnar::FromBytes::validate_deep(&self.variant)?;
+                #tag_consts
+                #[allow(unreachable_patterns)]
+                if (0 .. len).any(|index| !matches!(<CVar as ::columnar::IndexAs<#tag>>::index_as(&self.variant, index), #(#tag_idents)|*)) {
+                    return Err(String::from(concat!(stringify!(#c_ident), ": discriminant does not name a variant")));
+                }
+                Ok(len)
+            }
+        }
+
+        impl<CVar: ::columnar::schema::Schema> ::columnar::schema::Schema for #c_ident <CVar> {
+            fn schema() -> ::columnar::schema::Node {
+                ::columnar::schema::Node::enumeration(
+                    stringify!(#name),
+                    [#((stringify!(#names), ::columnar::schema::Node::tuple([]))),*],
+                    CVar::schema(),
+                )
+            }
         }
 
         impl ::columnar::Columnar for #name {
@@ -1297,7 +2654,7 @@ fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::Da
             type Container = #c_ident;
         }
 
-        impl<CV: ::columnar::common::BorrowIndexAs<u8>> ::columnar::Borrow for #c_ident <CV> {
+        impl<CV: ::columnar::common::BorrowIndexAs<#tag>> ::columnar::Borrow for #c_ident <CV> {
             type Ref<'a> = #name;
             type Borrowed<'a> = #c_ident < CV::Borrowed<'a> > where CV: 'a;
             #[inline(always)]
@@ -1316,15 +2673,21 @@ fn derive_tags(name: &syn::Ident, _generics: &syn:: Generics, data_enum: syn::Da
             fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> { thing }
         }
 
-        impl<CV: ::columnar::common::PushIndexAs<u8>> ::columnar::Container for #c_ident <CV> {
+        impl<CV: ::columnar::common::PushIndexAs<#tag>> ::columnar::Container for #c_ident <CV> {
             #[inline(always)]
             fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: std::ops::Range<usize>) {
                 self.variant.extend_from_self(other.variant, range);
             }
+            #[inline(always)]
+            fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+                self.variant.gather(other.variant, indices);
+            }
 
             fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
                 self.variant.reserve_for(selves.map(|x| x.variant));
             }
         }
+
+        #tag_check
     }.into()
 }
@@ -211,14 +211,15 @@ fn exp4_tuple5(n: usize, iters: u64) {
 }
 
 fn exp4_tuple8(n: usize, iters: u64) {
-    let mut c: ContainerOf<(u64, u64, u64, u64, u64, u64, u64, u64)> = Default::default();
+    type T8 = (u64, u64, u64, u64, u64, u64, u64, u64);
+    let mut c: ContainerOf<T8> = Default::default();
     for i in 0..n as u64 { c.push(&(i, i+1, i+2, i+3, i+4, i+5, i+6, i+7)); }
     let store = encode_indexed(&c);
     let idx = n / 2;
 
     let ns = bench_ns(iters, || {
         let mut slices = Indexed::decode(&store);
-        type B<'a> = BorrowedOf<'a, (u64, u64, u64, u64, u64, u64, u64, u64)>;
+        type B<'a> = BorrowedOf<'a, T8>;
         let b = B::from_bytes(&mut slices);
         let (_a, _b, _c, _d, _e, _f, _g, h_val) = b.get(idx);
         black_box(h_val);
@@ -310,8 +310,10 @@ fn bench_derived_enum(n: usize, iters: u64) {
 
 // ── Shape 7: Wide tuple — many fields, project to last ──────────────
 
+type Wide = (u64, u64, u64, u64, String, u64, u64, u64);
+
 fn bench_wide_tuple(n: usize, iters: u64) {
-    let mut c: ContainerOf<(u64, u64, u64, u64, String, u64, u64, u64)> = Default::default();
+    let mut c: ContainerOf<Wide> = Default::default();
     for i in 0..n as u64 {
         c.push(&(i, i+1, i+2, i+3, format!("w_{i}"), i+5, i+6, i+7));
     }
@@ -320,11 +322,11 @@ fn bench_wide_tuple(n: usize, iters: u64) {
 
     // Project to the last field (.7), then index.
     let di = bench_ns(iters, || {
-        let borrowed: BorrowedOf<'_, (u64, u64, u64, u64, String, u64, u64, u64)> = decode(&store);
+        let borrowed: BorrowedOf<'_, Wide> = decode(&store);
         black_box(Index::get(&borrowed.7, idx));
     });
 
-    let borrowed: BorrowedOf<'_, (u64, u64, u64, u64, String, u64, u64, u64)> = decode(&store);
+    let borrowed: BorrowedOf<'_, Wide> = decode(&store);
     let io = bench_ns(iters, || {
         black_box(Index::get(&borrowed.7, idx));
     });
@@ -333,12 +335,12 @@ fn bench_wide_tuple(n: usize, iters: u64) {
 
     // Project to the String field (.4).
     let di2 = bench_ns(iters, || {
-        let borrowed: BorrowedOf<'_, (u64, u64, u64, u64, String, u64, u64, u64)> = decode(&store);
+        let borrowed: BorrowedOf<'_, Wide> = decode(&store);
         let s: &[u8] = borrowed.4.get(idx);
         black_box(s);
     });
 
-    let borrowed: BorrowedOf<'_, (u64, u64, u64, u64, String, u64, u64, u64)> = decode(&store);
+    let borrowed: BorrowedOf<'_, Wide> = decode(&store);
     let io2 = bench_ns(iters, || {
         let s: &[u8] = borrowed.4.get(idx);
         black_box(s);
@@ -1,37 +1,608 @@
 //! Adaptive Radix Trees (https://db.in.tum.de/~leis/papers/ART.pdf).
 //!
 //! This ADT represents an unordered collection of byte sequences as a tree.
-//! Like a trie, the paths down the tree correspond to byte sequences, and 
+//! Like a trie, the paths down the tree correspond to byte sequences, and
 //! the membership of a byte sequence is determined by the a viable path.
-use alloc::boxed::Box;
-
-/// An ART node exists in the context of a sequence of bytes, and indicates
-/// the possible options based on the next byte in the sequence.
-pub enum ArtNode {
-    /// Some of the bytes continue to further nodes, but many do not.
-    Some(Box<[(u8, ArtNode)]>),
-    /// Many of the bytes continue to further nodes, although some may not.
-    /// If a node is `None`, it didn't actually go anywhere.
-    /// The representation exists to be more economical than the `Some` variant.
-    /// This is especially true if the associated `ArtNode` size is small, which
-    /// it is not in this case, but will be once we flatten it.
-    Many(Box<[ArtNode; 256]>),
-    /// Indicates that there are no branching points for the next few bytes,
-    /// after which the next node is provided.
-    Path(Box<[u8]>, Box<ArtNode>),
-    /// Nothing to see here.
-    None,
-}
+//!
+//! The tree is flattened into a few containers, one for each kind of node,
+//! and nodes refer to each other with encoded [`ArtIdx`] values. As in the
+//! paper, leaves are expanded lazily: a leaf refers to a complete key, which
+//! is compared against the query once the path has no more branching points.
+use alloc::{vec::Vec, string::String};
 
-/// A mock-up of what you would store for each `ArtNode` above, when columnar.
+use crate::{Borrow, Clear, Container, Index, Len, Push, Strings, Vecs};
+
+/// Nodes with more children than this are stored densely, as `Many`.
+const SOME_LIMIT: usize = 48;
+
+/// A reference to a node in an [`ArtNodes`], encoded in a `u64` by [`ArtIdx::encode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ArtIdx {
+    /// Nothing to see here.
+    None,
+    /// The only key below this point, by its index in `keys`.
+    Leaf(usize),
+    /// A key ends at this point, and other keys continue to a next node (both in `terms`).
+    Term(usize),
+    /// Some of the bytes continue to further nodes, but many do not (in `somes`).
     Some(usize),
+    /// Many of the bytes continue to further nodes, although some may not (in `manys`).
     Many(usize),
+    /// No branching points for the next few bytes, after which the next node is provided (in `paths` and `nexts`).
     Path(usize),
-    None,
 }
 
+impl ArtIdx {
+    /// Encodes the reference as a `u64`, with the kind in the low three bits.
+    #[inline(always)]
+    pub fn encode(self) -> u64 {
+        match self {
+            ArtIdx::None => 0,
+            ArtIdx::Leaf(i) => ((i as u64) << 3) | 1,
+            ArtIdx::Term(i) => ((i as u64) << 3) | 2,
+            ArtIdx::Some(i) => ((i as u64) << 3) | 3,
+            ArtIdx::Many(i) => ((i as u64) << 3) | 4,
+            ArtIdx::Path(i) => ((i as u64) << 3) | 5,
+        }
+    }
+    /// Decodes a reference produced by `encode`. Unrecognized kinds decode as `None`.
+    #[inline(always)]
+    pub fn decode(encoded: u64) -> Self {
+        let index = (encoded >> 3) as usize;
+        match encoded & 7 {
+            1 => ArtIdx::Leaf(index),
+            2 => ArtIdx::Term(index),
+            3 => ArtIdx::Some(index),
+            4 => ArtIdx::Many(index),
+            5 => ArtIdx::Path(index),
+            _ => ArtIdx::None,
+        }
+    }
+}
+
+/// A stand-in for `Vec<BTreeSet<Vec<u8>>>`, where each set is an adaptive radix tree.
+///
+/// The keys of each set are stored sorted and deduplicated in `keys`, and the nodes of
+/// each tree refer to them by index. As keys are sorted, the keys below any node form a
+/// contiguous range, which is how prefix lookups report their results.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ArtNodes<BC = Vec<u64>, TC = Vec<[u64; 2]>, KC = Strings, SC = Vecs<(Vec<u8>, Vec<u64>)>, PC = Vecs<Vec<u8>>> {
+    /// For each set, the encoded reference to its root.
+    pub roots: BC,
+    /// For each set, the cumulative number of keys through that set.
+    pub bounds: BC,
+    /// The keys of all sets, sorted and deduplicated within each set.
+    pub keys: KC,
+    /// For each term node, the index of the key that ends there and the encoded next node.
+    pub terms: TC,
+    /// For each sparse node, its sorted bytes and the encoded child for each.
+    pub somes: SC,
+    /// For each dense node, 256 encoded children, one for each byte.
+    pub manys: BC,
+    /// For each path node, the bytes that must be matched.
+    pub paths: PC,
+    /// For each path node, the encoded node that follows the path.
+    pub nexts: BC,
+}
+
+impl ArtNodes {
+    /// Pushes the set of keys produced by `keys`, which need not be sorted or distinct.
+    pub fn push_keys<I: IntoIterator>(&mut self, keys: I) where I::Item: AsRef<[u8]> {
+        let keys: Vec<I::Item> = keys.into_iter().collect();
+        let mut keys: Vec<&[u8]> = keys.iter().map(|k| k.as_ref()).collect();
+        keys.sort();
+        keys.dedup();
+        self.push_sorted(&keys);
+    }
+    /// Pushes a set of keys that are already sorted and distinct.
+    fn push_sorted(&mut self, keys: &[&[u8]]) {
+        let base = self.keys.len();
+        for key in keys.iter() { self.keys.push(*key); }
+        let root = self.build(keys, base, 0);
+        self.roots.push(root);
+        self.bounds.push(self.keys.len() as u64);
+    }
+    /// Builds the tree for sorted distinct `keys` that agree on their first `depth` bytes.
+    ///
+    /// The keys have indexes starting at `base`, and the result is the encoded root.
+    fn build(&mut self, keys: &[&[u8]], base: usize, depth: usize) -> u64 {
+        if keys.is_empty() { return ArtIdx::None.encode(); }
+        if keys.len() == 1 { return ArtIdx::Leaf(base).encode(); }
+        // Only the first key can end here, as keys are sorted and distinct.
+        if keys[0].len() == depth {
+            let next = self.build(&keys[1..], base + 1, depth);
+            self.terms.push([base as u64, next]);
+            return ArtIdx::Term(self.terms.len() - 1).encode();
+        }
+        // The common prefix of all keys is that of the first and last keys.
+        let first = &keys[0][depth..];
+        let last = &keys[keys.len() - 1][depth..];
+        let common = first.iter().zip(last.iter()).take_while(|(a, b)| a == b).count();
+        if common > 0 {
+            let next = self.build(keys, base, depth + common);
+            self.paths.push(&first[.. common]);
+            self.nexts.push(next);
+            return ArtIdx::Path(self.paths.len() - 1).encode();
+        }
+        // Branch on the byte at `depth`, which all keys have.
+        let mut labels = Vec::new();
+        let mut kids = Vec::new();
+        let mut lower = 0;
+        while lower < keys.len() {
+            let byte = keys[lower][depth];
+            let upper = lower + keys[lower..].partition_point(|k| k[depth] == byte);
+            labels.push(byte);
+            kids.push(self.build(&keys[lower .. upper], base + lower, depth + 1));
+            lower = upper;
+        }
+        if labels.len() <= SOME_LIMIT {
+            self.somes.values.0.extend_from_slice(&labels);
+            self.somes.values.1.extend_from_slice(&kids);
+            self.somes.bounds.push(self.somes.values.0.len() as u64);
+            ArtIdx::Some(self.somes.len() - 1).encode()
+        }
+        else {
+            let mut many = [ArtIdx::None.encode(); 256];
+            for (label, kid) in labels.iter().zip(kids.iter()) {
+                many[*label as usize] = *kid;
+            }
+            self.manys.extend_from_slice(&many);
+            ArtIdx::Many(self.manys.len() / 256 - 1).encode()
+        }
+    }
+}
+
+impl Borrow for ArtNodes {
+    type Ref<'a> = ArtRef<'a>;
+    type Borrowed<'a> = ArtNodes<&'a [u64], &'a [[u64; 2]], Strings<&'a [u64], &'a [u8]>, Vecs<(&'a [u8], &'a [u64]), &'a [u64]>, Vecs<&'a [u8], &'a [u64]>>;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        ArtNodes {
+            roots: &self.roots[..],
+            bounds: &self.bounds[..],
+            keys: self.keys.borrow(),
+            terms: &self.terms[..],
+            somes: self.somes.borrow(),
+            manys: &self.manys[..],
+            paths: self.paths.borrow(),
+            nexts: &self.nexts[..],
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a {
+        ArtNodes {
+            roots: thing.roots,
+            bounds: thing.bounds,
+            keys: Strings::<Vec<u64>, Vec<u8>>::reborrow(thing.keys),
+            terms: thing.terms,
+            somes: Vecs::<(Vec<u8>, Vec<u64>)>::reborrow(thing.somes),
+            manys: thing.manys,
+            paths: Vecs::<Vec<u8>>::reborrow(thing.paths),
+            nexts: thing.nexts,
+        }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a {
+        ArtRef { index: thing.index, nodes: Self::reborrow(thing.nodes) }
+    }
+}
 
-pub struct ArtNodes {
-    // pub inner: Results<Results< , >, Results< , >>,
-}
\ No newline at end of file
+impl Container for ArtNodes {
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.roots.reserve(selves.clone().map(|x| x.roots.len()).sum());
+        self.bounds.reserve(selves.clone().map(|x| x.bounds.len()).sum());
+        self.keys.reserve_for(selves.clone().map(|x| x.keys));
+        self.terms.reserve(selves.clone().map(|x| x.terms.len()).sum());
+        self.somes.reserve_for(selves.clone().map(|x| x.somes));
+        self.manys.reserve(selves.clone().map(|x| x.manys.len()).sum());
+        self.paths.reserve_for(selves.clone().map(|x| x.paths));
+        self.nexts.reserve(selves.map(|x| x.nexts.len()).sum());
+    }
+}
+
+impl<'a> Push<ArtRef<'a>> for ArtNodes {
+    fn push(&mut self, item: ArtRef<'a>) {
+        let keys: Vec<&[u8]> = item.into_index_iter().collect();
+        self.push_sorted(&keys);
+    }
+}
+impl<I: IntoIterator> Push<I> for ArtNodes where I::Item: AsRef<[u8]> {
+    #[inline(always)]
+    fn push(&mut self, keys: I) { self.push_keys(keys) }
+}
+
+impl Clear for ArtNodes {
+    fn clear(&mut self) {
+        self.roots.clear();
+        self.bounds.clear();
+        self.keys.clear();
+        self.terms.clear();
+        self.somes.clear();
+        self.manys.clear();
+        self.paths.clear();
+        self.nexts.clear();
+    }
+}
+
+impl<BC: Len, TC, KC, SC, PC> Len for ArtNodes<BC, TC, KC, SC, PC> {
+    #[inline(always)] fn len(&self) -> usize { self.roots.len() }
+}
+
+impl<'a> Index for ArtNodes<&'a [u64], &'a [[u64; 2]], Strings<&'a [u64], &'a [u8]>, Vecs<(&'a [u8], &'a [u64]), &'a [u64]>, Vecs<&'a [u8], &'a [u64]>> {
+    type Ref = ArtRef<'a>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref { ArtRef { index, nodes: *self } }
+}
+impl<'a> Index for &'a ArtNodes {
+    type Ref = ArtRef<'a>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref { ArtRef { index, nodes: self.borrow() } }
+}
+
+/// A reference to one set of keys in an [`ArtNodes`].
+///
+/// The reference indexes the keys of the set in sorted order, and
+/// supports membership and prefix lookups by navigating the tree.
+#[derive(Copy, Clone, Debug)]
+pub struct ArtRef<'a> {
+    /// The index of the set in `nodes`.
+    index: usize,
+    /// The borrowed containers of all sets.
+    nodes: <ArtNodes as Borrow>::Borrowed<'a>,
+}
+
+impl<'a> ArtRef<'a> {
+    /// The index in `nodes.keys` of the first key of this set.
+    #[inline(always)]
+    fn lower(&self) -> usize {
+        if self.index == 0 { 0 } else { self.nodes.bounds[self.index - 1] as usize }
+    }
+    /// True if `key` is in the set.
+    #[inline]
+    pub fn contains(&self, key: &[u8]) -> bool { self.find(key).is_some() }
+    /// The position of `key` in the set, in sorted order, should it be present.
+    pub fn find(&self, key: &[u8]) -> Option<usize> {
+        let mut node = ArtIdx::decode(self.nodes.roots[self.index]);
+        let mut depth = 0;
+        loop {
+            match node {
+                ArtIdx::None => return None,
+                ArtIdx::Leaf(k) => {
+                    return (self.nodes.keys.get(k) == key).then(|| k - self.lower());
+                },
+                ArtIdx::Term(i) => {
+                    let [k, next] = self.nodes.terms[i];
+                    if depth == key.len() { return Some(k as usize - self.lower()); }
+                    node = ArtIdx::decode(next);
+                },
+                ArtIdx::Path(i) => {
+                    let path = self.nodes.paths.get(i).as_slice();
+                    if !key[depth..].starts_with(path) { return None; }
+                    depth += path.len();
+                    node = ArtIdx::decode(self.nodes.nexts[i]);
+                },
+                ArtIdx::Some(i) | ArtIdx::Many(i) => {
+                    let byte = *key.get(depth)?;
+                    node = self.child(node, i, byte);
+                    depth += 1;
+                },
+            }
+        }
+    }
+    /// The positions of keys in the set that start with `prefix`, in sorted order.
+    pub fn prefix(&self, prefix: &[u8]) -> core::ops::Range<usize> {
+        let lower = self.lower();
+        let mut node = ArtIdx::decode(self.nodes.roots[self.index]);
+        let mut depth = 0;
+        while depth < prefix.len() {
+            match node {
+                ArtIdx::None => return 0 .. 0,
+                ArtIdx::Leaf(k) => {
+                    return if self.nodes.keys.get(k).starts_with(prefix) { k - lower .. k - lower + 1 } else { 0 .. 0 };
+                },
+                ArtIdx::Term(i) => { node = ArtIdx::decode(self.nodes.terms[i][1]); },
+                ArtIdx::Path(i) => {
+                    let path = self.nodes.paths.get(i).as_slice();
+                    let rest = &prefix[depth..];
+                    let len = core::cmp::min(path.len(), rest.len());
+                    if path[.. len] != rest[.. len] { return 0 .. 0; }
+                    // If the prefix ends within the path, all keys below the path match.
+                    if len < path.len() { break; }
+                    depth += len;
+                    node = ArtIdx::decode(self.nodes.nexts[i]);
+                },
+                ArtIdx::Some(i) | ArtIdx::Many(i) => {
+                    node = self.child(node, i, prefix[depth]);
+                    depth += 1;
+                },
+            }
+        }
+        match (self.extreme(node, true), self.extreme(node, false)) {
+            (Some(first), Some(last)) => first - lower .. last - lower + 1,
+            _ => 0 .. 0,
+        }
+    }
+    /// The keys of the set that start with `prefix`, in sorted order.
+    pub fn prefixed(&self, prefix: &[u8]) -> crate::Slice<Self> {
+        let range = self.prefix(prefix);
+        crate::Slice::new(range.start as u64, range.end as u64, *self)
+    }
+    /// The child of `node`, either `Some(i)` or `Many(i)`, for `byte`.
+    #[inline(always)]
+    fn child(&self, node: ArtIdx, i: usize, byte: u8) -> ArtIdx {
+        match node {
+            ArtIdx::Some(_) => {
+                let lower = if i == 0 { 0 } else { self.nodes.somes.bounds[i - 1] as usize };
+                let upper = self.nodes.somes.bounds[i] as usize;
+                let (labels, kids) = self.nodes.somes.values;
+                match labels[lower .. upper].binary_search(&byte) {
+                    Ok(pos) => ArtIdx::decode(kids[lower + pos]),
+                    Err(_) => ArtIdx::None,
+                }
+            },
+            _ => ArtIdx::decode(self.nodes.manys[256 * i + byte as usize]),
+        }
+    }
+    /// The index in `nodes.keys` of the first (or last) key below `node`.
+    fn extreme(&self, mut node: ArtIdx, first: bool) -> Option<usize> {
+        loop {
+            node = match node {
+                ArtIdx::None => return None,
+                ArtIdx::Leaf(k) => return Some(k),
+                ArtIdx::Term(i) => {
+                    let [k, next] = self.nodes.terms[i];
+                    if first { return Some(k as usize); }
+                    ArtIdx::decode(next)
+                },
+                ArtIdx::Path(i) => ArtIdx::decode(self.nodes.nexts[i]),
+                ArtIdx::Some(i) => {
+                    let lower = if i == 0 { 0 } else { self.nodes.somes.bounds[i - 1] as usize };
+                    let upper = self.nodes.somes.bounds[i] as usize;
+                    ArtIdx::decode(self.nodes.somes.values.1[if first { lower } else { upper - 1 }])
+                },
+                ArtIdx::Many(i) => {
+                    let kids = &self.nodes.manys[256 * i ..][.. 256];
+                    let kid = if first { kids.iter().find(|k| **k != 0) } else { kids.iter().rev().find(|k| **k != 0) };
+                    ArtIdx::decode(*kid?)
+                },
+            }
+        }
+    }
+}
+
+impl<'a> Len for ArtRef<'a> {
+    #[inline(always)]
+    fn len(&self) -> usize { self.nodes.bounds[self.index] as usize - self.lower() }
+}
+impl<'a> Index for ArtRef<'a> {
+    type Ref = &'a [u8];
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref { self.nodes.keys.get(self.lower() + index) }
+}
+impl<'a, 'b> PartialEq<ArtRef<'b>> for ArtRef<'a> {
+    fn eq(&self, other: &ArtRef<'b>) -> bool {
+        self.len() == other.len() && (0 .. self.len()).all(|i| self.get(i) == other.get(i))
+    }
+}
+
+impl<'a, BC: crate::AsBytes<'a>, TC: crate::AsBytes<'a>, KC: crate::AsBytes<'a>, SC: crate::AsBytes<'a>, PC: crate::AsBytes<'a>> crate::AsBytes<'a> for ArtNodes<BC, TC, KC, SC, PC> {
+    const SLICE_COUNT: usize = 4 * BC::SLICE_COUNT + TC::SLICE_COUNT + KC::SLICE_COUNT + SC::SLICE_COUNT + PC::SLICE_COUNT;
+    #[inline]
+    fn get_byte_slice(&self, mut index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        if index < BC::SLICE_COUNT { return self.roots.get_byte_slice(index); }
+        index -= BC::SLICE_COUNT;
+        if index < BC::SLICE_COUNT { return self.bounds.get_byte_slice(index); }
+        index -= BC::SLICE_COUNT;
+        if index < KC::SLICE_COUNT { return self.keys.get_byte_slice(index); }
+        index -= KC::SLICE_COUNT;
+        if index < TC::SLICE_COUNT { return self.terms.get_byte_slice(index); }
+        index -= TC::SLICE_COUNT;
+        if index < SC::SLICE_COUNT { return self.somes.get_byte_slice(index); }
+        index -= SC::SLICE_COUNT;
+        if index < BC::SLICE_COUNT { return self.manys.get_byte_slice(index); }
+        index -= BC::SLICE_COUNT;
+        if index < PC::SLICE_COUNT { return self.paths.get_byte_slice(index); }
+        index -= PC::SLICE_COUNT;
+        self.nexts.get_byte_slice(index)
+    }
+}
+
+impl<BC: crate::schema::Schema, TC: crate::schema::Schema, KC: crate::schema::Schema, SC: crate::schema::Schema, PC: crate::schema::Schema> crate::schema::Schema for ArtNodes<BC, TC, KC, SC, PC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("ArtNodes", [
+            ("roots", BC::schema()),
+            ("bounds", BC::schema()),
+            ("keys", KC::schema()),
+            ("terms", TC::schema()),
+            ("somes", SC::schema()),
+            ("manys", BC::schema()),
+            ("paths", PC::schema()),
+            ("nexts", BC::schema()),
+        ])
+    }
+}
+
+impl<'a> crate::FromBytes<'a> for ArtNodes<&'a [u64], &'a [[u64; 2]], Strings<&'a [u64], &'a [u8]>, Vecs<(&'a [u8], &'a [u64]), &'a [u64]>, Vecs<&'a [u8], &'a [u64]>> {
+    const SLICE_COUNT: usize = <Self as crate::AsBytes<'a>>::SLICE_COUNT;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            roots: crate::FromBytes::from_bytes(bytes),
+            bounds: crate::FromBytes::from_bytes(bytes),
+            keys: crate::FromBytes::from_bytes(bytes),
+            terms: crate::FromBytes::from_bytes(bytes),
+            somes: crate::FromBytes::from_bytes(bytes),
+            manys: crate::FromBytes::from_bytes(bytes),
+            paths: crate::FromBytes::from_bytes(bytes),
+            nexts: crate::FromBytes::from_bytes(bytes),
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self {
+            roots: crate::FromBytes::from_store(store, offset),
+            bounds: crate::FromBytes::from_store(store, offset),
+            keys: crate::FromBytes::from_store(store, offset),
+            terms: crate::FromBytes::from_store(store, offset),
+            somes: crate::FromBytes::from_store(store, offset),
+            manys: crate::FromBytes::from_store(store, offset),
+            paths: crate::FromBytes::from_store(store, offset),
+            nexts: crate::FromBytes::from_store(store, offset),
+        }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        <&[u64]>::element_sizes(sizes)?;
+        <&[u64]>::element_sizes(sizes)?;
+        <Strings<&[u64], &[u8]>>::element_sizes(sizes)?;
+        <&[[u64; 2]]>::element_sizes(sizes)?;
+        <Vecs<(&[u8], &[u64]), &[u64]>>::element_sizes(sizes)?;
+        <&[u64]>::element_sizes(sizes)?;
+        <Vecs<&[u8], &[u64]>>::element_sizes(sizes)?;
+        <&[u64]>::element_sizes(sizes)?;
+        Ok(())
+    }
+    /// Validates each container, and that each set is a tree over its own keys.
+    ///
+    /// Each node must be reachable from exactly one root, which rules out cycles.
+    fn validate_deep(&self) -> Result<usize, String> {
+        let keys = crate::FromBytes::validate_deep(&self.keys)?;
+        let somes = crate::FromBytes::validate_deep(&self.somes)?;
+        let paths = crate::FromBytes::validate_deep(&self.paths)?;
+        if self.roots.len() != self.bounds.len() || self.nexts.len() != paths || self.manys.len() % 256 != 0 {
+            return Err(format!("ArtNodes: {} roots with {} bounds, {} paths with {} nexts, and {} dense words", self.roots.len(), self.bounds.len(), paths, self.nexts.len(), self.manys.len()));
+        }
+        crate::common::validate_bounds("ArtNodes", &self.bounds, self.bounds.len(), keys)?;
+        // Visit each tree, checking that references are in bounds and keys belong to the tree.
+        let mut budget = self.terms.len() + somes + self.manys.len() / 256 + paths;
+        let mut todo = Vec::new();
+        for (index, root) in self.roots.iter().enumerate() {
+            let lower = if index == 0 { 0 } else { self.bounds[index - 1] as usize };
+            let upper = self.bounds[index] as usize;
+            todo.push(*root);
+            while let Some(encoded) = todo.pop() {
+                let node = ArtIdx::decode(encoded);
+                let key = match node {
+                    ArtIdx::None => None,
+                    ArtIdx::Leaf(k) => Some(k),
+                    ArtIdx::Term(i) if i < self.terms.len() => {
+                        todo.push(self.terms[i][1]);
+                        Some(self.terms[i][0] as usize)
+                    },
+                    ArtIdx::Some(i) if i < somes && self.somes.bounds[i] > if i == 0 { 0 } else { self.somes.bounds[i - 1] } => {
+                        let (lower, upper) = (if i == 0 { 0 } else { self.somes.bounds[i - 1] as usize }, self.somes.bounds[i] as usize);
+                        todo.extend_from_slice(&self.somes.values.1[lower .. upper]);
+                        None
+                    },
+                    ArtIdx::Many(i) if i < self.manys.len() / 256 => {
+                        todo.extend_from_slice(&self.manys[256 * i ..][.. 256]);
+                        None
+                    },
+                    ArtIdx::Path(i) if i < paths => {
+                        todo.push(self.nexts[i]);
+                        None
+                    },
+                    _ => return Err(format!("ArtNodes: set {} has an invalid node {:?}", index, node)),
+                };
+                if key.is_some_and(|k| k < lower || k >= upper) {
+                    return Err(format!("ArtNodes: set {} has keys {} .. {} but references key {:?}", index, lower, upper, key));
+                }
+                if !matches!(node, ArtIdx::None | ArtIdx::Leaf(_)) {
+                    budget = budget.checked_sub(1).ok_or_else(|| format!("ArtNodes: set {} visits more nodes than exist", index))?;
+                }
+            }
+        }
+        Ok(self.roots.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+    use crate::{Borrow, Container, Index, Len, Push};
+    use crate::bytes::stash::Stash;
+    use super::ArtNodes;
+
+    fn words() -> Vec<&'static [u8]> {
+        vec![b"", b"a", b"ab", b"abc", b"abd", b"abdicate", b"b", b"banana", b"band", b"bandana", b"zebra"]
+    }
+
+    #[test]
+    fn membership() {
+        let mut nodes = ArtNodes::default();
+        // Unsorted and duplicated input is sorted and deduplicated.
+        nodes.push(words().into_iter().rev().chain(words()));
+        nodes.push([b"solo"]);
+        nodes.push(Vec::<&[u8]>::new());
+        assert_eq!(nodes.len(), 3);
+
+        let borrowed = nodes.borrow();
+        let set = borrowed.get(0);
+        assert_eq!(set.len(), words().len());
+        for (index, word) in words().iter().enumerate() {
+            assert_eq!(set.get(index), *word);
+            assert_eq!(set.find(word), Some(index));
+        }
+        for absent in [&b"abe"[..], b"abcd", b"ba", b"bandanas", b"c", b"zebr", b"\xff"] {
+            assert!(!set.contains(absent));
+        }
+        assert!(borrowed.get(1).contains(b"solo"));
+        assert!(!borrowed.get(1).contains(b"sol"));
+        assert!(!borrowed.get(2).contains(b""));
+        assert_eq!(borrowed.get(2).len(), 0);
+    }
+
+    #[test]
+    fn prefixes() {
+        let mut nodes = ArtNodes::default();
+        nodes.push(words());
+        let set = (&nodes).get(0);
+        let all = words();
+        for prefix in [&b""[..], b"a", b"ab", b"abd", b"abdi", b"b", b"ban", b"band", b"bandanas", b"c", b"z", b"zebra"] {
+            let expected: Vec<usize> = (0 .. all.len()).filter(|i| all[*i].starts_with(prefix)).collect();
+            let range = set.prefix(prefix);
+            assert_eq!(range.clone().collect::<Vec<_>>(), expected, "prefix {:?}", prefix);
+            assert_eq!(set.prefixed(prefix).len(), range.len());
+        }
+    }
+
+    #[test]
+    fn dense_nodes() {
+        // Keys branching on every byte value produce `Many` nodes.
+        let keys: Vec<Vec<u8>> = (0 ..= 255u8).flat_map(|a| [vec![7, a], vec![7, a, a]]).collect();
+        let mut nodes = ArtNodes::default();
+        nodes.push(keys.iter());
+        assert_eq!(nodes.manys.len(), 256);
+        let set = (&nodes).get(0);
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(set.find(key), Some(index));
+        }
+        assert!(!set.contains(&[7]));
+        assert!(!set.contains(&[7, 3, 4]));
+        assert_eq!(set.prefix(&[7]), 0 .. keys.len());
+        assert_eq!(set.prefix(&[7, 9]), 18 .. 20);
+    }
+
+    #[test]
+    fn round_trip_stash() {
+        let mut nodes = ArtNodes::default();
+        nodes.push(words());
+        nodes.push([b"x", b"y"]);
+
+        // Copy through the `Container` interface.
+        let mut copy = ArtNodes::default();
+        copy.extend_from_self(nodes.borrow(), 0 .. 2);
+        assert_eq!(copy, nodes);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &nodes.borrow()).unwrap();
+        let stash: Stash<ArtNodes, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+        let borrowed = stash.borrow();
+        assert_eq!(borrowed.len(), 2);
+        assert_eq!(borrowed.get(0), nodes.borrow().get(0));
+        assert!(borrowed.get(0).contains(b"bandana"));
+        assert_eq!(borrowed.get(0).prefix(b"ab"), 2 .. 6);
+        assert!(borrowed.get(1).contains(b"y"));
+    }
+}
@@ -203,7 +203,13 @@ impl<'a, TC: crate::AsBytes<'a>, BC: crate::AsBytes<'a>> crate::AsBytes<'a> for
     }
 }
 
-impl<'a, TC: crate::FromBytes<'a>, BC: crate::FromBytes<'a>> crate::FromBytes<'a> for Trees<TC, BC> {
+impl<TC: crate::schema::Schema, BC: crate::schema::Schema> crate::schema::Schema for Trees<TC, BC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Trees", [("groups", BC::schema()), ("bounds", BC::schema()), ("values", TC::schema())])
+    }
+}
+
+impl<'a, TC: crate::FromBytes<'a>, BC: crate::FromBytes<'a> + IndexAs<u64>> crate::FromBytes<'a> for Trees<TC, BC> {
     const SLICE_COUNT: usize = BC::SLICE_COUNT + BC::SLICE_COUNT + TC::SLICE_COUNT;
     #[inline(always)]
     fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -227,6 +233,24 @@ impl<'a, TC: crate::FromBytes<'a>, BC: crate::FromBytes<'a>> crate::FromBytes<'a
         TC::element_sizes(sizes)?;
         Ok(())
     }
+    fn validate_deep(&self) -> Result<usize, String> {
+        let values = self.values.validate_deep()?;
+        let groups = self.groups.validate_deep()?;
+        let bounds = self.bounds.validate_deep()?;
+        if groups == 0 || bounds != values + 1 {
+            return Err(format!("Trees: {} groups and {} bounds for {} values", groups, bounds, values));
+        }
+        crate::common::validate_bounds("Trees", &self.bounds, values + 1, values)?;
+        crate::common::validate_bounds("Trees", &self.groups, groups, values)?;
+        // Each tree must start with a root, whose children exclude itself.
+        for index in 0 .. groups - 1 {
+            let root = self.groups.index_as(index) as usize;
+            if root >= values || self.bounds.index_as(root) >= self.bounds.index_as(root + 1) {
+                return Err(format!("Trees: tree {} has an invalid root {}", index, root));
+            }
+        }
+        Ok(groups - 1)
+    }
 }
 
 /// LOUDS (level ordered unary degree sequence) is a succinct tree representation.
@@ -359,6 +383,13 @@ mod test {
         let t1 = rebuilt.get(1);
         assert_eq!(*t1.value(), 100);
         assert_eq!(t1.kids(), 0);
+
+        // Children must lie within the values, and roots must not be their own children.
+        assert_eq!(rebuilt.validate_deep(), Ok(2));
+        let bounds = [0u64, 3, 3, 4, 6, 6];
+        assert!(Trees { bounds: &bounds[..], ..rebuilt }.validate_deep().is_err());
+        let bounds = [0u64, 3, 3, 4, 4, 4];
+        assert!(Trees { bounds: &bounds[..], ..rebuilt }.validate_deep().is_err());
     }
 
     #[test]
@@ -1,5 +1,5 @@
 //! Implementations of traits for `Arc<T>`
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec, string::String};
 
 use crate::{Len, Borrow, AsBytes, FromBytes};
 
@@ -21,6 +21,12 @@ impl<'a, T: FromBytes<'a>> FromBytes<'a> for Arc<T> {
     const SLICE_COUNT: usize = T::SLICE_COUNT;
     #[inline(always)] fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self { Arc::new(T::from_bytes(bytes)) }
     #[inline(always)] fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self { Arc::new(T::from_store(store, offset)) }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { T::element_sizes(sizes) }
+    fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { T::validate(slices) }
+    fn validate_deep(&self) -> Result<usize, String> { self.as_ref().validate_deep() }
+}
+impl<T: crate::schema::Schema> crate::schema::Schema for Arc<T> {
+    fn schema() -> crate::schema::Node { T::schema() }
 }
 
 #[cfg(test)]
@@ -0,0 +1,446 @@
+//! Conversion between columnar containers and Apache Arrow arrays.
+//!
+//! Many containers here are close to Arrow arrays: `Vec<T>` of primitives are Arrow primitive arrays,
+//! and `Strings` and `Vecs` are Arrow string and list arrays, though with `u64` bounds of where each
+//! element ends rather than `i64` offsets of where each starts and ends. The `Options` container stores
+//! only its present values, where Arrow stores a value in each slot with a validity bitmap.
+//!
+//! [`IntoArrow`] is implemented for containers generically in their parts, and so for both owned and
+//! borrowed containers. Owned `Vec` parts become Arrow buffers without copying, where the layouts agree.
+//! Borrowed parts are copied, as Arrow buffers must own their memory or share it with an owner.
+//! [`FromArrow`] is implemented for owned containers, and copies.
+//! The derive implements both for structs, as Arrow struct arrays, and for enums, as Arrow dense
+//! union arrays whose type ids are the variant indexes.
+//!
+//! Strings are Arrow `LargeUtf8` arrays and lists are `LargeList` arrays, with `i64` offsets.
+//! Imports also accept `Utf8` and `List` arrays, with `i32` offsets.
+
+use alloc::{vec::Vec, string::String, sync::Arc};
+
+use arrow_array::Array;
+use arrow_buffer::{Buffer, BooleanBuffer, ScalarBuffer};
+use arrow_data::transform::MutableArrayData;
+use arrow_schema::{Field, Fields, UnionFields, UnionMode};
+
+pub use arrow_data::ArrayData;
+pub use arrow_schema::DataType;
+
+use crate::{Discriminant, Index, IndexAs, Len, Options, Strings, Vecs};
+use crate::sums::discriminant::{Tag, Tags};
+use crate::primitive::{Bools, Empties};
+
+/// A container that converts to an Arrow array.
+pub trait IntoArrow {
+    /// The type of the Arrow array the container converts to.
+    fn data_type() -> DataType;
+    /// Converts the container into an Arrow array, without copying owned parts whose layouts agree.
+    fn into_arrow(self) -> Result<ArrayData, String>;
+}
+
+/// A container that can be formed from an Arrow array.
+pub trait FromArrow: Sized {
+    /// Copies the contents of `data` into a new container.
+    fn from_arrow(data: &ArrayData) -> Result<Self, String>;
+}
+
+/// Builds `builder` into an array, reporting errors as strings.
+fn build(builder: arrow_data::ArrayDataBuilder) -> Result<ArrayData, String> {
+    builder.build().map_err(|error| format!("arrow: {}", error))
+}
+
+/// Checks that `data` has no nulls, which only `Options` import.
+fn check_nulls(data: &ArrayData) -> Result<(), String> {
+    if data.null_count() > 0 {
+        return Err(format!("arrow: {} array has nulls, which require `Option`", data.data_type()));
+    }
+    Ok(())
+}
+
+/// Checks that `data` has type `expected` and no nulls.
+fn check(data: &ArrayData, expected: &DataType) -> Result<(), String> {
+    if data.data_type() != expected {
+        return Err(format!("arrow: expected {} but found {}", expected, data.data_type()));
+    }
+    check_nulls(data)
+}
+
+/// Arrow `i64` offsets for elements that end at `bounds`, with a leading zero.
+fn offsets<BC: IndexAs<u64> + Len>(bounds: &BC) -> Result<Buffer, String> {
+    let mut offsets = Vec::with_capacity(bounds.len() + 1);
+    offsets.push(0i64);
+    for index in 0 .. bounds.len() {
+        let bound = bounds.index_as(index);
+        offsets.push(i64::try_from(bound).map_err(|_| format!("arrow: bound {} does not fit in `i64`", bound))?);
+    }
+    Ok(Buffer::from_vec(offsets))
+}
+
+macro_rules! implement_arrow {
+    ($($index_type:ty, $arrow_type:ty, $data_type:expr;)*) => { $(
+        impl IntoArrow for Vec<$index_type> {
+            fn data_type() -> DataType { $data_type }
+            fn into_arrow(self) -> Result<ArrayData, String> {
+                build(ArrayData::builder($data_type).len(self.len()).add_buffer(Buffer::from_vec(self)))
+            }
+        }
+        impl<'a> IntoArrow for &'a [$index_type] {
+            fn data_type() -> DataType { $data_type }
+            fn into_arrow(self) -> Result<ArrayData, String> {
+                build(ArrayData::builder($data_type).len(self.len()).add_buffer(Buffer::from_slice_ref(self)))
+            }
+        }
+        impl FromArrow for Vec<$index_type> {
+            fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+                check(data, &$data_type)?;
+                Ok(arrow_array::PrimitiveArray::<$arrow_type>::from(data.clone()).values().to_vec())
+            }
+        }
+    )* }
+}
+
+implement_arrow!(
+    u8, arrow_array::types::UInt8Type, DataType::UInt8;
+    u16, arrow_array::types::UInt16Type, DataType::UInt16;
+    u32, arrow_array::types::UInt32Type, DataType::UInt32;
+    u64, arrow_array::types::UInt64Type, DataType::UInt64;
+    i8, arrow_array::types::Int8Type, DataType::Int8;
+    i16, arrow_array::types::Int16Type, DataType::Int16;
+    i32, arrow_array::types::Int32Type, DataType::Int32;
+    i64, arrow_array::types::Int64Type, DataType::Int64;
+    f32, arrow_array::types::Float32Type, DataType::Float32;
+    f64, arrow_array::types::Float64Type, DataType::Float64;
+);
+
+impl<VC: Len + IndexAs<u64>, TC: IndexAs<u64>> IntoArrow for Bools<VC, TC> {
+    fn data_type() -> DataType { DataType::Boolean }
+    fn into_arrow(self) -> Result<ArrayData, String> {
+        let bits: BooleanBuffer = (0 .. self.len()).map(|index| self.get(index)).collect();
+        build(ArrayData::builder(DataType::Boolean).len(self.len()).add_buffer(bits.into_inner()))
+    }
+}
+
+impl FromArrow for Bools {
+    fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+        check(data, &DataType::Boolean)?;
+        let mut bools = Bools::default();
+        for bit in arrow_array::BooleanArray::from(data.clone()).values().iter() {
+            crate::Push::push(&mut bools, &bit);
+        }
+        Ok(bools)
+    }
+}
+
+impl<CC: crate::common::index::CopyAs<u64>> IntoArrow for Empties<CC> {
+    fn data_type() -> DataType { DataType::Null }
+    fn into_arrow(self) -> Result<ArrayData, String> {
+        Ok(ArrayData::new_null(&DataType::Null, self.len()))
+    }
+}
+
+impl FromArrow for Empties {
+    fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+        if data.data_type() != &DataType::Null {
+            return Err(format!("arrow: expected {} but found {}", DataType::Null, data.data_type()));
+        }
+        Ok(Empties { count: data.len() as u64, empty: () })
+    }
+}
+
+impl<BC: IndexAs<u64> + Len, VC: IntoArrow> IntoArrow for Strings<BC, VC> {
+    fn data_type() -> DataType { DataType::LargeUtf8 }
+    fn into_arrow(self) -> Result<ArrayData, String> {
+        // The bytes are the buffer of the values as an array of `u8`, which is not copied if owned.
+        let values = self.values.into_arrow()?;
+        let bytes = values.buffers().first().cloned().ok_or("arrow: `Strings` values have no buffer")?;
+        build(ArrayData::builder(DataType::LargeUtf8).len(self.bounds.len()).add_buffer(offsets(&self.bounds)?).add_buffer(bytes))
+    }
+}
+
+impl FromArrow for Strings {
+    fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+        let mut strings = Strings::default();
+        match data.data_type() {
+            DataType::Utf8 => {
+                check(data, &DataType::Utf8)?;
+                for string in arrow_array::StringArray::from(data.clone()).iter() {
+                    crate::Push::push(&mut strings, string.unwrap_or_default());
+                }
+            },
+            _ => {
+                check(data, &DataType::LargeUtf8)?;
+                for string in arrow_array::LargeStringArray::from(data.clone()).iter() {
+                    crate::Push::push(&mut strings, string.unwrap_or_default());
+                }
+            },
+        }
+        Ok(strings)
+    }
+}
+
+impl<TC: IntoArrow, BC: IndexAs<u64> + Len> IntoArrow for Vecs<TC, BC> {
+    fn data_type() -> DataType { DataType::LargeList(Arc::new(Field::new("item", TC::data_type(), true))) }
+    fn into_arrow(self) -> Result<ArrayData, String> {
+        let offsets = offsets(&self.bounds)?;
+        build(ArrayData::builder(Self::data_type()).len(self.bounds.len()).add_buffer(offsets).add_child_data(self.values.into_arrow()?))
+    }
+}
+
+impl<TC: FromArrow> FromArrow for Vecs<TC> {
+    fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+        // The offsets of the elements, and the range of the values they reference.
+        let (offsets, values): (Vec<u64>, ArrayData) = match data.data_type() {
+            DataType::List(_) => {
+                check_nulls(data)?;
+                let list = arrow_array::ListArray::from(data.clone());
+                (list.value_offsets().iter().map(|offset| *offset as u64).collect(), list.values().to_data())
+            },
+            DataType::LargeList(_) => {
+                check_nulls(data)?;
+                let list = arrow_array::LargeListArray::from(data.clone());
+                (list.value_offsets().iter().map(|offset| *offset as u64).collect(), list.values().to_data())
+            },
+            other => return Err(format!("arrow: expected a list but found {}", other)),
+        };
+        let (lower, upper) = (offsets[0] as usize, offsets[offsets.len() - 1] as usize);
+        Ok(Vecs {
+            bounds: offsets[1 ..].iter().map(|offset| offset - lower as u64).collect(),
+            values: TC::from_arrow(&values.slice(lower, upper - lower))?,
+        })
+    }
+}
+
+impl<TC: IntoArrow, CC: Len + IndexAs<u64>, VC: Len + IndexAs<u64>, WC: IndexAs<u64>> IntoArrow for Options<TC, CC, VC, WC> {
+    fn data_type() -> DataType { TC::data_type() }
+    fn into_arrow(self) -> Result<ArrayData, String> {
+        let len = self.len();
+        let somes = self.somes.into_arrow()?;
+        if somes.len() == len { return Ok(somes); }
+        // Interleave runs of present values with runs of nulls.
+        let mut output = MutableArrayData::new(vec![&somes], true, len);
+        let (mut index, mut rank) = (0, 0);
+        while index < len {
+            let some = self.indexes.get(index);
+            let count = (index .. len).take_while(|position| self.indexes.get(*position) == some).count();
+            if some {
+                output.extend(0, rank, rank + count);
+                rank += count;
+            }
+            else {
+                output.extend_nulls(count);
+            }
+            index += count;
+        }
+        Ok(output.freeze())
+    }
+}
+
+impl<TC: FromArrow> FromArrow for Options<TC> {
+    fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+        let somes = match data.nulls() {
+            None => data.clone(),
+            Some(nulls) => {
+                // Retain runs of valid values, which then have no nulls.
+                let mut somes = MutableArrayData::new(vec![data], false, data.len() - data.null_count());
+                for (start, end) in nulls.valid_slices() {
+                    somes.extend(0, start, end);
+                }
+                somes.freeze()
+            },
+        };
+        let mut indexes = crate::RankSelect::default();
+        for index in 0 .. data.len() {
+            indexes.push(data.is_valid(index));
+        }
+        Ok(Options { indexes, somes: TC::from_arrow(&somes)? })
+    }
+}
+
+/// The type of a struct array with fields named by `fields`.
+pub fn struct_type<'a>(fields: impl IntoIterator<Item = (&'a str, DataType)>) -> DataType {
+    DataType::Struct(fields.into_iter().map(|(name, data_type)| Field::new(name, data_type, true)).collect())
+}
+
+/// A struct array of `len` elements, with fields named by `fields`.
+pub fn structure<'a>(len: usize, fields: impl IntoIterator<Item = (&'a str, Result<ArrayData, String>)>) -> Result<ArrayData, String> {
+    let mut names = Vec::new();
+    let mut children = Vec::new();
+    for (name, data) in fields {
+        let data = data?;
+        if data.len() != len {
+            return Err(format!("arrow: field `{}` has length {} rather than {}", name, data.len(), len));
+        }
+        names.push(Field::new(name, data.data_type().clone(), true));
+        children.push(data);
+    }
+    build(ArrayData::builder(DataType::Struct(Fields::from(names))).len(len).child_data(children))
+}
+
+/// The fields of the struct array `data`, which must be named by `names`.
+pub fn struct_fields(data: &ArrayData, names: &[&str]) -> Result<Vec<ArrayData>, String> {
+    match data.data_type() {
+        DataType::Struct(fields) if fields.iter().map(|field| field.name().as_str()).eq(names.iter().copied()) => {
+            check_nulls(data)?;
+            Ok(arrow_array::StructArray::from(data.clone()).columns().iter().map(|column| column.to_data()).collect())
+        },
+        other => Err(format!("arrow: expected a struct with fields {:?} but found {}", names, other)),
+    }
+}
+
+/// The type of a dense union array with variants named by `variants`, whose type ids are their positions.
+pub fn union_type<'a>(variants: impl IntoIterator<Item = (&'a str, DataType)>) -> DataType {
+    let fields = variants.into_iter().map(|(name, data_type)| Field::new(name, data_type, true)).collect::<Vec<_>>();
+    DataType::Union(UnionFields::new((0 .. fields.len()).map(|index| index as i8), fields), UnionMode::Dense)
+}
+
+/// A dense union array, whose elements are located by `indexes` among the arrays of `variants`.
+///
+/// The discriminants in `indexes` are those in `tags`, and the type id of each is its position in `tags`.
+pub fn union<'a, T, CVar, COff>(indexes: &Discriminant<CVar, COff>, tags: &[T], variants: impl IntoIterator<Item = (&'a str, Result<ArrayData, String>)>) -> Result<ArrayData, String>
+where
+    T: Tag,
+    CVar: Tags<Tag = T>,
+    COff: Len + IndexAs<u64>,
+{
+    let mut fields = Vec::new();
+    let mut children = Vec::new();
+    for (name, data) in variants {
+        let data = data?;
+        fields.push(Field::new(name, data.data_type().clone(), true));
+        children.push(data);
+    }
+    if fields.len() > i8::MAX as usize + 1 {
+        return Err(format!("arrow: {} variants exceed the {} type ids of a union", fields.len(), i8::MAX as usize + 1));
+    }
+    let len = indexes.len();
+    let mut type_ids = Vec::with_capacity(len);
+    let mut offsets = Vec::with_capacity(len);
+    for index in 0 .. len {
+        let (variant, offset) = indexes.get(index);
+        let position = tags.iter().position(|tag| *tag == variant).ok_or_else(|| format!("arrow: discriminant {:?} is not a variant", variant))?;
+        type_ids.push(position as i8);
+        offsets.push(i32::try_from(offset).map_err(|_| format!("arrow: offset {} does not fit in `i32`", offset))?);
+    }
+    let data_type = DataType::Union(UnionFields::new((0 .. fields.len()).map(|index| index as i8), fields), UnionMode::Dense);
+    build(ArrayData::builder(data_type).len(len).add_buffer(Buffer::from_vec(type_ids)).add_buffer(Buffer::from_vec(offsets)).child_data(children))
+}
+
+/// The variants of the dense union array `data`, which must be named by `names`, and the discriminant of each element.
+///
+/// The discriminant of each element is the entry of `tags` at the position of its type id.
+pub fn union_variants<T: Tag>(data: &ArrayData, names: &[&str], tags: &[T]) -> Result<(Vec<ArrayData>, Discriminant<Vec<T>>), String> {
+    match data.data_type() {
+        DataType::Union(fields, UnionMode::Dense) if fields.iter().map(|(_, field)| field.name().as_str()).eq(names.iter().copied()) => {
+            if fields.iter().enumerate().any(|(index, (type_id, _))| type_id as usize != index) {
+                return Err("arrow: union type ids are not the positions of their variants".into());
+            }
+            let union = arrow_array::UnionArray::from(data.clone());
+            let offsets: &ScalarBuffer<i32> = union.offsets().ok_or("arrow: dense union without offsets")?;
+            let mut indexes = Discriminant::default();
+            for (type_id, offset) in union.type_ids().iter().zip(offsets.iter()) {
+                crate::Push::push(&mut indexes, (tags[*type_id as usize], *offset as u64));
+            }
+            let variants = (0 .. names.len()).map(|index| union.child(index as i8).to_data()).collect();
+            Ok((variants, indexes))
+        },
+        other => Err(format!("arrow: expected a dense union with variants {:?} but found {}", names, other)),
+    }
+}
+
+/// A dense union array of variants without fields, whose discriminants are `variants`.
+///
+/// The discriminants are those in `tags`, and the type id of each is its position in `tags`.
+pub fn units<'a, T: Tag>(variants: impl IntoIterator<Item = T>, tags: &[T], names: impl IntoIterator<Item = &'a str>) -> Result<ArrayData, String> {
+    let names = names.into_iter().collect::<Vec<_>>();
+    let mut counts = alloc::vec![0u64; names.len()];
+    let mut indexes: Discriminant<Vec<T>> = Discriminant::default();
+    for variant in variants {
+        let position = tags.iter().position(|tag| *tag == variant).ok_or_else(|| format!("arrow: discriminant {:?} is not a variant", variant))?;
+        crate::Push::push(&mut indexes, (variant, counts[position]));
+        counts[position] += 1;
+    }
+    union(&indexes, tags, names.into_iter().zip(counts).map(|(name, count)| (name, Ok(ArrayData::new_null(&DataType::Null, count as usize)))))
+}
+
+macro_rules! tuple_arrow {
+    ($($name:ident,$index:tt)+) => (
+        impl<$($name: IntoArrow + Len),*> IntoArrow for ($($name,)*) {
+            fn data_type() -> DataType { struct_type([$((stringify!($index), $name::data_type())),*]) }
+            fn into_arrow(self) -> Result<ArrayData, String> {
+                let len = self.0.len();
+                structure(len, [$((stringify!($index), self.$index.into_arrow())),*])
+            }
+        }
+        impl<$($name: FromArrow),*> FromArrow for ($($name,)*) {
+            fn from_arrow(data: &ArrayData) -> Result<Self, String> {
+                let fields = struct_fields(data, &[$(stringify!($index)),*])?;
+                Ok(($($name::from_arrow(&fields[$index])?,)*))
+            }
+        }
+    )
+}
+
+tuple_arrow!(A,0);
+tuple_arrow!(A,0 B,1);
+tuple_arrow!(A,0 B,1 C,2);
+tuple_arrow!(A,0 B,1 C,2 D,3);
+tuple_arrow!(A,0 B,1 C,2 D,3 E,4);
+tuple_arrow!(A,0 B,1 C,2 D,3 E,4 F,5);
+tuple_arrow!(A,0 B,1 C,2 D,3 E,4 F,5 G,6);
+tuple_arrow!(A,0 B,1 C,2 D,3 E,4 F,5 G,6 H,7);
+tuple_arrow!(A,0 B,1 C,2 D,3 E,4 F,5 G,6 H,7 I,8);
+
+#[cfg(test)]
+mod test {
+
+    use alloc::{vec::Vec, string::String};
+    use crate::{Borrow, ContainerOf, Index, Len};
+    use crate::common::Push;
+    use super::{IntoArrow, FromArrow};
+
+    #[test]
+    fn round_trip() {
+        type T = (u64, Option<String>, Vec<Option<i32>>, bool);
+        let items: Vec<T> = (0 .. 100u64).map(|i| (
+            i,
+            if i % 3 == 0 { None } else { Some(format!("{}", i)) },
+            (0 .. i % 5).map(|j| if j % 2 == 0 { Some(j as i32) } else { None }).collect(),
+            i % 2 == 0,
+        )).collect();
+        let mut column: ContainerOf<T> = Default::default();
+        for item in items.iter() { column.push(item); }
+
+        // Borrowed and owned containers convert to the same array.
+        let data = column.borrow().into_arrow().unwrap();
+        assert_eq!(data.data_type(), &<ContainerOf<T> as IntoArrow>::data_type());
+        assert_eq!(data.len(), 100);
+        let strings = arrow_array::StructArray::from(data.clone());
+        assert_eq!(strings.column(1).null_count(), 34);
+        assert_eq!(column.clone().into_arrow().unwrap(), data);
+
+        let back = ContainerOf::<T>::from_arrow(&data).unwrap();
+        assert_eq!(back.len(), 100);
+        for index in 0 .. 100 {
+            assert_eq!(back.borrow().get(index), column.borrow().get(index));
+        }
+
+        // Sliced arrays import only their elements.
+        let back = ContainerOf::<T>::from_arrow(&data.slice(10, 20)).unwrap();
+        assert_eq!(back.len(), 20);
+        assert_eq!(back.borrow().get(0), column.borrow().get(10));
+        assert!(Vec::<u32>::from_arrow(&data).is_err());
+    }
+
+    #[test]
+    fn zero_copy() {
+        let values: Vec<u64> = (0 .. 1000).collect();
+        let pointer = values.as_ptr();
+        let data = values.into_arrow().unwrap();
+        assert_eq!(data.buffers()[0].as_ptr() as *const u64, pointer);
+
+        // Borrowed parts are copied into buffers of their own.
+        let values: Vec<u64> = (0 .. 1000).collect();
+        let data = (&values[..]).into_arrow().unwrap();
+        assert_ne!(data.buffers()[0].as_ptr() as *const u64, values.as_ptr());
+        assert_eq!(Vec::<u64>::from_arrow(&data).unwrap(), values);
+    }
+}
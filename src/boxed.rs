@@ -5,7 +5,7 @@
 //!
 //! We need this wrapper to distinguish which [`Push`] implementation to use, otherwise
 //! the implementations would conflict.
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec, string::String};
 
 use crate::{AsBytes, Borrow, Clear, Columnar, Container, FromBytes, Index, IndexMut, Len, Push, Ref};
 
@@ -35,6 +35,7 @@ impl<C: Borrow> Borrow for Boxed<C> {
 }
 impl<C: Container> Container for Boxed<C> {
     #[inline(always)] fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) { self.0.extend_from_self(other.0, range) }
+    #[inline(always)] fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) { self.0.gather(other.0, indices) }
     #[inline(always)] fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone { self.0.reserve_for(selves.map(|x| x.0)) }
 }
 impl<C: Len> Len for Boxed<C> {
@@ -64,6 +65,12 @@ impl<'a, C: FromBytes<'a>> FromBytes<'a> for Boxed<C> {
     const SLICE_COUNT: usize = C::SLICE_COUNT;
     #[inline(always)] fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self { Self(C::from_bytes(bytes)) }
     #[inline(always)] fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self { Self(C::from_store(store, offset)) }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { C::element_sizes(sizes) }
+    fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { C::validate(slices) }
+    fn validate_deep(&self) -> Result<usize, String> { self.0.validate_deep() }
+}
+impl<C: crate::schema::Schema> crate::schema::Schema for Boxed<C> {
+    fn schema() -> crate::schema::Node { C::schema() }
 }
 impl<C: Index> Index for Boxed<C> {
     type Ref = Boxed<C::Ref>;
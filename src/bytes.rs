@@ -8,6 +8,10 @@
 //! The most reliable entry point to the read side of this functionality is the `Stash` type,
 //! which can be formed from any type that implements `Deref<Target=[u8]>`. Doing so will check
 //! `u64` alignment, copy the contents if misaligned, and perform some structural validation.
+//! With the `mmap` feature, the unsafe `Stash::open` forms a `Stash` from a memory-mapped file.
+//! The `batches` module frames a sequence of encoded containers, with random access to each,
+//! and the `stream` module decodes a sequence of encoded containers as their bytes arrive.
+//! The `versioned` module records the schema with the data, so that later versions of a type can read it.
 
 /// A trait for writing bytes, usable in `no_std` environments.
 ///
@@ -39,6 +43,46 @@ impl WriteBytes for alloc::vec::Vec<u8> {
     }
 }
 
+/// A trait for reading bytes, usable in `no_std` environments.
+///
+/// This mirrors `WriteBytes`, replacing `std::io::Read` for the columnar decoding functions.
+/// Implementations exist for `&[u8]` (always) and `std::io::Read` (with the `std` feature).
+pub trait ReadBytes {
+    /// The error type returned by read operations.
+    type Error;
+    /// Read some bytes into the slice, and return the number read, or return an error.
+    ///
+    /// Returning zero for a non-empty slice indicates the end of the input.
+    fn read(&mut self, bytes: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ReadBytes for R {
+    type Error = std::io::Error;
+    #[inline(always)]
+    fn read(&mut self, bytes: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            match std::io::Read::read(self, bytes) {
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => { },
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ReadBytes for &[u8] {
+    type Error = core::convert::Infallible;
+    #[inline(always)]
+    fn read(&mut self, bytes: &mut [u8]) -> Result<usize, Self::Error> {
+        let count = core::cmp::min(bytes.len(), self.len());
+        let (head, tail) = self.split_at(count);
+        bytes[..count].copy_from_slice(head);
+        *self = tail;
+        Ok(count)
+    }
+}
+
 
 /// A binary encoding of sequences of byte slices.
 ///
@@ -183,7 +227,7 @@ pub mod indexed {
             debug_assert!(slices <= store.len(), "DecodedStore::new: slice count {slices} exceeds store length {}", store.len());
             let index = store.get(..slices).unwrap_or(&[]);
             let last = index.last().copied().unwrap_or(0) as usize;
-            let last_w = (last + 7) / 8;
+            let last_w = last.div_ceil(8);
             debug_assert!(last_w <= store.len(), "DecodedStore::new: last word offset {last_w} exceeds store length {}", store.len());
             let words = store.get(..last_w).unwrap_or(&[]);
             Self { index, words }
@@ -199,7 +243,7 @@ pub mod indexed {
                 .min(self.words.len() * 8);
             let lower = (((*self.index.get(k).unwrap_or(&0) as usize) + 7) & !7)
                 .min(upper);
-            let upper_w = ((upper + 7) / 8).min(self.words.len());
+            let upper_w = upper.div_ceil(8).min(self.words.len());
             let lower_w = (lower / 8).min(upper_w);
             let tail = (upper % 8) as u8;
             (self.words.get(lower_w..upper_w).unwrap_or(&[]), tail)
@@ -209,6 +253,9 @@ pub mod indexed {
         pub fn len(&self) -> usize {
             self.index.len().saturating_sub(1)
         }
+        /// True if the store contains no slices.
+        #[inline(always)]
+        pub fn is_empty(&self) -> bool { self.len() == 0 }
     }
 
     /// Validates the internal structure of indexed-encoded data.
@@ -272,6 +319,17 @@ pub mod indexed {
         T::validate(&slices)
     }
 
+    /// Validates `store` as [`validate`] does, and then the internal structure of the decoded `T`.
+    ///
+    /// Beyond framing and element sizes, this checks the invariants that indexing relies on,
+    /// for example that vector bounds are in-bounds for their values, and that sum type tags
+    /// and offsets reference their variants. If this returns `Ok(len)`, then indexing the
+    /// decoded `T` at any position less than `len` should not panic.
+    pub fn validate_deep<'a, T: crate::FromBytes<'a>>(store: &'a [u64]) -> Result<usize, String> {
+        validate::<T>(store)?;
+        T::from_store(&DecodedStore::new(store), &mut 0).validate_deep()
+    }
+
     /// Decodes a specific byte slice by index. It will be `u64` aligned.
     #[inline(always)]
     pub fn decode_index(store: &[u64], index: u64) -> &[u8] {
@@ -365,9 +423,8 @@ pub mod stash {
     /// One can form a `Stash` directly by loading the variants, which are public. Do so with care,
     /// as loading mis-aligned `B` into the `Bytes` variant can result in a run-time panic, and
     /// loading structurally invalid data into either the `Bytes` or `Align` variant can produce
-    /// incorrect results at runtime (clamped index accesses, for example). The validation does not
-    /// confirm that the internal structure of types are valid, for example that all vector bounds
-    /// are in-bounds for their values, and these may result in panics at runtime for invalid data.
+    /// incorrect results at runtime (clamped index accesses, for example), or panics for data whose
+    /// internal structure is invalid, for example vector bounds that are out-of-bounds for their values.
     #[derive(Clone)]
     pub enum Stash<C, B> {
         /// The typed variant of the container.
@@ -392,6 +449,7 @@ pub mod stash {
         /// this will return an error. If this returns a `Stash` then all accesses that do not panic should
         /// be correct. The resulting `Stash` may still panic if the internal structure of the data
         /// are inconsistent, for example if any vector bounds are out-of-bounds for their values slice.
+        /// Use [`try_from_bytes_deep`](Self::try_from_bytes_deep) to also check the internal structure.
         ///
         /// There is no `unsafe` that is called through this type, and invalid data can result in panics
         /// or incorrect results, but not undefined behavior.
@@ -422,18 +480,33 @@ pub mod stash {
         /// assert_eq!(borrowed.1.get(1), b"world");
         /// ```
         pub fn try_from_bytes(bytes: B) -> Result<Self, String> {
-            use crate::bytes::indexed::validate;
             use crate::Borrow;
-            if !(bytes.len() % 8 == 0) { return Err(format!("bytes.len() = {:?} not a multiple of 8", bytes.len())) }
+            Self::try_from_bytes_with(bytes, crate::bytes::indexed::validate::<<C as Borrow>::Borrowed<'_>>)
+        }
+
+        /// As [`try_from_bytes`](Self::try_from_bytes), and also validates the internal structure of the data.
+        ///
+        /// This additionally checks the invariants that indexing relies on, for example that vector bounds
+        /// are in-bounds for their values, and that sum type tags name a variant. It reads all of the data,
+        /// in time proportional to its size. If this returns a `Stash` then indexing it at any position
+        /// less than its length will not panic. See [`FromBytes::validate_deep`].
+        pub fn try_from_bytes_deep(bytes: B) -> Result<Self, String> {
+            use crate::Borrow;
+            Self::try_from_bytes_with(bytes, |words| crate::bytes::indexed::validate_deep::<<C as Borrow>::Borrowed<'_>>(words).map(|_| ()))
+        }
+
+        /// Forms a `Stash` from `bytes`, which `validate` checks as aligned words.
+        fn try_from_bytes_with(bytes: B, validate: impl Fn(&[u64]) -> Result<(), String>) -> Result<Self, String> {
+            if bytes.len() % 8 != 0 { return Err(format!("bytes.len() = {:?} not a multiple of 8", bytes.len())) }
             if let Ok(words) = bytemuck::try_cast_slice::<_, u64>(&bytes) {
-                validate::<<C as Borrow>::Borrowed<'_>>(words)?;
+                validate(words)?;
                 Ok(Self::Bytes(bytes))
             }
             else {
                 // Re-locating bytes for alignment reasons.
                 let mut alloc: Vec<u64> = vec![0; bytes.len() / 8];
                 bytemuck::cast_slice_mut(&mut alloc[..]).copy_from_slice(&bytes[..]);
-                validate::<<C as Borrow>::Borrowed<'_>>(&alloc)?;
+                validate(&alloc)?;
                 Ok(Self::Align(alloc.into()))
             }
         }
@@ -521,6 +594,16 @@ pub mod stash {
             }
             Ok(())
         }
+        /// Write the contents to the file at `path`, creating it or replacing its contents.
+        ///
+        /// The file can be read back with `Stash::try_from_bytes`, or with `Stash::open` if the `mmap`
+        /// feature is enabled. Do not write to a file that is currently open as a memory-mapped stash.
+        #[cfg(feature = "std")]
+        pub fn write_to_path(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+            self.write_bytes(&mut writer)?;
+            std::io::Write::flush(&mut writer)
+        }
     }
 
     // This implementation converts to owned data if it is not already, which can be expensive.
@@ -546,6 +629,814 @@ pub mod stash {
     }
 }
 
+/// A framing of many [`indexed`] encoded batches, with a footer that locates each batch.
+///
+/// The format is a header word, the encoded batches one after the other, and then a footer.
+/// The footer has for each batch the byte offset at which it ends and its number of rows,
+/// followed by a fingerprint of the container type, the number of batches, and a trailer word.
+/// All words are `u64` in native byte order, as with [`indexed`], and each batch starts `u64` aligned.
+///
+/// The fingerprint is derived from the slice count and element sizes of the borrowed container,
+/// and is checked when reading. It detects many type mismatches, but is not a complete schema:
+/// types with equal fingerprints may not be interchangeable.
+pub mod batches {
+
+    use alloc::{vec::Vec, string::String};
+    use crate::Len;
+    use super::WriteBytes;
+    use super::stash::Stash;
+
+    /// The first word of the format, which also ends the footer.
+    pub const MAGIC: u64 = u64::from_le_bytes(*b"COLBATCH");
+
+    /// A fingerprint of the encoded layout of `T`, from its slice count and element sizes.
+    ///
+    /// This is an FNV-1a hash, which is stable across platforms and builds.
+    pub fn fingerprint<'a, T: crate::FromBytes<'a>>() -> u64 {
+        let mut sizes = Vec::new();
+        // Types without element sizes contribute only their slice count.
+        if T::element_sizes(&mut sizes).is_err() { sizes.clear(); }
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for word in core::iter::once(T::SLICE_COUNT).chain(sizes) {
+            for byte in (word as u64).to_le_bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+
+    #[inline(always)]
+    fn write_word<W: WriteBytes>(writer: &mut W, word: u64) -> Result<(), W::Error> {
+        writer.write_all(bytemuck::cast_slice(core::slice::from_ref(&word)))
+    }
+
+    /// Writes a sequence of batches of `C` to `W`, and a footer when finished.
+    ///
+    /// Dropping the writer without calling [`finish`](Self::finish) leaves the output without a
+    /// footer, and it will not be readable.
+    pub struct BatchWriter<C, W> {
+        writer: W,
+        /// The number of bytes written so far.
+        written: u64,
+        /// For each batch, its end offset in bytes and its number of rows.
+        batches: Vec<[u64; 2]>,
+        marker: core::marker::PhantomData<C>,
+    }
+
+    impl<C: crate::ContainerBytes, W: WriteBytes> BatchWriter<C, W> {
+        /// Starts the format by writing its header to `writer`.
+        pub fn new(mut writer: W) -> Result<Self, W::Error> {
+            write_word(&mut writer, MAGIC)?;
+            Ok(Self { writer, written: 8, batches: Vec::new(), marker: core::marker::PhantomData })
+        }
+        /// Appends `batch` to the output.
+        pub fn write(&mut self, batch: &C::Borrowed<'_>) -> Result<(), W::Error> {
+            super::indexed::write(&mut self.writer, batch)?;
+            self.written += super::indexed::length_in_bytes(batch) as u64;
+            self.batches.push([self.written, batch.len() as u64]);
+            Ok(())
+        }
+        /// Appends the contents of `stash` to the output.
+        pub fn write_stash<B: core::ops::Deref<Target=[u8]>>(&mut self, stash: &Stash<C, B>) -> Result<(), W::Error> {
+            self.write(&stash.borrow())
+        }
+        /// The number of batches written so far.
+        pub fn batches(&self) -> usize { self.batches.len() }
+        /// Writes the footer, and returns the underlying writer.
+        pub fn finish(mut self) -> Result<W, W::Error> {
+            for [offset, rows] in self.batches.iter() {
+                write_word(&mut self.writer, *offset)?;
+                write_word(&mut self.writer, *rows)?;
+            }
+            write_word(&mut self.writer, fingerprint::<C::Borrowed<'_>>())?;
+            write_word(&mut self.writer, self.batches.len() as u64)?;
+            write_word(&mut self.writer, MAGIC)?;
+            Ok(self.writer)
+        }
+    }
+
+    /// The bytes of one batch, as a range within shared bytes `B`.
+    ///
+    /// This dereferences to the bytes of the batch, and can back a [`Stash`] without copying.
+    #[derive(Clone)]
+    pub struct BatchBytes<B> {
+        pub(super) bytes: B,
+        pub(super) range: core::ops::Range<usize>,
+    }
+
+    impl<B: core::ops::Deref<Target=[u8]>> core::ops::Deref for BatchBytes<B> {
+        type Target = [u8];
+        #[inline(always)] fn deref(&self) -> &[u8] { &self.bytes[self.range.clone()] }
+    }
+
+    /// Random access to the batches of `C` in bytes `B` produced by a [`BatchWriter`].
+    ///
+    /// Forming the reader validates the framing and the fingerprint. Each batch is validated
+    /// as by [`Stash::try_from_bytes`] when accessed, and its row count checked against the footer.
+    /// If `B` is `u64` aligned, as with memory-mapped files, batches are used in place.
+    #[derive(Clone)]
+    pub struct BatchReader<C, B> {
+        bytes: B,
+        /// For each batch, its end offset in bytes and its number of rows.
+        batches: Vec<[u64; 2]>,
+        marker: core::marker::PhantomData<C>,
+    }
+
+    impl<C: crate::ContainerBytes, B: core::ops::Deref<Target=[u8]> + Clone> BatchReader<C, B> {
+        /// Reads the footer of `bytes`, and checks that it describes batches of `C`.
+        pub fn try_from_bytes(bytes: B) -> Result<Self, String> {
+            let word = |index: usize| -> u64 {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(&bytes[8 * index ..][.. 8]);
+                u64::from_ne_bytes(word)
+            };
+            if bytes.len() % 8 != 0 || bytes.len() < 32 {
+                return Err(format!("batches: length {} is not a multiple of 8 of at least 32", bytes.len()));
+            }
+            let words = bytes.len() / 8;
+            if word(0) != MAGIC || word(words - 1) != MAGIC {
+                return Err("batches: missing header or trailer".into());
+            }
+            let expected = fingerprint::<C::Borrowed<'_>>();
+            if word(words - 3) != expected {
+                return Err(format!("batches: fingerprint {:#x} does not match the expected {:#x}", word(words - 3), expected));
+            }
+            let count = word(words - 2);
+            let footer = usize::try_from(count).ok().and_then(|count| count.checked_mul(2)).filter(|len| *len <= words - 4);
+            let footer = footer.map(|len| words - 3 - len).ok_or_else(|| format!("batches: {} batches do not fit in {} words", count, words))?;
+            let mut batches = Vec::with_capacity(count as usize);
+            let mut prior = 8;
+            for index in 0 .. count as usize {
+                let (offset, rows) = (word(footer + 2 * index), word(footer + 2 * index + 1));
+                if offset < prior || offset > 8 * footer as u64 || offset % 8 != 0 {
+                    return Err(format!("batches: batch {} ends at invalid offset {}", index, offset));
+                }
+                batches.push([offset, rows]);
+                prior = offset;
+            }
+            if prior != 8 * footer as u64 {
+                return Err(format!("batches: batches end at {} but the footer starts at {}", prior, 8 * footer));
+            }
+            Ok(Self { bytes, batches, marker: core::marker::PhantomData })
+        }
+        /// The number of batches.
+        #[inline(always)] pub fn len(&self) -> usize { self.batches.len() }
+        /// True when there are no batches.
+        #[inline(always)] pub fn is_empty(&self) -> bool { self.batches.is_empty() }
+        /// The number of rows in batch `index`, as recorded in the footer.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `index` is not less than the number of batches.
+        #[inline(always)] pub fn rows(&self, index: usize) -> usize { self.batches[index][1] as usize }
+        /// The total number of rows in all batches, as recorded in the footer.
+        ///
+        /// Returns an error if the total does not fit in a `usize`, which the batches themselves cannot hold.
+        pub fn total_rows(&self) -> Result<usize, String> {
+            let mut total: usize = 0;
+            for [_, rows] in self.batches.iter() {
+                total = usize::try_from(*rows).ok().and_then(|rows| total.checked_add(rows))
+                    .ok_or_else(|| format!("batches: total rows {} plus {} overflow `usize`", total, rows))?;
+            }
+            Ok(total)
+        }
+        /// The batch at `index`, validated and checked against its recorded number of rows.
+        pub fn batch(&self, index: usize) -> Result<Stash<C, BatchBytes<B>>, String> {
+            if index >= self.len() {
+                return Err(format!("batches: batch {} requested of {} batches", index, self.len()));
+            }
+            let lower = if index == 0 { 8 } else { self.batches[index - 1][0] as usize };
+            let upper = self.batches[index][0] as usize;
+            let stash = Stash::try_from_bytes(BatchBytes { bytes: self.bytes.clone(), range: lower .. upper })?;
+            if stash.len() != self.rows(index) {
+                return Err(format!("batches: batch {} has {} rows but the footer records {}", index, stash.len(), self.rows(index)));
+            }
+            Ok(stash)
+        }
+        /// Iterates over the batches in order, validating each as it is reached.
+        pub fn iter(&self) -> impl Iterator<Item = Result<Stash<C, BatchBytes<B>>, String>> + '_ {
+            (0 .. self.len()).map(|index| self.batch(index))
+        }
+    }
+}
+
+/// Incremental decoding of a sequence of [`indexed`] encoded containers from a [`ReadBytes`] source.
+///
+/// The input is any number of containers written one after the other by [`indexed::write`], as
+/// might arrive over a network connection in arbitrary fragments. Each encoding starts with its
+/// offsets, from which the reader learns the length of the container before reading its contents.
+pub mod stream {
+
+    use alloc::{vec::Vec, string::String, sync::Arc};
+    use super::ReadBytes;
+    use super::stash::Stash;
+
+    /// An error encountered while reading a stream of containers.
+    #[derive(Debug)]
+    pub enum StreamError<E> {
+        /// The underlying reader returned an error.
+        Read(E),
+        /// The input ended part way through a container.
+        Truncated,
+        /// A container was invalid, or larger than the reader's limit.
+        Invalid(String),
+    }
+
+    impl<E: core::fmt::Display> core::fmt::Display for StreamError<E> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self {
+                StreamError::Read(error) => write!(f, "read error: {}", error),
+                StreamError::Truncated => write!(f, "input ended within a container"),
+                StreamError::Invalid(error) => write!(f, "invalid container: {}", error),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<E: std::error::Error> std::error::Error for StreamError<E> { }
+
+    /// Reads into `bytes` until it is full or the input ends, and returns the number of bytes read.
+    fn fill<R: ReadBytes>(reader: &mut R, bytes: &mut [u8]) -> Result<usize, StreamError<R::Error>> {
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match reader.read(&mut bytes[filled..]).map_err(StreamError::Read)? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        Ok(filled)
+    }
+
+    /// Reads containers of type `C` one at a time from `R`, validating each as it completes.
+    ///
+    /// Each container is read directly into a `u64` aligned allocation, which is then validated as by
+    /// [`Stash::try_from_bytes`] and returned in the `Align` variant, without further copying.
+    /// The reader is also an iterator over the containers, ending when the input ends between containers.
+    pub struct StreamReader<C, R> {
+        reader: R,
+        /// The largest container in bytes that the reader will allocate for.
+        limit: usize,
+        marker: core::marker::PhantomData<C>,
+    }
+
+    impl<C: crate::ContainerBytes, R: ReadBytes> StreamReader<C, R> {
+        /// Creates a reader of containers from `reader`.
+        pub fn new(reader: R) -> Self {
+            Self { reader, limit: usize::MAX, marker: core::marker::PhantomData }
+        }
+        /// Limits the size in bytes of each container, which otherwise is only limited by the input.
+        ///
+        /// The size is read from the input before allocating, and untrusted input should have a limit.
+        pub fn with_limit(mut self, limit: usize) -> Self {
+            self.limit = limit;
+            self
+        }
+        /// Returns the underlying reader.
+        pub fn into_inner(self) -> R { self.reader }
+        /// Reads the next container, or returns `None` if the input ends before it starts.
+        ///
+        /// After an error the position in the input is unspecified, and reading should stop.
+        #[allow(clippy::type_complexity)]
+        pub fn next_batch(&mut self) -> Result<Option<Stash<C, Vec<u8>>>, StreamError<R::Error>> {
+            // The first word is the byte offset at which the offsets end.
+            let mut first = 0u64;
+            match fill(&mut self.reader, bytemuck::bytes_of_mut(&mut first))? {
+                0 => return Ok(None),
+                8 => { },
+                _ => return Err(StreamError::Truncated),
+            }
+            let slices = <C::Borrowed<'_> as crate::FromBytes>::SLICE_COUNT;
+            if first != 8 * (1 + slices as u64) {
+                return Err(StreamError::Invalid(format!("expected {} slices but found offsets ending at {}", slices, first)));
+            }
+            let mut header = Vec::with_capacity(1 + slices);
+            header.push(first);
+            header.resize(1 + slices, 0);
+            if fill(&mut self.reader, bytemuck::cast_slice_mut(&mut header[1..]))? < 8 * slices {
+                return Err(StreamError::Truncated);
+            }
+            // The last offset is where the last slice ends, rounded up to a whole word.
+            let end = header.last().copied().unwrap_or(first);
+            let words = usize::try_from(end.div_ceil(8)).ok().filter(|words| words.checked_mul(8).is_some_and(|bytes| bytes <= self.limit));
+            let words = words.ok_or_else(|| StreamError::Invalid(format!("container of {} bytes exceeds the limit of {}", end, self.limit)))?;
+            if words < header.len() {
+                return Err(StreamError::Invalid(format!("last offset {} precedes the end of the offsets at {}", end, first)));
+            }
+            let mut store: Arc<[u64]> = core::iter::repeat(0).take(words).collect();
+            let slots = Arc::get_mut(&mut store).unwrap();
+            slots[.. header.len()].copy_from_slice(&header);
+            if fill(&mut self.reader, bytemuck::cast_slice_mut(&mut slots[header.len() ..]))? < 8 * (words - header.len()) {
+                return Err(StreamError::Truncated);
+            }
+            super::indexed::validate_deep::<C::Borrowed<'_>>(&store).map_err(StreamError::Invalid)?;
+            Ok(Some(Stash::Align(store)))
+        }
+    }
+
+    impl<C: crate::ContainerBytes, R: ReadBytes> Iterator for StreamReader<C, R> {
+        type Item = Result<Stash<C, Vec<u8>>, StreamError<R::Error>>;
+        fn next(&mut self) -> Option<Self::Item> { self.next_batch().transpose() }
+    }
+}
+
+/// An encoding that records the schema of the data, so that later versions of a type can read it.
+///
+/// The format starts with the word [`MAGIC`](versioned::MAGIC), followed by the length in words of
+/// a header, the header, and the [`indexed`] encoding of the data. The header is itself the `indexed`
+/// encoding of one `(u64, u64, Vec<(String, u64)>)`, holding the version of the type, the number of
+/// elements, and the path and element size of each slice of the data. See [`Evolve`](crate::schema::Evolve)
+/// for how the data are read by a different version of the type.
+pub mod versioned {
+
+    use alloc::{vec::Vec, string::String};
+    use crate::{Borrow, BorrowedOf, ContainerOf, Index, Len};
+    use crate::schema::{Evolve, Schema};
+    use super::WriteBytes;
+    use super::batches::BatchBytes;
+    use super::stash::Stash;
+
+    /// The first word of the format.
+    pub const MAGIC: u64 = u64::from_le_bytes(*b"COLSCHEM");
+
+    /// The header, holding the version, the number of elements, and the path and size of each slice.
+    type Header = (u64, u64, Vec<(String, u64)>);
+
+    /// Writes `item` as data of version [`T::VERSION`](Evolve::VERSION), preceded by its schema.
+    pub fn write<'a, T, W>(writer: &mut W, item: &BorrowedOf<'a, T>) -> Result<(), W::Error>
+    where
+        T: Evolve,
+        ContainerOf<T>: Schema,
+        W: WriteBytes,
+    {
+        let slices = <ContainerOf<T> as Schema>::schema().slices();
+        let header: Header = (T::VERSION, item.len() as u64, slices.into_iter().map(|(path, _, size)| (path, size as u64)).collect());
+        let mut column: ContainerOf<Header> = Default::default();
+        crate::Push::push(&mut column, &header);
+        let mut store = Vec::new();
+        super::indexed::encode(&mut store, &column.borrow());
+        writer.write_all(bytemuck::cast_slice(&[MAGIC, store.len() as u64]))?;
+        writer.write_all(bytemuck::cast_slice(&store))?;
+        super::indexed::write(writer, item)
+    }
+
+    /// Reads data written by [`write`] for any version of `T`, as the current version of `T`.
+    ///
+    /// If the data have the slices of the current version, they are validated as by
+    /// [`Stash::try_from_bytes`] and used in place. Otherwise, the slices of fields present in both
+    /// are copied, those of fields absent from the data are the default values that [`Evolve::default_field`]
+    /// provides, and those of fields absent from `T` are skipped. A field present in both must have
+    /// the same slices with the same element sizes, and the result is validated in the `Align` variant.
+    /// Defaults are only built for at most one element per bit of the data, which bounds what an untrusted
+    /// header can allocate, but rejects data whose fields present in both store nothing for their elements.
+    pub fn read<T, B>(bytes: B) -> Result<Stash<ContainerOf<T>, BatchBytes<B>>, String>
+    where
+        T: Evolve,
+        ContainerOf<T>: Schema,
+        B: core::ops::Deref<Target=[u8]> + Clone,
+    {
+        if bytes.len() % 8 != 0 { return Err(format!("versioned: length {} is not a multiple of 8", bytes.len())) }
+        match bytemuck::try_cast_slice::<_, u64>(&bytes) {
+            Ok(words) => {
+                let (start, len, evolved) = evolve::<T>(words)?;
+                let stash = match evolved {
+                    Some(store) => Stash::Align(store.into()),
+                    None => Stash::try_from_bytes(BatchBytes { bytes: bytes.clone(), range: 8 * start .. bytes.len() })?,
+                };
+                check_len(stash, len)
+            },
+            Err(_) => {
+                // Re-locating bytes for alignment reasons.
+                let mut words: Vec<u64> = alloc::vec![0; bytes.len() / 8];
+                bytemuck::cast_slice_mut(&mut words[..]).copy_from_slice(&bytes[..]);
+                let (start, len, evolved) = evolve::<T>(&words)?;
+                let store = evolved.unwrap_or_else(|| words[start ..].to_vec());
+                super::indexed::validate_deep::<BorrowedOf<T>>(&store)?;
+                check_len(Stash::Align(store.into()), len)
+            },
+        }
+    }
+
+    /// Checks that `stash` has the number of elements the header records.
+    fn check_len<C: crate::ContainerBytes, B: core::ops::Deref<Target=[u8]>>(stash: Stash<C, B>, len: u64) -> Result<Stash<C, B>, String> {
+        if stash.len() as u64 != len {
+            return Err(format!("versioned: data have {} elements but the header records {}", stash.len(), len));
+        }
+        Ok(stash)
+    }
+
+    /// Reads the header of `words`, and returns the word at which the data start, the recorded number
+    /// of elements, and if the data do not have the slices of `T`, the `indexed` encoding of those slices.
+    fn evolve<T>(words: &[u64]) -> Result<(usize, u64, Option<Vec<u64>>), String>
+    where
+        T: Evolve,
+        ContainerOf<T>: Schema,
+    {
+        if words.len() < 2 || words[0] != MAGIC {
+            return Err("versioned: missing header".into());
+        }
+        let start = usize::try_from(words[1]).ok().and_then(|len| len.checked_add(2)).filter(|start| *start <= words.len());
+        let start = start.ok_or_else(|| format!("versioned: header of {} words exceeds the data", words[1]))?;
+        let header = &words[2 .. start];
+        if super::indexed::validate_deep::<BorrowedOf<Header>>(header)? != 1 {
+            return Err("versioned: header does not hold one entry".into());
+        }
+        let header = <BorrowedOf<Header> as crate::FromBytes>::from_store(&super::indexed::DecodedStore::new(header), &mut 0);
+        let (version, len) = (header.0[0], header.1[0]);
+        let written: Vec<(&[u8], u64)> = header.2.get(0).into_iter().map(|(path, size)| (path, *size)).collect();
+
+        let expected = <ContainerOf<T> as Schema>::schema().slices();
+        if written.len() == expected.len() && written.iter().zip(expected.iter()).all(|((path, size), (p, _, s))| *path == p.as_bytes() && *size == *s as u64) {
+            return Ok((start, len, None));
+        }
+
+        let data = &words[start ..];
+        super::indexed::validate_structure(data, written.len())?;
+        let slices: Vec<&[u8]> = super::indexed::decode(data).collect();
+        let rows = usize::try_from(len).map_err(|_| format!("versioned: length {} does not fit in `usize`", len))?;
+        // The length is not yet validated, and must not build more defaults than the data could hold elements.
+        if rows / 64 > data.len() {
+            return Err(format!("versioned: length {} exceeds the {} bits of the data", len, 64 * data.len()));
+        }
+
+        // For each field of `T`, the positions of its slices in the data, or the encoding of its defaults.
+        let mut fields: Vec<Result<Vec<usize>, Vec<u64>>> = Vec::new();
+        let mut index = 0;
+        while index < expected.len() {
+            let field = expected[index].0.split('.').next().unwrap_or("");
+            let count = expected[index ..].iter().take_while(|(path, _, _)| path.split('.').next() == Some(field)).count();
+            let found: Option<Vec<usize>> = expected[index .. index + count].iter().map(|(path, _, size)| {
+                written.iter().position(|(p, s)| *p == path.as_bytes() && *s == *size as u64)
+            }).collect();
+            if let Some(found) = found {
+                fields.push(Ok(found));
+            }
+            else if written.iter().any(|(path, _)| path.split(|byte| *byte == b'.').next() == Some(field.as_bytes())) {
+                return Err(format!("versioned: field `{}` has different slices in data of version {}", field, version));
+            }
+            else {
+                let store = T::default_field(field, version, rows).ok_or_else(|| format!("versioned: field `{}` is absent from data of version {}", field, version))?;
+                super::indexed::validate_structure(&store, count)?;
+                fields.push(Err(store));
+            }
+            index += count;
+        }
+
+        let mut store = alloc::vec![0u64; 1 + expected.len()];
+        store[0] = (8 * store.len()) as u64;
+        let bytes = fields.iter().flat_map(|field| -> Vec<&[u8]> {
+            match field {
+                Ok(found) => found.iter().map(|position| slices[*position]).collect(),
+                Err(defaults) => super::indexed::decode(defaults).collect(),
+            }
+        });
+        for (index, bytes) in bytes.enumerate() {
+            store[index + 1] = (8 * store.len() + bytes.len()) as u64;
+            store.extend(bytes.chunks(8).map(|chunk| {
+                let mut word = [0u8; 8];
+                word[.. chunk.len()].copy_from_slice(chunk);
+                u64::from_ne_bytes(word)
+            }));
+        }
+        super::indexed::validate_deep::<BorrowedOf<T>>(&store)?;
+        Ok((start, len, Some(store)))
+    }
+}
+
+/// A variant of the [`indexed`] encoding in which each byte slice is compressed independently.
+///
+/// The offset index is as in `indexed` and is not compressed, so a [`DecodedStore`](indexed::DecodedStore)
+/// over the compressed words locates each compressed slice, which can be decompressed on its own.
+/// Each compressed slice starts with a word holding its decompressed length in bytes, with the top bit
+/// set if the slice is stored as is because compression did not make it smaller. The codec is a simple
+/// LZ77 scheme with the sequence format of LZ4 blocks, favoring speed over compression ratio.
+///
+/// Decompression produces `indexed` encoded words, which are validated and decoded as usual.
+/// To read one column, decompress only its slices with [`decompress_range`]. The slices of a field
+/// start after those of the fields before it: the second field of a tuple `(A, B)` has slices
+/// `A::SLICE_COUNT .. A::SLICE_COUNT + B::SLICE_COUNT`, using the `SLICE_COUNT` of the borrowed types.
+#[cfg(feature = "compress")]
+pub mod compressed {
+
+    use alloc::{vec::Vec, string::String};
+    use crate::AsBytes;
+    use super::WriteBytes;
+    use super::indexed::DecodedStore;
+    use super::stash::Stash;
+
+    /// Set in a slice's length word when the slice is stored uncompressed.
+    const STORED: u64 = 1 << 63;
+    /// The shortest match the codec encodes.
+    const MIN_MATCH: usize = 4;
+    /// The number of bits in the hash of four bytes, used to find prior matches.
+    const HASH_BITS: u32 = 12;
+
+    #[inline(always)]
+    fn read_u32(bytes: &[u8], position: usize) -> u32 {
+        u32::from_le_bytes([bytes[position], bytes[position + 1], bytes[position + 2], bytes[position + 3]])
+    }
+
+    /// Appends `length` as a sequence of bytes each 255 except the last.
+    fn push_length(output: &mut Vec<u8>, mut length: usize) {
+        while length >= 255 {
+            output.push(255);
+            length -= 255;
+        }
+        output.push(length as u8);
+    }
+
+    /// Appends a sequence of `literals`, followed by a match of `length` bytes at `offset` back if any.
+    fn push_sequence(output: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+        let extra = matched.map(|(_, length)| length - MIN_MATCH).unwrap_or(0);
+        output.push(((literals.len().min(15) as u8) << 4) | extra.min(15) as u8);
+        if literals.len() >= 15 { push_length(output, literals.len() - 15); }
+        output.extend_from_slice(literals);
+        if let Some((offset, _)) = matched {
+            output.extend_from_slice(&(offset as u16).to_le_bytes());
+            if extra >= 15 { push_length(output, extra - 15); }
+        }
+    }
+
+    /// Appends the compressed form of `input` to `output`.
+    fn compress(input: &[u8], output: &mut Vec<u8>) {
+        let mut table = alloc::vec![0usize; 1 << HASH_BITS];
+        let (mut anchor, mut position) = (0, 0);
+        while position + MIN_MATCH <= input.len() {
+            let word = read_u32(input, position);
+            let hash = (word.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize;
+            let candidate = core::mem::replace(&mut table[hash], position);
+            if candidate < position && position - candidate <= u16::MAX as usize && read_u32(input, candidate) == word {
+                let mut length = MIN_MATCH;
+                while position + length < input.len() && input[candidate + length] == input[position + length] {
+                    length += 1;
+                }
+                push_sequence(output, &input[anchor .. position], Some((position - candidate, length)));
+                position += length;
+                anchor = position;
+            }
+            else {
+                position += 1;
+            }
+        }
+        push_sequence(output, &input[anchor ..], None);
+    }
+
+    /// Reads a length continued by bytes each 255 except the last.
+    fn read_length(input: &[u8], position: &mut usize) -> Result<usize, String> {
+        let mut length = 0usize;
+        loop {
+            let byte = *input.get(*position).ok_or("compressed: truncated length")?;
+            *position += 1;
+            length = length.checked_add(byte as usize).ok_or("compressed: length overflow")?;
+            if byte < 255 { return Ok(length); }
+        }
+    }
+
+    /// Appends the decompression of `input` to `output`, which must produce exactly `length` bytes.
+    fn decompress(input: &[u8], length: usize, output: &mut Vec<u8>) -> Result<(), String> {
+        // Each input byte produces at most 255 output bytes, which bounds what we reserve.
+        if length > input.len().saturating_mul(255) {
+            return Err(format!("compressed: {} input bytes cannot produce {} bytes", input.len(), length));
+        }
+        let start = output.len();
+        output.reserve(length);
+        let mut position = 0;
+        while position < input.len() {
+            let token = input[position];
+            position += 1;
+            let mut literals = (token >> 4) as usize;
+            if literals == 15 { literals += read_length(input, &mut position)?; }
+            let bytes = position.checked_add(literals).and_then(|end| input.get(position .. end)).ok_or("compressed: truncated literals")?;
+            if output.len() - start + literals > length { return Err("compressed: output exceeds length".into()); }
+            output.extend_from_slice(bytes);
+            position += literals;
+            // The last sequence has only literals.
+            if position == input.len() { break; }
+            let offset = input.get(position .. position + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize).ok_or("compressed: truncated offset")?;
+            position += 2;
+            let mut matched = (token & 15) as usize + MIN_MATCH;
+            if token & 15 == 15 { matched += read_length(input, &mut position)?; }
+            if offset == 0 || offset > output.len() - start {
+                return Err(format!("compressed: invalid match offset {}", offset));
+            }
+            if output.len() - start + matched > length { return Err("compressed: output exceeds length".into()); }
+            // Matches may overlap the bytes they produce, and are copied one byte at a time.
+            for _ in 0 .. matched {
+                output.push(output[output.len() - offset]);
+            }
+        }
+        if output.len() - start != length {
+            return Err(format!("compressed: produced {} bytes rather than {}", output.len() - start, length));
+        }
+        Ok(())
+    }
+
+    /// Writes `item` in the compressed encoding, with each slice compressed independently.
+    ///
+    /// The offsets and lengths are as in [`indexed::write`](super::indexed::write), applied to the compressed slices.
+    pub fn write<'a, A, W>(writer: &mut W, item: &A) -> Result<(), W::Error>
+    where
+        A: AsBytes<'a>,
+        W: WriteBytes,
+    {
+        // Compress each slice, preceded by its length word, to learn the offsets before writing.
+        let mut slices = Vec::with_capacity(A::SLICE_COUNT);
+        for index in 0 .. A::SLICE_COUNT {
+            let (_align, bytes) = item.get_byte_slice(index);
+            let mut slice = Vec::with_capacity(8 + bytes.len() / 2);
+            slice.extend_from_slice(&(bytes.len() as u64).to_ne_bytes());
+            compress(bytes, &mut slice);
+            if slice.len() >= 8 + bytes.len() {
+                slice.clear();
+                slice.extend_from_slice(&(bytes.len() as u64 | STORED).to_ne_bytes());
+                slice.extend_from_slice(bytes);
+            }
+            slices.push(slice);
+        }
+        let mut position = (8 * (1 + A::SLICE_COUNT)) as u64;
+        writer.write_all(&position.to_ne_bytes())?;
+        for slice in slices.iter() {
+            writer.write_all(&(position + slice.len() as u64).to_ne_bytes())?;
+            position += ((slice.len() + 7) & !7) as u64;
+        }
+        for slice in slices.iter() {
+            writer.write_all(slice)?;
+            let padding = ((slice.len() + 7) & !7) - slice.len();
+            if padding > 0 { writer.write_all(&[0u8; 8][.. padding])?; }
+        }
+        Ok(())
+    }
+
+    /// Appends the decompressed bytes of slice `index` of `store` to `output`.
+    pub fn decompress_slice(store: &DecodedStore<'_>, index: usize, output: &mut Vec<u8>) -> Result<(), String> {
+        let (words, tail) = store.get(index);
+        let bytes: &[u8] = bytemuck::cast_slice(words);
+        let bytes = &bytes[.. if tail == 0 { bytes.len() } else { bytes.len().saturating_sub(8 - tail as usize) }];
+        let header = bytes.get(.. 8).ok_or_else(|| format!("compressed: slice {} has no length", index))?;
+        let header = u64::from_ne_bytes(header.try_into().unwrap());
+        let length = usize::try_from(header & !STORED).map_err(|_| format!("compressed: slice {} length {} does not fit in `usize`", index, header & !STORED))?;
+        if header & STORED != 0 {
+            if bytes.len() - 8 != length {
+                return Err(format!("compressed: stored slice {} has {} bytes rather than {}", index, bytes.len() - 8, length));
+            }
+            output.extend_from_slice(&bytes[8 ..]);
+            Ok(())
+        }
+        else {
+            decompress(&bytes[8 ..], length, output)
+        }
+    }
+
+    /// Decompresses the slices in `range` of `store` into an `indexed` encoding of just those slices.
+    ///
+    /// Slices outside of `range` are not decompressed.
+    pub fn decompress_range(store: &[u64], range: core::ops::Range<usize>) -> Result<Vec<u64>, String> {
+        let slices = store.first().map(|first| (*first as usize / 8).saturating_sub(1)).unwrap_or(0);
+        super::indexed::validate_structure(store, slices)?;
+        if range.start > range.end || range.end > slices {
+            return Err(format!("compressed: range {:?} exceeds {} slices", range, slices));
+        }
+        let decoded = DecodedStore::new(store);
+        let mut words = alloc::vec![0u64; 1 + range.len()];
+        words[0] = (8 * (1 + range.len())) as u64;
+        let mut buffer = Vec::new();
+        for (offset, index) in range.enumerate() {
+            buffer.clear();
+            decompress_slice(&decoded, index, &mut buffer)?;
+            words[offset + 1] = (8 * words.len() + buffer.len()) as u64;
+            words.extend(buffer.chunks(8).map(|chunk| {
+                let mut word = [0u8; 8];
+                word[.. chunk.len()].copy_from_slice(chunk);
+                u64::from_ne_bytes(word)
+            }));
+        }
+        Ok(words)
+    }
+
+    /// Decompresses all slices of `store` into an `indexed` encoding.
+    pub fn decompress_all(store: &[u64]) -> Result<Vec<u64>, String> {
+        let slices = store.first().map(|first| (*first as usize / 8).saturating_sub(1)).unwrap_or(0);
+        decompress_range(store, 0 .. slices)
+    }
+
+    impl<C: crate::ContainerBytes, B> Stash<C, B> {
+        /// Decompresses and validates `bytes` produced by [`write`], into the `Align` variant.
+        ///
+        /// The validation is that of [`Stash::try_from_bytes`], applied to the decompressed data.
+        pub fn try_from_compressed(bytes: &[u8]) -> Result<Self, String> {
+            if bytes.len() % 8 != 0 { return Err(format!("bytes.len() = {:?} not a multiple of 8", bytes.len())) }
+            let words = match bytemuck::try_cast_slice::<_, u64>(bytes) {
+                Ok(words) => decompress_all(words)?,
+                Err(_) => {
+                    let mut words: Vec<u64> = alloc::vec![0; bytes.len() / 8];
+                    bytemuck::cast_slice_mut(&mut words[..]).copy_from_slice(bytes);
+                    decompress_all(&words)?
+                }
+            };
+            super::indexed::validate::<<C as crate::Borrow>::Borrowed<'_>>(&words)?;
+            Ok(Stash::Align(words.into()))
+        }
+    }
+}
+
+/// Memory-mapped file contents, which can back a `Stash` without reading the file into memory.
+#[cfg(feature = "mmap")]
+pub mod mmap {
+
+    use std::{fs::File, io, path::Path, sync::Arc};
+    use super::stash::Stash;
+    use super::batches::BatchReader;
+
+    /// The read-only, memory-mapped contents of a file, which dereference to a byte slice.
+    ///
+    /// Mappings are page aligned, so a `Stash` formed from these bytes will use them in place as its
+    /// `Bytes` variant, rather than copy them into its `Align` variant. Clones share the mapping.
+    #[derive(Clone)]
+    pub struct MmapBytes {
+        map: Arc<memmap2::Mmap>,
+    }
+
+    impl MmapBytes {
+        /// Maps the contents of the file at `path`.
+        ///
+        /// The operating system pages in the contents as they are accessed.
+        ///
+        /// # Safety
+        ///
+        /// The file must not be modified or truncated, by this or any other process, while it is mapped:
+        /// the mapped bytes are assumed to be immutable, and a truncated file can fault on access.
+        /// This is the contract of [`memmap2::Mmap::map`], which this calls.
+        pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+            let file = File::open(path)?;
+            // SAFETY: the caller promises that the file does not change while mapped.
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+            Ok(Self { map: Arc::new(map) })
+        }
+    }
+
+    impl From<memmap2::Mmap> for MmapBytes {
+        /// Uses a mapping the caller has made, and for which the caller has upheld the contract of `Mmap::map`.
+        fn from(map: memmap2::Mmap) -> Self { Self { map: Arc::new(map) } }
+    }
+
+    impl core::ops::Deref for MmapBytes {
+        type Target = [u8];
+        #[inline(always)] fn deref(&self) -> &[u8] { &self.map[..] }
+    }
+
+    impl<C: crate::ContainerBytes> Stash<C, MmapBytes> {
+        /// Opens the file at `path` as a memory-mapped `Stash`, validated as by `Stash::try_from_bytes`.
+        ///
+        /// Invalid contents result in an error of kind `InvalidData`. Only the framing is validated,
+        /// which reads the start of the file; callers who want the internal structure checked as well
+        /// can pass the bytes of the result to [`Stash::try_from_bytes_deep`], which reads all of it.
+        ///
+        /// # Safety
+        ///
+        /// As for [`MmapBytes::open`], the file must not change while the stash, or any clone of it, is live.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// use columnar::{Borrow, ContainerOf, Index, Push};
+        /// use columnar::bytes::stash::Stash;
+        /// use columnar::bytes::mmap::MmapBytes;
+        ///
+        /// let mut stash: Stash<ContainerOf<(u64, String)>, MmapBytes> = Default::default();
+        /// stash.push(&(0u64, format!("hello")));
+        /// stash.push(&(1u64, format!("world")));
+        ///
+        /// let path = std::env::temp_dir().join("columnar-doctest-mmap.bin");
+        /// stash.write_to_path(&path).unwrap();
+        /// // SAFETY: nothing else modifies the file while it is mapped.
+        /// let stash: Stash<ContainerOf<(u64, String)>, MmapBytes> = unsafe { Stash::open(&path) }.unwrap();
+        /// assert!(matches!(stash, Stash::Bytes(_)));
+        /// assert_eq!(stash.borrow().1.get(1), b"world");
+        /// # drop(stash);
+        /// # std::fs::remove_file(&path).unwrap();
+        /// ```
+        pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+            // SAFETY: the caller promises that the file does not change while mapped.
+            let bytes = unsafe { MmapBytes::open(path)? };
+            Self::try_from_bytes(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        }
+    }
+
+    impl<C: crate::ContainerBytes> BatchReader<C, MmapBytes> {
+        /// Opens the file at `path` as memory-mapped batches, validated as by `BatchReader::try_from_bytes`.
+        ///
+        /// Only the footer is read on opening, and each batch is validated structurally when accessed.
+        ///
+        /// # Safety
+        ///
+        /// As for [`MmapBytes::open`], the file must not change while the reader, or any clone of it, is live.
+        pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+            // SAFETY: the caller promises that the file does not change while mapped.
+            let bytes = unsafe { MmapBytes::open(path)? };
+            Self::try_from_bytes(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::ContainerOf;
@@ -566,19 +1457,19 @@ mod test {
         assert_eq!(column.len(), 200);
 
         for i in 0..100 {
-            assert_eq!(column.get(2*i+0), Ok(i as u64));
+            assert_eq!(column.get(2*i), Ok(i as u64));
             assert_eq!(column.get(2*i+1), Err(i as u64));
         }
 
         let column2 = crate::Results::<&[u64], &[u64], &[u64], &[u64], &[u64]>::from_bytes(&mut column.borrow().as_bytes().map(|(_, bytes)| bytes));
         for i in 0..100 {
-            assert_eq!(column.get(2*i+0), column2.get(2*i+0).copied().map_err(|e| *e));
+            assert_eq!(column.get(2*i), column2.get(2*i).copied().map_err(|e| *e));
             assert_eq!(column.get(2*i+1), column2.get(2*i+1).copied().map_err(|e| *e));
         }
 
         let column3 = crate::Results::<&[u64], &[u64], &[u64], &[u64], &[u64]>::from_bytes(&mut column2.as_bytes().map(|(_, bytes)| bytes));
         for i in 0..100 {
-            assert_eq!(column3.get(2*i+0), column2.get(2*i+0));
+            assert_eq!(column3.get(2*i), column2.get(2*i));
             assert_eq!(column3.get(2*i+1), column2.get(2*i+1));
         }
 
@@ -588,7 +1479,7 @@ mod test {
         let ds = crate::bytes::indexed::DecodedStore::new(&store);
         let column4 = crate::Results::<&[u64], &[u64], &[u64], &[u64], &[u64]>::from_store(&ds, &mut 0);
         for i in 0..100 {
-            assert_eq!(column.get(2*i+0), column4.get(2*i+0).copied().map_err(|e| *e));
+            assert_eq!(column.get(2*i), column4.get(2*i).copied().map_err(|e| *e));
             assert_eq!(column.get(2*i+1), column4.get(2*i+1).copied().map_err(|e| *e));
         }
     }
@@ -627,14 +1518,15 @@ mod test {
         assert_eq!(stash.borrow().get(2), Some(&b"world"[..]));
 
         // Result<(u64, String), u64>
-        let mut c: ContainerOf<Result<(u64, String), u64>> = Default::default();
-        let val: Result<(u64, String), u64> = Ok((42, "test".to_string()));
+        type R = Result<(u64, String), u64>;
+        let mut c: ContainerOf<R> = Default::default();
+        let val: R = Ok((42, "test".to_string()));
         c.push(&val);
-        let val2: Result<(u64, String), u64> = Err(99);
+        let val2: R = Err(99);
         c.push(&val2);
         let mut bytes: Vec<u8> = Vec::new();
         crate::bytes::indexed::write(&mut bytes, &c.borrow()).unwrap();
-        let stash: Stash<ContainerOf<Result<(u64, String), u64>>, Vec<u8>> =
+        let stash: Stash<ContainerOf<R>, Vec<u8>> =
             Stash::try_from_bytes(bytes).expect("Result<(u64, String), u64> should validate");
         let borrowed = stash.borrow();
         match borrowed.get(0) {
@@ -647,6 +1539,259 @@ mod test {
         }
     }
 
+    /// Test that try_from_bytes_deep rejects well-framed bytes whose contents are inconsistent.
+    #[test]
+    fn validate_deep_rejects() {
+        use crate::{Strings, Vecs, ContainerOf};
+        use crate::bytes::stash::Stash;
+
+        fn stash<'a, C: crate::ContainerBytes>(item: &impl crate::AsBytes<'a>) -> Result<Stash<C, Vec<u8>>, String> {
+            let mut bytes: Vec<u8> = Vec::new();
+            crate::bytes::indexed::write(&mut bytes, item).unwrap();
+            Stash::try_from_bytes_deep(bytes)
+        }
+
+        // Bounds that decrease, or that exceed the values.
+        let strings = Strings { bounds: &[3u64, 1][..], values: &b"abc"[..] };
+        assert!(stash::<Strings>(&strings).is_err());
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &strings).unwrap();
+        assert!(Stash::<Strings, Vec<u8>>::try_from_bytes(bytes).is_ok());
+        let strings = Strings { bounds: &[1u64, 4][..], values: &b"abc"[..] };
+        assert!(stash::<Strings>(&strings).is_err());
+        let vecs = Vecs { bounds: &[2u64][..], values: &[0u64][..] };
+        assert!(stash::<Vecs<Vec<u64>>>(&vecs).is_err());
+
+        // Tuple fields of unequal lengths.
+        let tuple = (&[0u64, 1][..], &[0u32][..]);
+        assert!(stash::<ContainerOf<(u64, u32)>>(&tuple).is_err());
+
+        // The same containers when consistent.
+        let strings = Strings { bounds: &[1u64, 3][..], values: &b"abc"[..] };
+        assert!(stash::<Strings>(&strings).is_ok());
+        let tuple = (&[0u64, 1][..], &[0u32, 1][..]);
+        assert!(stash::<ContainerOf<(u64, u32)>>(&tuple).is_ok());
+    }
+
+    /// Test that types without their own `validate_deep` pass structural validation, but not deep validation.
+    #[test]
+    fn validate_deep_default() {
+        use crate::FromBytes;
+        use crate::bytes::indexed::{validate, validate_deep};
+
+        struct Words<'a>(&'a [u64]);
+        impl<'a> crate::FromBytes<'a> for Words<'a> {
+            const SLICE_COUNT: usize = 1;
+            fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self { Words(crate::FromBytes::from_bytes(bytes)) }
+            fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { <&[u64]>::element_sizes(sizes) }
+        }
+
+        let mut store = Vec::new();
+        crate::bytes::indexed::encode(&mut store, &&[0u64, 1, 2][..]);
+        assert!(validate::<Words>(&store).is_ok());
+        assert!(validate_deep::<Words>(&store).is_err());
+        assert_eq!(Words::from_store(&crate::bytes::indexed::DecodedStore::new(&store), &mut 0).0, [0, 1, 2]);
+
+        // Containers of such types are also rejected by deep validation.
+        let mut store = Vec::new();
+        crate::bytes::indexed::encode(&mut store, &(&[0u64, 1, 2][..], &[0u32, 1, 2][..]));
+        assert!(validate::<(Words, &[u32])>(&store).is_ok());
+        assert!(validate_deep::<(Words, &[u32])>(&store).is_err());
+        assert_eq!(validate_deep::<(&[u64], &[u32])>(&store), Ok(3));
+    }
+
+    /// Test that files written by a stash can be opened memory-mapped, without relocation.
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn open_mmap() {
+        use crate::common::{Push, Index};
+        use crate::bytes::stash::Stash;
+        use crate::bytes::mmap::MmapBytes;
+
+        let mut stash: Stash<ContainerOf<(u64, String)>, MmapBytes> = Default::default();
+        for i in 0 .. 1000u64 { stash.push(&(i, format!("{i}"))); }
+
+        let path = std::env::temp_dir().join(format!("columnar-open-mmap-{}.bin", std::process::id()));
+        stash.write_to_path(&path).unwrap();
+        // SAFETY: the file is private to the test, and not written while it is mapped.
+        let opened: Stash<ContainerOf<(u64, String)>, MmapBytes> = unsafe { Stash::open(&path) }.unwrap();
+        assert!(matches!(opened, Stash::Bytes(_)));
+        assert_eq!(opened.borrow(), stash.borrow());
+        assert_eq!(opened.borrow().1.get(999), b"999");
+        drop(opened);
+
+        // Batches in a memory-mapped file are also used in place.
+        let mut writer = crate::bytes::batches::BatchWriter::new(Vec::new()).unwrap();
+        writer.write_stash(&stash).unwrap();
+        writer.write_stash(&stash).unwrap();
+        std::fs::write(&path, writer.finish().unwrap()).unwrap();
+        let reader = unsafe { crate::bytes::batches::BatchReader::<ContainerOf<(u64, String)>, MmapBytes>::open(&path) }.unwrap();
+        assert!(matches!(reader.batch(1).unwrap(), Stash::Bytes(_)));
+        assert_eq!(reader.batch(1).unwrap().borrow(), stash.borrow());
+        drop(reader);
+
+        // Opening checks only the framing, leaving the internal structure to deep validation.
+        let strings = crate::Strings { bounds: &[3u64, 1][..], values: &b"abc"[..] };
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &strings).unwrap();
+        std::fs::write(&path, bytes).unwrap();
+        let opened = unsafe { Stash::<crate::Strings, MmapBytes>::open(&path) }.unwrap();
+        let Stash::Bytes(bytes) = opened else { panic!("mapped bytes are used in place") };
+        assert!(Stash::<crate::Strings, MmapBytes>::try_from_bytes_deep(bytes).is_err());
+
+        // Contents that do not describe the container type are rejected.
+        std::fs::write(&path, [1u8; 24]).unwrap();
+        let error = unsafe { Stash::<ContainerOf<(u64, String)>, MmapBytes>::open(&path) }.err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Test that batches can be written in sequence, and read back in any order.
+    #[test]
+    fn batches_round_trip() {
+        use crate::common::{Push, Index, Len};
+        use crate::Borrow;
+        use crate::bytes::batches::{BatchWriter, BatchReader};
+
+        type C = ContainerOf<(u64, String)>;
+        let mut writer: BatchWriter<C, Vec<u8>> = BatchWriter::new(Vec::new()).unwrap();
+        for batch in 0 .. 5u64 {
+            let mut column: C = Default::default();
+            for i in 0 .. 10 * batch { column.push(&(i, format!("{batch}:{i}"))); }
+            writer.write(&column.borrow()).unwrap();
+        }
+        assert_eq!(writer.batches(), 5);
+        let bytes = writer.finish().unwrap();
+
+        let reader: BatchReader<C, alloc::sync::Arc<[u8]>> = BatchReader::try_from_bytes(bytes.clone().into()).unwrap();
+        assert_eq!(reader.len(), 5);
+        assert_eq!(reader.total_rows(), Ok(100));
+        let batch = reader.batch(3).unwrap();
+        assert_eq!(batch.len(), 30);
+        assert_eq!(batch.borrow().1.get(7), b"3:7");
+        let lens: Vec<usize> = reader.iter().map(|batch| batch.unwrap().len()).collect();
+        assert_eq!(lens, [0, 10, 20, 30, 40]);
+        assert!(reader.batch(5).is_err());
+
+        // A different container type is rejected by its fingerprint.
+        assert!(BatchReader::<ContainerOf<(u64, u64)>, _>::try_from_bytes(&bytes[..]).is_err());
+
+        // Row counts in the footer are checked against each batch.
+        let mut corrupt = bytes.clone();
+        let rows = corrupt.len() - 8 * 4;
+        corrupt[rows] += 1;
+        let reader: BatchReader<C, &[u8]> = BatchReader::try_from_bytes(&corrupt[..]).unwrap();
+        assert!(reader.batch(4).is_err());
+
+        // Row counts whose total overflows are reported rather than wrapped.
+        corrupt[rows ..][.. 8].copy_from_slice(&u64::MAX.to_ne_bytes());
+        let reader: BatchReader<C, &[u8]> = BatchReader::try_from_bytes(&corrupt[..]).unwrap();
+        assert!(reader.total_rows().is_err());
+
+        // Truncated input is rejected.
+        assert!(BatchReader::<C, _>::try_from_bytes(&bytes[.. bytes.len() - 8]).is_err());
+    }
+
+    /// Test that containers can be read from a stream that arrives in small fragments.
+    #[test]
+    fn stream_fragments() {
+        use crate::common::{Push, Index, Len};
+        use crate::Borrow;
+        use crate::bytes::stash::Stash;
+        use crate::bytes::stream::{StreamReader, StreamError};
+
+        /// Yields at most `chunk` bytes per read.
+        struct Chunks<'a> { bytes: &'a [u8], chunk: usize }
+        impl crate::bytes::ReadBytes for Chunks<'_> {
+            type Error = core::convert::Infallible;
+            fn read(&mut self, bytes: &mut [u8]) -> Result<usize, Self::Error> {
+                let count = self.chunk.min(bytes.len()).min(self.bytes.len());
+                bytes[..count].copy_from_slice(&self.bytes[..count]);
+                self.bytes = &self.bytes[count..];
+                Ok(count)
+            }
+        }
+
+        type C = ContainerOf<(u64, String)>;
+        let mut bytes: Vec<u8> = Vec::new();
+        for batch in 0 .. 4u64 {
+            let mut column: C = Default::default();
+            for i in 0 .. batch { column.push(&(i, format!("{batch}:{i}"))); }
+            crate::bytes::indexed::write(&mut bytes, &column.borrow()).unwrap();
+        }
+
+        for chunk in [1, 3, 8, 1000] {
+            let reader: StreamReader<C, _> = StreamReader::new(Chunks { bytes: &bytes, chunk });
+            let batches: Vec<Stash<C, Vec<u8>>> = reader.map(|batch| batch.unwrap()).collect();
+            assert_eq!(batches.iter().map(|batch| batch.len()).collect::<Vec<_>>(), [0, 1, 2, 3]);
+            assert_eq!(batches[3].borrow().1.get(2), b"3:2");
+        }
+
+        // Input that ends within a container, or exceeds the limit, is an error.
+        let mut reader: StreamReader<C, _> = StreamReader::new(Chunks { bytes: &bytes[.. bytes.len() - 8], chunk: 5 });
+        assert!(reader.by_ref().take(3).all(|batch| batch.is_ok()));
+        assert!(matches!(reader.next(), Some(Err(StreamError::Truncated))));
+        let mut reader: StreamReader<C, _> = StreamReader::new(Chunks { bytes: &bytes, chunk: 5 }).with_limit(64);
+        assert!(matches!(reader.nth(3), Some(Err(StreamError::Invalid(_)))));
+
+        // Containers of a different type are rejected.
+        let mut reader: StreamReader<ContainerOf<u64>, _> = StreamReader::new(Chunks { bytes: &bytes, chunk: 5 });
+        assert!(matches!(reader.next(), Some(Err(StreamError::Invalid(_)))));
+    }
+
+    /// Test that the compressed encoding round trips, and can decompress individual columns.
+    #[cfg(feature = "compress")]
+    #[test]
+    fn compressed_round_trip() {
+        use crate::common::{Push, Index, Len};
+        use crate::{Borrow, FromBytes, Strings};
+        use crate::bytes::stash::Stash;
+        use crate::bytes::compressed;
+
+        type C = ContainerOf<(u64, String)>;
+        let mut column: C = Default::default();
+        for i in 0 .. 10_000u64 { column.push(&(i % 7, format!("item {}", i % 100))); }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        compressed::write(&mut bytes, &column.borrow()).unwrap();
+        assert!(bytes.len() * 4 < crate::bytes::indexed::length_in_bytes(&column.borrow()));
+
+        let stash: Stash<C, Vec<u8>> = Stash::try_from_compressed(&bytes).unwrap();
+        assert_eq!(stash.borrow(), column.borrow());
+
+        // Decompress only the slices of the strings, which follow the one slice of the integers.
+        // Copy into words, as the bytes of a `Vec<u8>` need not be aligned for `u64`.
+        let mut words: Vec<u64> = vec![0; bytes.len() / 8];
+        bytemuck::cast_slice_mut(&mut words[..]).copy_from_slice(&bytes);
+        let strings = compressed::decompress_range(&words, 1 .. 3).unwrap();
+        crate::bytes::indexed::validate_deep::<Strings<&[u64], &[u8]>>(&strings).unwrap();
+        let store = crate::bytes::indexed::DecodedStore::new(&strings);
+        let strings = Strings::<&[u64], &[u8]>::from_store(&store, &mut 0);
+        assert_eq!(strings.len(), 10_000);
+        assert_eq!(strings.get(1234), b"item 34");
+
+        // Incompressible slices are stored as they are, and empty containers round trip.
+        let noise: Vec<u64> = (0 .. 1000u64).map(|i| i.wrapping_mul(0x9E3779B97F4A7C15).rotate_left(17)).collect();
+        let mut bytes: Vec<u8> = Vec::new();
+        compressed::write(&mut bytes, &&noise[..]).unwrap();
+        assert!(bytes.len() <= 8 * (noise.len() + 3));
+        let stash: Stash<Vec<u64>, Vec<u8>> = Stash::try_from_compressed(&bytes).unwrap();
+        assert_eq!(stash.borrow(), &noise[..]);
+        let mut bytes: Vec<u8> = Vec::new();
+        compressed::write(&mut bytes, &C::default().borrow()).unwrap();
+        assert_eq!(Stash::<C, Vec<u8>>::try_from_compressed(&bytes).unwrap().len(), 0);
+
+        // Corrupt data produces errors, rather than panics.
+        let mut bytes: Vec<u8> = Vec::new();
+        compressed::write(&mut bytes, &column.borrow()).unwrap();
+        for position in (16 .. bytes.len()).step_by(31) {
+            let mut corrupt = bytes.clone();
+            corrupt[position] ^= 0x5A;
+            let _ = Stash::<C, Vec<u8>>::try_from_compressed(&corrupt);
+        }
+        assert!(compressed::decompress_range(&words, 2 .. 4).is_err());
+    }
+
     /// Test from_store for tuples.
     #[test]
     fn from_store_tuple() {
@@ -0,0 +1,284 @@
+//! A container that stores each distinct value once, and a code for each element.
+//!
+//! This is more efficient than a list of `T` when there are few distinct values, as with
+//! low cardinality columns like country codes or event types, anywhere within the container.
+//! Values must be ordered, and each element is referenced by a `u32` code into the distinct values.
+//! Elements can be compared by their codes, which are equal exactly when the elements are equal.
+//!
+//! A field of a derived type can use a dictionary with the derive attribute `#[columnar(with = Dictionary)]`,
+//! or name the whole container with `#[columnar(container = Dictionary<..>)]`.
+use alloc::{vec::Vec, string::String};
+
+use crate::{Push, Index, Len, Clear, Borrow, Container, IndexAs};
+
+/// A container that encodes each element as a code into a container of distinct values.
+///
+/// The `values` container holds each distinct value once, in order of first appearance.
+/// The `codes` container holds for each element the position of its value in `values`.
+/// The `order` container holds the codes sorted by their values, which allows us to find
+/// the code for a value, both while pushing and in the borrowed form.
+///
+/// Pushing a value already present takes time logarithmic in the number of distinct values,
+/// but pushing a new value inserts its code into `order`, which takes time linear in the number
+/// of distinct values. Building a dictionary of `n` distinct values takes time quadratic in `n`,
+/// and so a dictionary should be used only where the distinct values are few.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Dictionary<TC, CC = Vec<u32>, OC = Vec<u32>> {
+    /// Distinct values, in order of first appearance.
+    pub values: TC,
+    /// The code of each element, indexing into `values`.
+    pub codes: CC,
+    /// Codes sorted by their values.
+    pub order: OC,
+}
+
+impl<TC: Index + Len, CC: IndexAs<u32>, OC: IndexAs<u32> + Len> Dictionary<TC, CC, OC> {
+    /// The code of the element at `index`.
+    ///
+    /// Two elements are equal exactly when their codes are equal.
+    #[inline(always)] pub fn code(&self, index: usize) -> u32 { self.codes.index_as(index) }
+    /// The value for `code`.
+    #[inline(always)] pub fn value(&self, code: u32) -> TC::Ref { self.values.get(code as usize) }
+    /// The number of distinct values.
+    #[inline(always)] pub fn distinct(&self) -> usize { self.values.len() }
+    /// The code for `item`, should it be present.
+    ///
+    /// Filtering by equality with `item` can then compare codes rather than values.
+    pub fn code_of<T>(&self, item: T) -> Option<u32> where TC::Ref: PartialOrd<T> {
+        let (mut lower, mut upper) = (0, self.order.len());
+        while lower < upper {
+            let middle = lower + (upper - lower) / 2;
+            if self.value(self.order.index_as(middle)) < item { lower = middle + 1; }
+            else { upper = middle; }
+        }
+        if lower < self.order.len() {
+            let code = self.order.index_as(lower);
+            if self.value(code) == item { return Some(code); }
+        }
+        None
+    }
+}
+
+impl<T, TC: Push<T> + Len> Push<T> for Dictionary<TC>
+where
+    for<'a> &'a TC: Index,
+    for<'a> <&'a TC as Index>::Ref : PartialOrd<T>,
+{
+    #[inline]
+    fn push(&mut self, item: T) {
+        // Find the position of `item` among the sorted values, and whether it is already present.
+        let position = self.order.partition_point(|code| (&self.values).get(*code as usize) < item);
+        let found = self.order[position..].first().copied().filter(|code| (&self.values).get(*code as usize) == item);
+        let code = match found {
+            Some(code) => code,
+            None => {
+                let code: u32 = self.values.len().try_into().expect("too many distinct values for `u32` codes");
+                self.values.push(item);
+                self.order.insert(position, code);
+                code
+            }
+        };
+        self.codes.push(code);
+    }
+}
+
+impl<TC, CC: Len, OC> Len for Dictionary<TC, CC, OC> {
+    #[inline(always)] fn len(&self) -> usize { self.codes.len() }
+}
+
+impl<TC: Index, CC: IndexAs<u32>, OC> Index for Dictionary<TC, CC, OC> {
+    type Ref = TC::Ref;
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref {
+        self.values.get(self.codes.index_as(index) as usize)
+    }
+}
+
+impl<'a, TC> Index for &'a Dictionary<TC>
+where
+    &'a TC: Index,
+{
+    type Ref = <&'a TC as Index>::Ref;
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref {
+        (&self.values).get(self.codes[index] as usize)
+    }
+}
+
+impl<TC: Borrow> Borrow for Dictionary<TC> {
+    type Ref<'a> = TC::Ref<'a> where TC: 'a;
+    type Borrowed<'a> = Dictionary<TC::Borrowed<'a>, &'a [u32], &'a [u32]> where TC: 'a;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        Dictionary {
+            values: self.values.borrow(),
+            codes: &self.codes[..],
+            order: &self.order[..],
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where TC: 'a {
+        Dictionary {
+            values: TC::reborrow(thing.values),
+            codes: thing.codes,
+            order: thing.order,
+        }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a {
+        TC::reborrow_ref(thing)
+    }
+}
+
+impl<TC: Container> Container for Dictionary<TC>
+where
+    for<'a> &'a TC: Index,
+    for<'a, 'b> <&'a TC as Index>::Ref: PartialOrd<TC::Ref<'b>>,
+{
+    // Codes are relative to `other.values`, and must be translated to codes in `self.values`.
+    // Use the default implementation, which resolves each element via `get()` and re-pushes.
+
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.codes.reserve_for(selves.map(|x| x.codes));
+    }
+}
+
+impl<TC: Clear> Clear for Dictionary<TC> {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.codes.clear();
+        self.order.clear();
+    }
+}
+
+impl<'a, TC: crate::AsBytes<'a>, CC: crate::AsBytes<'a>, OC: crate::AsBytes<'a>> crate::AsBytes<'a> for Dictionary<TC, CC, OC> {
+    const SLICE_COUNT: usize = TC::SLICE_COUNT + CC::SLICE_COUNT + OC::SLICE_COUNT;
+    #[inline]
+    fn get_byte_slice(&self, index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        if index < TC::SLICE_COUNT {
+            self.values.get_byte_slice(index)
+        } else if index < TC::SLICE_COUNT + CC::SLICE_COUNT {
+            self.codes.get_byte_slice(index - TC::SLICE_COUNT)
+        } else {
+            self.order.get_byte_slice(index - TC::SLICE_COUNT - CC::SLICE_COUNT)
+        }
+    }
+}
+
+impl<TC: crate::schema::Schema, CC: crate::schema::Schema, OC: crate::schema::Schema> crate::schema::Schema for Dictionary<TC, CC, OC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Dictionary", [("values", TC::schema()), ("codes", CC::schema()), ("order", OC::schema())])
+    }
+}
+
+impl<'a, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a> + IndexAs<u32>, OC: crate::FromBytes<'a> + IndexAs<u32>> crate::FromBytes<'a> for Dictionary<TC, CC, OC> {
+    const SLICE_COUNT: usize = TC::SLICE_COUNT + CC::SLICE_COUNT + OC::SLICE_COUNT;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            values: crate::FromBytes::from_bytes(bytes),
+            codes: crate::FromBytes::from_bytes(bytes),
+            order: crate::FromBytes::from_bytes(bytes),
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self {
+            values: TC::from_store(store, offset),
+            codes: CC::from_store(store, offset),
+            order: OC::from_store(store, offset),
+        }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        TC::element_sizes(sizes)?;
+        CC::element_sizes(sizes)?;
+        OC::element_sizes(sizes)?;
+        Ok(())
+    }
+    fn validate_deep(&self) -> Result<usize, String> {
+        let values = self.values.validate_deep()?;
+        let codes = self.codes.validate_deep()?;
+        let order = self.order.validate_deep()?;
+        if order != values {
+            return Err(format!("Dictionary: {} ordered codes for {} values", order, values));
+        }
+        if let Some(index) = (0 .. codes).find(|i| self.codes.index_as(*i) as usize >= values) {
+            return Err(format!("Dictionary: code {} at {} exceeds {} values", self.codes.index_as(index), index, values));
+        }
+        if let Some(index) = (0 .. order).find(|i| self.order.index_as(*i) as usize >= values) {
+            return Err(format!("Dictionary: ordered code {} at {} exceeds {} values", self.order.index_as(index), index, values));
+        }
+        Ok(codes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use alloc::vec::Vec;
+    use crate::common::{Push, Index, Len, Clear};
+    use crate::{Borrow, Container, Strings};
+    use crate::bytes::stash::Stash;
+    use super::Dictionary;
+
+    const COUNTRIES: [&str; 8] = ["US", "FR", "US", "DE", "FR", "US", "JP", "DE"];
+
+    fn countries() -> Dictionary<Strings> {
+        let mut dictionary: Dictionary<Strings> = Default::default();
+        for country in COUNTRIES {
+            dictionary.push(country.as_bytes());
+        }
+        dictionary
+    }
+
+    #[test]
+    fn push_and_index() {
+        let dictionary = countries();
+        assert_eq!(dictionary.len(), COUNTRIES.len());
+        assert_eq!(dictionary.values.len(), 4);
+        for (index, country) in COUNTRIES.iter().enumerate() {
+            assert_eq!((&dictionary).get(index), country.as_bytes());
+            assert_eq!(dictionary.borrow().get(index), country.as_bytes());
+        }
+        // Codes are assigned in order of first appearance.
+        assert_eq!(dictionary.codes, [0, 1, 0, 2, 1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn filter_by_code() {
+        let dictionary = countries();
+        let borrowed = dictionary.borrow();
+        let code = borrowed.code_of(&b"FR"[..]).unwrap();
+        let matches: Vec<usize> = (0 .. borrowed.len()).filter(|i| borrowed.code(*i) == code).collect();
+        assert_eq!(matches, [1, 4]);
+        assert_eq!(borrowed.value(code), b"FR");
+        assert!(borrowed.code_of(&b"GB"[..]).is_none());
+        assert_eq!(borrowed.distinct(), 4);
+    }
+
+    #[test]
+    fn extend_and_clear() {
+        let dictionary = countries();
+        let mut other: Dictionary<Strings> = Default::default();
+        other.push(&b"JP"[..]);
+        other.extend_from_self(dictionary.borrow(), 2 .. 7);
+        assert_eq!(other.len(), 6);
+        assert_eq!(other.values.len(), 4);
+        for (index, country) in ["JP", "US", "DE", "FR", "US", "JP"].iter().enumerate() {
+            assert_eq!((&other).get(index), country.as_bytes());
+        }
+        other.clear();
+        assert_eq!(other.len(), 0);
+        assert!(other.borrow().code_of(&b"JP"[..]).is_none());
+    }
+
+    #[test]
+    fn round_trip_stash() {
+        let dictionary = countries();
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &dictionary.borrow()).unwrap();
+        let stash: Stash<Dictionary<Strings>, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+        assert_eq!(stash.borrow(), dictionary.borrow());
+        assert_eq!(stash.borrow().code_of(&b"DE"[..]), Some(2));
+    }
+}
@@ -0,0 +1,648 @@
+//! Columnar containers whose shape is discovered from `serde::Serialize` values.
+//!
+//! [`Columns`] stands in for a `Vec<T>` of any `T: Serialize`, including types that cannot
+//! derive `Columnar`. It is a `serde::Serializer` that pushes each value into containers of
+//! the shape the value presents: primitives into `Vec`s, strings into `Strings`, sequences and
+//! maps into `Vecs`, options into `Options`, structs and tuples into a container for each field,
+//! and enum variants into a container for each variant, with a `Discriminant` of each value's variant.
+//!
+//! The shape is fixed by the first value that reaches each container, and later values must agree.
+//! Containers not yet reached, such as the values of sequences that have all been empty, have no
+//! shape and accept any value. The elements of the borrowed form serialize as the values pushed.
+
+use alloc::{vec::Vec, string::String, boxed::Box};
+
+use serde::ser::{self, Serialize};
+
+use crate::{Discriminant, Index, Len, Options, Strings, Vecs};
+use crate::common::Push;
+use crate::primitive::Bools;
+
+/// The containers of values of a shape discovered as they are pushed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Columns {
+    /// No values have been pushed, and the shape is not yet known.
+    #[default]
+    Empty,
+    /// Unit values, unit structs, and unit variants, by their count.
+    Unit(u64),
+    Bool(Bools),
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    I128(Vec<i128>),
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+    U128(Vec<u128>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    Char(Vec<char>),
+    String(Strings),
+    Bytes(Vecs<Vec<u8>>),
+    Option(Options<Box<Columns>>),
+    /// Sequences, with the values of all sequences in one container.
+    Seq(Vecs<Box<Columns>>),
+    /// Maps, with the keys and values of all entries in two containers.
+    Map(Vecs<Box<(Columns, Columns)>>),
+    /// Tuples and tuple structs, by the name of the struct if any, a container for each field, and the count.
+    Tuple(Option<&'static str>, Vec<Columns>, u64),
+    /// Structs, by their name, their fields in order, and the count.
+    Struct(&'static str, Vec<(&'static str, Columns)>, u64),
+    /// Enums, by their name, their variants in order, and the variant and offset of each value.
+    ///
+    /// Variants are recorded as they are first pushed, and the name of variants not yet pushed is empty.
+    Enum(&'static str, Vec<(&'static str, Form, Columns)>, Discriminant),
+}
+
+/// The form of the values of an enum variant, which the container of its values does not determine.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Form {
+    /// A variant without values, whose container is `Unit`.
+    Unit,
+    /// A variant with one unnamed value.
+    Newtype,
+    /// A variant with unnamed values, whose container is `Tuple`.
+    Tuple,
+    /// A variant with named values, whose container is `Struct`, named by the variant.
+    Struct,
+}
+
+/// An error pushing a value whose shape differs from the shape of the containers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error(pub String);
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { f.write_str(&self.0) }
+}
+
+impl ser::StdError for Error { }
+
+impl ser::Error for Error {
+    fn custom<T: core::fmt::Display>(message: T) -> Self { Error(format!("{}", message)) }
+}
+
+impl Columns {
+    /// Pushes `item` into the containers, fixing the shape of any containers it is the first to reach.
+    ///
+    /// On error the contents of partially pushed values remain, and the containers should not be used further.
+    pub fn push<T: Serialize + ?Sized>(&mut self, item: &T) -> Result<(), Error> {
+        item.serialize(self)
+    }
+
+    /// A description of the shape of the containers, for error messages.
+    fn describe(&self) -> String {
+        match self {
+            Columns::Empty => "nothing".into(),
+            Columns::Unit(_) => "unit".into(),
+            Columns::Bool(_) => "bool".into(),
+            Columns::I8(_) => "i8".into(),
+            Columns::I16(_) => "i16".into(),
+            Columns::I32(_) => "i32".into(),
+            Columns::I64(_) => "i64".into(),
+            Columns::I128(_) => "i128".into(),
+            Columns::U8(_) => "u8".into(),
+            Columns::U16(_) => "u16".into(),
+            Columns::U32(_) => "u32".into(),
+            Columns::U64(_) => "u64".into(),
+            Columns::U128(_) => "u128".into(),
+            Columns::F32(_) => "f32".into(),
+            Columns::F64(_) => "f64".into(),
+            Columns::Char(_) => "char".into(),
+            Columns::String(_) => "string".into(),
+            Columns::Bytes(_) => "bytes".into(),
+            Columns::Option(_) => "option".into(),
+            Columns::Seq(_) => "sequence".into(),
+            Columns::Map(_) => "map".into(),
+            Columns::Tuple(None, fields, _) => format!("tuple of {} fields", fields.len()),
+            Columns::Tuple(Some(name), _, _) => format!("tuple struct `{}`", name),
+            Columns::Struct(name, _, _) => format!("struct `{}`", name),
+            Columns::Enum(name, _, _) => format!("enum `{}`", name),
+        }
+    }
+
+    /// An error for pushing `found` into containers of a different shape.
+    fn mismatch(&self, found: &str) -> Error {
+        Error(format!("expected {} but found {}", self.describe(), found))
+    }
+
+    /// The container for the values of `variant` of the enum `name`, with the discriminant and the index of the variant.
+    fn variant(&mut self, name: &'static str, index: u32, variant: &'static str, form: Form) -> Result<(&mut Columns, &mut Discriminant, u8), Error> {
+        if let Columns::Empty = self { *self = Columns::Enum(name, Vec::new(), Discriminant::default()); }
+        if !matches!(self, Columns::Enum(enum_name, _, _) if *enum_name == name) {
+            return Err(self.mismatch(&format!("enum `{}`", name)));
+        }
+        let Columns::Enum(_, variants, indexes) = self else { unreachable!() };
+        let index = u8::try_from(index).map_err(|_| Error(format!("enum `{}` has more than 256 variants", name)))?;
+        while variants.len() <= index as usize { variants.push(("", Form::Unit, Columns::Empty)); }
+        let (variant_name, variant_form, values) = &mut variants[index as usize];
+        if variant_name.is_empty() { *variant_name = variant; *variant_form = form; }
+        if *variant_name != variant || *variant_form != form {
+            return Err(Error(format!("variant {} of enum `{}` is `{}` ({:?}) but found `{}` ({:?})", index, name, variant_name, variant_form, variant, form)));
+        }
+        Ok((values, indexes, index))
+    }
+}
+
+impl Len for Columns {
+    fn len(&self) -> usize {
+        match self {
+            Columns::Empty => 0,
+            Columns::Unit(count) => *count as usize,
+            Columns::Bool(values) => values.len(),
+            Columns::I8(values) => values.len(),
+            Columns::I16(values) => values.len(),
+            Columns::I32(values) => values.len(),
+            Columns::I64(values) => values.len(),
+            Columns::I128(values) => values.len(),
+            Columns::U8(values) => values.len(),
+            Columns::U16(values) => values.len(),
+            Columns::U32(values) => values.len(),
+            Columns::U64(values) => values.len(),
+            Columns::U128(values) => values.len(),
+            Columns::F32(values) => values.len(),
+            Columns::F64(values) => values.len(),
+            Columns::Char(values) => values.len(),
+            Columns::String(values) => values.len(),
+            Columns::Bytes(values) => values.len(),
+            Columns::Option(values) => values.len(),
+            Columns::Seq(values) => values.len(),
+            Columns::Map(values) => values.len(),
+            Columns::Tuple(_, _, count) => *count as usize,
+            Columns::Struct(_, _, count) => *count as usize,
+            Columns::Enum(_, _, indexes) => indexes.len(),
+        }
+    }
+}
+
+/// The bounds of element `index` of containers with `bounds`.
+fn bounds(bounds: &[u64], index: usize) -> (usize, usize) {
+    let lower = if index == 0 { 0 } else { bounds[index - 1] };
+    (lower as usize, bounds[index] as usize)
+}
+
+macro_rules! serialize_primitive {
+    ($($method:ident, $type:ty, $variant:ident;)*) => { $(
+        fn $method(self, value: $type) -> Result<(), Error> {
+            if let Columns::Empty = self { *self = Columns::$variant(Default::default()); }
+            match self {
+                Columns::$variant(values) => { values.push(value); Ok(()) },
+                other => Err(other.mismatch(stringify!($type))),
+            }
+        }
+    )* }
+}
+
+impl<'a> ser::Serializer for &'a mut Columns {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqBuilder<'a>;
+    type SerializeTuple = TupleBuilder<'a>;
+    type SerializeTupleStruct = TupleBuilder<'a>;
+    type SerializeTupleVariant = TupleBuilder<'a>;
+    type SerializeMap = MapBuilder<'a>;
+    type SerializeStruct = StructBuilder<'a>;
+    type SerializeStructVariant = StructBuilder<'a>;
+
+    serialize_primitive!(
+        serialize_bool, bool, Bool;
+        serialize_i8, i8, I8;
+        serialize_i16, i16, I16;
+        serialize_i32, i32, I32;
+        serialize_i64, i64, I64;
+        serialize_i128, i128, I128;
+        serialize_u8, u8, U8;
+        serialize_u16, u16, U16;
+        serialize_u32, u32, U32;
+        serialize_u64, u64, U64;
+        serialize_u128, u128, U128;
+        serialize_f32, f32, F32;
+        serialize_f64, f64, F64;
+        serialize_char, char, Char;
+        serialize_str, &str, String;
+    );
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        if let Columns::Empty = self { *self = Columns::Bytes(Default::default()); }
+        match self {
+            Columns::Bytes(values) => { values.push(value); Ok(()) },
+            other => Err(other.mismatch("bytes")),
+        }
+    }
+    fn serialize_none(self) -> Result<(), Error> {
+        if let Columns::Empty = self { *self = Columns::Option(Default::default()); }
+        match self {
+            Columns::Option(options) => { options.indexes.push(false); Ok(()) },
+            other => Err(other.mismatch("option")),
+        }
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        if let Columns::Empty = self { *self = Columns::Option(Default::default()); }
+        match self {
+            Columns::Option(options) => {
+                value.serialize(&mut *options.somes)?;
+                options.indexes.push(true);
+                Ok(())
+            },
+            other => Err(other.mismatch("option")),
+        }
+    }
+    fn serialize_unit(self) -> Result<(), Error> {
+        if let Columns::Empty = self { *self = Columns::Unit(0); }
+        match self {
+            Columns::Unit(count) => { *count += 1; Ok(()) },
+            other => Err(other.mismatch("unit")),
+        }
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+    fn serialize_unit_variant(self, name: &'static str, index: u32, variant: &'static str) -> Result<(), Error> {
+        let (values, indexes, index) = self.variant(name, index, variant, Form::Unit)?;
+        let offset = values.len() as u64;
+        values.serialize_unit()?;
+        indexes.push(index, offset);
+        Ok(())
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, name: &'static str, index: u32, variant: &'static str, value: &T) -> Result<(), Error> {
+        let (values, indexes, index) = self.variant(name, index, variant, Form::Newtype)?;
+        let offset = values.len() as u64;
+        value.serialize(values)?;
+        indexes.push(index, offset);
+        Ok(())
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqBuilder<'a>, Error> {
+        if let Columns::Empty = self { *self = Columns::Seq(Default::default()); }
+        match self {
+            Columns::Seq(vecs) => Ok(SeqBuilder { bounds: &mut vecs.bounds, values: &mut vecs.values }),
+            other => Err(other.mismatch("sequence")),
+        }
+    }
+    fn serialize_tuple(self, len: usize) -> Result<TupleBuilder<'a>, Error> {
+        TupleBuilder::new(self, None, len, None)
+    }
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<TupleBuilder<'a>, Error> {
+        TupleBuilder::new(self, Some(name), len, None)
+    }
+    fn serialize_tuple_variant(self, name: &'static str, index: u32, variant: &'static str, len: usize) -> Result<TupleBuilder<'a>, Error> {
+        let (values, indexes, index) = self.variant(name, index, variant, Form::Tuple)?;
+        TupleBuilder::new(values, None, len, Some((indexes, index)))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapBuilder<'a>, Error> {
+        if let Columns::Empty = self { *self = Columns::Map(Default::default()); }
+        match self {
+            Columns::Map(vecs) => Ok(MapBuilder { bounds: &mut vecs.bounds, entries: &mut vecs.values }),
+            other => Err(other.mismatch("map")),
+        }
+    }
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<StructBuilder<'a>, Error> {
+        StructBuilder::new(self, name, None)
+    }
+    fn serialize_struct_variant(self, name: &'static str, index: u32, variant: &'static str, _len: usize) -> Result<StructBuilder<'a>, Error> {
+        let (values, indexes, index) = self.variant(name, index, variant, Form::Struct)?;
+        StructBuilder::new(values, variant, Some((indexes, index)))
+    }
+}
+
+/// Pushes the elements of a sequence, and then its bounds.
+pub struct SeqBuilder<'a> {
+    bounds: &'a mut Vec<u64>,
+    values: &'a mut Columns,
+}
+
+impl<'a> ser::SerializeSeq for SeqBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.values)
+    }
+    fn end(self) -> Result<(), Error> {
+        self.bounds.push(self.values.len() as u64);
+        Ok(())
+    }
+}
+
+/// Pushes the keys and values of a map, and then its bounds.
+pub struct MapBuilder<'a> {
+    bounds: &'a mut Vec<u64>,
+    entries: &'a mut (Columns, Columns),
+}
+
+impl<'a> ser::SerializeMap for MapBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut self.entries.0)
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut self.entries.1)
+    }
+    fn end(self) -> Result<(), Error> {
+        if self.entries.0.len() != self.entries.1.len() {
+            return Err(Error(format!("map has {} keys but {} values", self.entries.0.len(), self.entries.1.len())));
+        }
+        self.bounds.push(self.entries.0.len() as u64);
+        Ok(())
+    }
+}
+
+/// Pushes the fields of a tuple, and then its count and the discriminant of its variant, if any.
+pub struct TupleBuilder<'a> {
+    fields: &'a mut Vec<Columns>,
+    count: &'a mut u64,
+    position: usize,
+    variant: Option<(&'a mut Discriminant, u8)>,
+}
+
+impl<'a> TupleBuilder<'a> {
+    fn new(columns: &'a mut Columns, name: Option<&'static str>, len: usize, variant: Option<(&'a mut Discriminant, u8)>) -> Result<Self, Error> {
+        if let Columns::Empty = columns { *columns = Columns::Tuple(name, (0 .. len).map(|_| Columns::Empty).collect(), 0); }
+        if !matches!(columns, Columns::Tuple(tuple_name, fields, _) if *tuple_name == name && fields.len() == len) {
+            let found = match name { Some(name) => format!("tuple struct `{}`", name), None => format!("tuple of {} fields", len) };
+            return Err(columns.mismatch(&found));
+        }
+        let Columns::Tuple(_, fields, count) = columns else { unreachable!() };
+        Ok(TupleBuilder { fields, count, position: 0, variant })
+    }
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let field = self.fields.get_mut(self.position).ok_or_else(|| Error(format!("tuple has more than {} fields", self.position)))?;
+        value.serialize(field)?;
+        self.position += 1;
+        Ok(())
+    }
+    fn end(self) -> Result<(), Error> {
+        if self.position != self.fields.len() {
+            return Err(Error(format!("tuple has {} fields rather than {}", self.position, self.fields.len())));
+        }
+        if let Some((indexes, index)) = self.variant { indexes.push(index, *self.count); }
+        *self.count += 1;
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for TupleBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> { self.element(value) }
+    fn end(self) -> Result<(), Error> { TupleBuilder::end(self) }
+}
+
+impl<'a> ser::SerializeTupleStruct for TupleBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> { self.element(value) }
+    fn end(self) -> Result<(), Error> { TupleBuilder::end(self) }
+}
+
+impl<'a> ser::SerializeTupleVariant for TupleBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> { self.element(value) }
+    fn end(self) -> Result<(), Error> { TupleBuilder::end(self) }
+}
+
+/// Pushes the fields of a struct, and then its count and the discriminant of its variant, if any.
+///
+/// The fields are recorded as the first struct is pushed, and later structs must present the same fields in order.
+pub struct StructBuilder<'a> {
+    fields: &'a mut Vec<(&'static str, Columns)>,
+    count: &'a mut u64,
+    position: usize,
+    variant: Option<(&'a mut Discriminant, u8)>,
+}
+
+impl<'a> StructBuilder<'a> {
+    fn new(columns: &'a mut Columns, name: &'static str, variant: Option<(&'a mut Discriminant, u8)>) -> Result<Self, Error> {
+        if let Columns::Empty = columns { *columns = Columns::Struct(name, Vec::new(), 0); }
+        if !matches!(columns, Columns::Struct(struct_name, _, _) if *struct_name == name) {
+            return Err(columns.mismatch(&format!("struct `{}`", name)));
+        }
+        let Columns::Struct(_, fields, count) = columns else { unreachable!() };
+        Ok(StructBuilder { fields, count, position: 0, variant })
+    }
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        if self.position == self.fields.len() {
+            if *self.count > 0 { return Err(Error(format!("unexpected field `{}`", key))); }
+            self.fields.push((key, Columns::Empty));
+        }
+        let (name, field) = &mut self.fields[self.position];
+        if *name != key {
+            return Err(Error(format!("expected field `{}` but found `{}`", name, key)));
+        }
+        value.serialize(field)?;
+        self.position += 1;
+        Ok(())
+    }
+    fn end(self) -> Result<(), Error> {
+        if let Some((name, _)) = self.fields.get(self.position) {
+            return Err(Error(format!("missing field `{}`", name)));
+        }
+        if let Some((indexes, index)) = self.variant { indexes.push(index, *self.count); }
+        *self.count += 1;
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for StructBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> { self.field(key, value) }
+    fn end(self) -> Result<(), Error> { StructBuilder::end(self) }
+}
+
+impl<'a> ser::SerializeStructVariant for StructBuilder<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> { self.field(key, value) }
+    fn end(self) -> Result<(), Error> { StructBuilder::end(self) }
+}
+
+/// A reference to a value in [`Columns`], which serializes as the value pushed.
+#[derive(Copy, Clone, Debug)]
+pub struct ColumnsRef<'a> {
+    columns: &'a Columns,
+    index: usize,
+}
+
+impl<'a> Index for &'a Columns {
+    type Ref = ColumnsRef<'a>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        ColumnsRef { columns: self, index }
+    }
+}
+
+impl<'a> ColumnsRef<'a> {
+    fn at(columns: &'a Columns, index: usize) -> Self { ColumnsRef { columns, index } }
+}
+
+impl<'a> Serialize for ColumnsRef<'a> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+        let index = self.index;
+        match self.columns {
+            Columns::Empty => Err(S::Error::custom("no values have been pushed")),
+            Columns::Unit(_) => serializer.serialize_unit(),
+            Columns::Bool(values) => serializer.serialize_bool(values.get(index)),
+            Columns::I8(values) => serializer.serialize_i8(values[index]),
+            Columns::I16(values) => serializer.serialize_i16(values[index]),
+            Columns::I32(values) => serializer.serialize_i32(values[index]),
+            Columns::I64(values) => serializer.serialize_i64(values[index]),
+            Columns::I128(values) => serializer.serialize_i128(values[index]),
+            Columns::U8(values) => serializer.serialize_u8(values[index]),
+            Columns::U16(values) => serializer.serialize_u16(values[index]),
+            Columns::U32(values) => serializer.serialize_u32(values[index]),
+            Columns::U64(values) => serializer.serialize_u64(values[index]),
+            Columns::U128(values) => serializer.serialize_u128(values[index]),
+            Columns::F32(values) => serializer.serialize_f32(values[index]),
+            Columns::F64(values) => serializer.serialize_f64(values[index]),
+            Columns::Char(values) => serializer.serialize_char(values[index]),
+            Columns::String(values) => serializer.serialize_str(core::str::from_utf8(values.get(index)).map_err(S::Error::custom)?),
+            Columns::Bytes(values) => {
+                let (lower, upper) = bounds(&values.bounds, index);
+                serializer.serialize_bytes(&values.values[lower .. upper])
+            },
+            Columns::Option(values) => {
+                if values.indexes.get(index) { serializer.serialize_some(&ColumnsRef::at(&values.somes, values.indexes.rank(index))) }
+                else { serializer.serialize_none() }
+            },
+            Columns::Seq(values) => {
+                let (lower, upper) = bounds(&values.bounds, index);
+                let mut seq = serializer.serialize_seq(Some(upper - lower))?;
+                for position in lower .. upper { seq.serialize_element(&ColumnsRef::at(&values.values, position))?; }
+                seq.end()
+            },
+            Columns::Map(values) => {
+                let (lower, upper) = bounds(&values.bounds, index);
+                let mut map = serializer.serialize_map(Some(upper - lower))?;
+                for position in lower .. upper {
+                    map.serialize_entry(&ColumnsRef::at(&values.values.0, position), &ColumnsRef::at(&values.values.1, position))?;
+                }
+                map.end()
+            },
+            Columns::Tuple(None, fields, _) => {
+                let mut tuple = serializer.serialize_tuple(fields.len())?;
+                for field in fields.iter() { tuple.serialize_element(&ColumnsRef::at(field, index))?; }
+                tuple.end()
+            },
+            Columns::Tuple(Some(name), fields, _) => {
+                let mut tuple = serializer.serialize_tuple_struct(name, fields.len())?;
+                for field in fields.iter() { tuple.serialize_field(&ColumnsRef::at(field, index))?; }
+                tuple.end()
+            },
+            Columns::Struct(name, fields, _) => {
+                let mut structure = serializer.serialize_struct(name, fields.len())?;
+                for (key, field) in fields.iter() { structure.serialize_field(key, &ColumnsRef::at(field, index))?; }
+                structure.end()
+            },
+            Columns::Enum(name, variants, indexes) => {
+                let (variant, offset) = indexes.get(index);
+                let (variant_name, form, values) = &variants[variant as usize];
+                let offset = offset as usize;
+                match (form, values) {
+                    (Form::Unit, _) => serializer.serialize_unit_variant(name, variant as u32, variant_name),
+                    (Form::Newtype, values) => serializer.serialize_newtype_variant(name, variant as u32, variant_name, &ColumnsRef::at(values, offset)),
+                    (Form::Tuple, Columns::Tuple(_, fields, _)) => {
+                        let mut tuple = serializer.serialize_tuple_variant(name, variant as u32, variant_name, fields.len())?;
+                        for field in fields.iter() { tuple.serialize_field(&ColumnsRef::at(field, offset))?; }
+                        tuple.end()
+                    },
+                    (Form::Struct, Columns::Struct(_, fields, _)) => {
+                        let mut structure = serializer.serialize_struct_variant(name, variant as u32, variant_name, fields.len())?;
+                        for (key, field) in fields.iter() { structure.serialize_field(key, &ColumnsRef::at(field, offset))?; }
+                        structure.end()
+                    },
+                    _ => Err(S::Error::custom(format!("variant `{}` has values of the wrong shape", variant_name))),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec::Vec, string::String, collections::BTreeMap};
+    use serde::Serialize;
+    use crate::{Index, Len};
+    use super::{Columns, Form};
+
+    #[derive(Serialize)]
+    enum Shape {
+        Circle(f64),
+        Rect { width: u32, height: u32 },
+        Point,
+        Segment(i8, i8),
+    }
+
+    #[derive(Serialize)]
+    struct Unannotated {
+        id: u64,
+        name: String,
+        tags: Vec<String>,
+        score: Option<f32>,
+        shape: Shape,
+        pair: (u8, char),
+        counts: BTreeMap<String, i32>,
+        nothing: (),
+    }
+
+    fn items() -> Vec<Unannotated> {
+        (0 .. 20u64).map(|i| Unannotated {
+            id: i,
+            name: format!("item {}", i),
+            tags: (0 .. i % 3).map(|j| format!("tag {}", j)).collect(),
+            score: if i % 4 == 0 { None } else { Some(i as f32 / 2.0) },
+            shape: match i % 4 {
+                0 => Shape::Point,
+                1 => Shape::Circle(i as f64),
+                2 => Shape::Rect { width: i as u32, height: 2 },
+                _ => Shape::Segment(-(i as i8), i as i8),
+            },
+            pair: (i as u8, char::from(b'a' + i as u8)),
+            counts: (0 .. i % 2).map(|j| (format!("{}", j), -(i as i32))).collect(),
+            nothing: (),
+        }).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut columns = Columns::default();
+        for item in items().iter() { columns.push(item).unwrap(); }
+        assert_eq!(columns.len(), 20);
+        for (index, item) in items().iter().enumerate() {
+            assert_eq!(serde_json::to_value((&columns).get(index)).unwrap(), serde_json::to_value(item).unwrap());
+        }
+
+        // Fields are containers of their values, and enum variants are containers of theirs.
+        let Columns::Struct("Unannotated", fields, 20) = &columns else { panic!("not a struct: {:?}", columns) };
+        assert_eq!(fields.iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["id", "name", "tags", "score", "shape", "pair", "counts", "nothing"]);
+        assert!(matches!(&fields[0].1, Columns::U64(ids) if ids.len() == 20));
+        assert!(matches!(&fields[2].1, Columns::Seq(tags) if tags.values.len() == 19));
+        let Columns::Enum("Shape", variants, indexes) = &fields[4].1 else { panic!("not an enum") };
+        assert_eq!(variants.iter().map(|(name, form, _)| (*name, *form)).collect::<Vec<_>>(), [
+            ("Circle", Form::Newtype), ("Rect", Form::Struct), ("Point", Form::Unit), ("Segment", Form::Tuple),
+        ]);
+        assert_eq!(indexes.len(), 20);
+        assert!(matches!(&variants[1].2, Columns::Struct("Rect", _, 5)));
+    }
+
+    #[test]
+    fn mismatches() {
+        let mut columns = Columns::default();
+        columns.push(&(1u64, Some("one"))).unwrap();
+        columns.push(&(2u64, None::<&str>)).unwrap();
+        assert!(columns.push(&(3u64, Some(3u64))).unwrap_err().0.contains("expected string but found u64"));
+        assert!(Columns::default().push(&[1u8, 2]).is_ok());
+
+        // Values not yet reached accept any shape.
+        let mut columns = Columns::default();
+        columns.push(&Vec::<u32>::new()).unwrap();
+        columns.push(&vec![Some(1.5f64)]).unwrap();
+        assert!(columns.push(&vec![1.5f64]).is_err());
+    }
+}
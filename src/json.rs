@@ -0,0 +1,673 @@
+//! A columnar container for JSON values.
+//!
+//! [`Jsons`] is a stand-in for `Vec<serde_json::Value>`. Each value is decomposed
+//! into numbers, strings, arrays, and objects, each stored in its own container,
+//! and values refer to their contents with encoded [`JsonIdx`] values. The borrowed
+//! form can be navigated through [`JsonRef`] without materializing any values.
+//!
+//! Values are added from any `serde::Deserializer`, which includes `serde_json::Value`.
+use alloc::{vec::Vec, string::String};
+
+use crate::{Borrow, Clear, Container, Index, Len, Lookbacks, Push, Strings, Vecs};
+
+/// Sum type indicating where to find the data for each variant, encoded in a `u64` by [`JsonIdx::encode`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum JsonIdx {
+    Null,
+    Bool(bool),
+    /// A non-negative integer, by its index in `unsigned`.
+    Unsigned(usize),
+    /// A negative integer, by its index in `signed`.
+    Signed(usize),
+    /// A floating point number, by its index in `floats`.
+    Float(usize),
+    String(usize),
+    Array(usize),
+    Object(usize),
+}
+
+impl JsonIdx {
+    /// Encodes the index as a `u64`, with the variant in the low three bits.
+    #[inline(always)]
+    pub fn encode(self) -> u64 {
+        match self {
+            JsonIdx::Null => 0,
+            JsonIdx::Bool(b) => ((b as u64) << 3) | 1,
+            JsonIdx::Unsigned(i) => ((i as u64) << 3) | 2,
+            JsonIdx::Signed(i) => ((i as u64) << 3) | 3,
+            JsonIdx::Float(i) => ((i as u64) << 3) | 4,
+            JsonIdx::String(i) => ((i as u64) << 3) | 5,
+            JsonIdx::Array(i) => ((i as u64) << 3) | 6,
+            JsonIdx::Object(i) => ((i as u64) << 3) | 7,
+        }
+    }
+    /// Decodes an index produced by `encode`.
+    #[inline(always)]
+    pub fn decode(encoded: u64) -> Self {
+        let index = (encoded >> 3) as usize;
+        match encoded & 7 {
+            0 => JsonIdx::Null,
+            1 => JsonIdx::Bool(index != 0),
+            2 => JsonIdx::Unsigned(index),
+            3 => JsonIdx::Signed(index),
+            4 => JsonIdx::Float(index),
+            5 => JsonIdx::String(index),
+            6 => JsonIdx::Array(index),
+            _ => JsonIdx::Object(index),
+        }
+    }
+}
+
+/// Stand-in for `Vec<serde_json::Value>`.
+///
+/// The `roots` container indicates the root of each stored value.
+/// The (transitive) contents of each value are stored throughout,
+/// at locations that may not necessarily be found in `roots`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Jsons<
+    IC = Vec<u64>,
+    UC = Vec<u64>,
+    NC = Vec<i64>,
+    FC = Vec<f64>,
+    SC = Lookbacks<Strings>,
+    AC = Vecs<Vec<u64>>,
+    OC = Vecs<(Lookbacks<Strings>, Vec<u64>)>,
+> {
+    /// The encoded `JsonIdx` of each value.
+    pub roots: IC,
+    // No need to store nulls or bools, which are encoded in their `JsonIdx`.
+    /// Non-negative integers.
+    pub unsigned: UC,
+    /// Negative integers.
+    pub signed: NC,
+    /// Floating point numbers.
+    pub floats: FC,
+    /// Strings, with nearby repeats stored only once.
+    pub strings: SC,
+    /// Arrays, as lists of encoded `JsonIdx` values.
+    pub arrays: AC,
+    /// Objects, as lists of keys and encoded `JsonIdx` values, in their original order.
+    pub objects: OC,
+}
+
+/// The borrowed form of `Jsons`.
+type JsonsBorrowed<'a> = Jsons<
+    &'a [u64],
+    &'a [u64],
+    &'a [i64],
+    &'a [f64],
+    <Lookbacks<Strings> as Borrow>::Borrowed<'a>,
+    Vecs<&'a [u64], &'a [u64]>,
+    Vecs<(<Lookbacks<Strings> as Borrow>::Borrowed<'a>, &'a [u64]), &'a [u64]>,
+>;
+
+impl Jsons {
+    /// Pushes the value produced by `deserializer`, for example a `serde_json::Value`.
+    ///
+    /// On error the contents of partially pushed values remain, but no root is pushed.
+    pub fn push_value<'de, D: serde::Deserializer<'de>>(&mut self, deserializer: D) -> Result<(), D::Error> {
+        use serde::de::DeserializeSeed;
+        let root = JsonSeed { store: self }.deserialize(deserializer)?;
+        self.roots.push(root);
+        Ok(())
+    }
+    /// Copies a value from a `JsonRef`, returning its encoded index.
+    fn copy(&mut self, json: JsonRef<'_>) -> u64 {
+        let idx = match json.idx {
+            JsonIdx::Null | JsonIdx::Bool(_) => json.idx,
+            JsonIdx::Unsigned(i) => {
+                self.unsigned.push(json.store.unsigned[i]);
+                JsonIdx::Unsigned(self.unsigned.len() - 1)
+            },
+            JsonIdx::Signed(i) => {
+                self.signed.push(json.store.signed[i]);
+                JsonIdx::Signed(self.signed.len() - 1)
+            },
+            JsonIdx::Float(i) => {
+                self.floats.push(json.store.floats[i]);
+                JsonIdx::Float(self.floats.len() - 1)
+            },
+            JsonIdx::String(i) => {
+                self.strings.push(json.store.strings.get(i));
+                JsonIdx::String(self.strings.len() - 1)
+            },
+            JsonIdx::Array(i) => {
+                let array = JsonArray { slice: json.store.arrays.get(i), store: json.store };
+                let items: Vec<u64> = array.into_index_iter().map(|item| self.copy(item)).collect();
+                self.arrays.push_iter(items);
+                JsonIdx::Array(self.arrays.len() - 1)
+            },
+            JsonIdx::Object(i) => {
+                let object = JsonObject { slice: json.store.objects.get(i), store: json.store };
+                let items: Vec<(&[u8], u64)> = object.into_index_iter().map(|(key, item)| (key, self.copy(item))).collect();
+                self.objects.push_iter(items.iter().map(|(key, item)| (*key, item)));
+                JsonIdx::Object(self.objects.len() - 1)
+            },
+        };
+        idx.encode()
+    }
+}
+
+impl Borrow for Jsons {
+    type Ref<'a> = JsonRef<'a>;
+    type Borrowed<'a> = JsonsBorrowed<'a>;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        Jsons {
+            roots: &self.roots[..],
+            unsigned: &self.unsigned[..],
+            signed: &self.signed[..],
+            floats: &self.floats[..],
+            strings: self.strings.borrow(),
+            arrays: self.arrays.borrow(),
+            objects: self.objects.borrow(),
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a {
+        Jsons {
+            roots: thing.roots,
+            unsigned: thing.unsigned,
+            signed: thing.signed,
+            floats: thing.floats,
+            strings: Lookbacks::<Strings>::reborrow(thing.strings),
+            arrays: Vecs::<Vec<u64>>::reborrow(thing.arrays),
+            objects: Vecs::<(Lookbacks<Strings>, Vec<u64>)>::reborrow(thing.objects),
+        }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a {
+        JsonRef { idx: thing.idx, store: Self::reborrow(thing.store) }
+    }
+}
+
+impl Container for Jsons {
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.roots.reserve(selves.clone().map(|x| x.roots.len()).sum());
+        self.unsigned.reserve(selves.clone().map(|x| x.unsigned.len()).sum());
+        self.signed.reserve(selves.clone().map(|x| x.signed.len()).sum());
+        self.floats.reserve(selves.clone().map(|x| x.floats.len()).sum());
+        self.strings.reserve_for(selves.clone().map(|x| x.strings));
+        self.arrays.reserve_for(selves.clone().map(|x| x.arrays));
+        self.objects.reserve_for(selves.map(|x| x.objects));
+    }
+}
+
+impl<'a> Push<JsonRef<'a>> for Jsons {
+    fn push(&mut self, json: JsonRef<'a>) {
+        let root = self.copy(json);
+        self.roots.push(root);
+    }
+}
+
+impl Clear for Jsons {
+    fn clear(&mut self) {
+        self.roots.clear();
+        self.unsigned.clear();
+        self.signed.clear();
+        self.floats.clear();
+        self.strings.clear();
+        self.arrays.clear();
+        self.objects.clear();
+    }
+}
+
+impl<IC: Len, UC, NC, FC, SC, AC, OC> Len for Jsons<IC, UC, NC, FC, SC, AC, OC> {
+    #[inline(always)] fn len(&self) -> usize { self.roots.len() }
+}
+
+impl<'a> Index for JsonsBorrowed<'a> {
+    type Ref = JsonRef<'a>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        JsonRef { idx: JsonIdx::decode(self.roots[index]), store: *self }
+    }
+}
+impl<'a> Index for &'a Jsons {
+    type Ref = JsonRef<'a>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref { self.borrow().get(index) }
+}
+
+/// Stand-in for `&'a serde_json::Value`.
+#[derive(Copy, Clone, Debug)]
+pub struct JsonRef<'a> {
+    idx: JsonIdx,
+    store: JsonsBorrowed<'a>,
+}
+
+/// The contents of a [`JsonRef`], one level deep.
+#[derive(Copy, Clone, Debug)]
+pub enum JsonValue<'a> {
+    Null,
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    String(&'a [u8]),
+    Array(JsonArray<'a>),
+    Object(JsonObject<'a>),
+}
+
+impl<'a> JsonRef<'a> {
+    /// Where to find the data for the value.
+    #[inline(always)] pub fn idx(&self) -> JsonIdx { self.idx }
+    /// The value, one level deep.
+    #[inline]
+    pub fn value(&self) -> JsonValue<'a> {
+        match self.idx {
+            JsonIdx::Null => JsonValue::Null,
+            JsonIdx::Bool(b) => JsonValue::Bool(b),
+            JsonIdx::Unsigned(i) => JsonValue::Unsigned(self.store.unsigned[i]),
+            JsonIdx::Signed(i) => JsonValue::Signed(self.store.signed[i]),
+            JsonIdx::Float(i) => JsonValue::Float(self.store.floats[i]),
+            JsonIdx::String(i) => JsonValue::String(self.store.strings.get(i)),
+            JsonIdx::Array(i) => JsonValue::Array(JsonArray { slice: self.store.arrays.get(i), store: self.store }),
+            JsonIdx::Object(i) => JsonValue::Object(JsonObject { slice: self.store.objects.get(i), store: self.store }),
+        }
+    }
+    #[inline] pub fn is_null(&self) -> bool { self.idx == JsonIdx::Null }
+    #[inline] pub fn as_bool(&self) -> Option<bool> { if let JsonValue::Bool(b) = self.value() { Some(b) } else { None } }
+    /// The value as a `u64`, if it is a non-negative integer.
+    #[inline] pub fn as_u64(&self) -> Option<u64> { if let JsonValue::Unsigned(n) = self.value() { Some(n) } else { None } }
+    /// The value as an `i64`, if it is an integer in range.
+    #[inline] pub fn as_i64(&self) -> Option<i64> {
+        match self.value() {
+            JsonValue::Unsigned(n) => n.try_into().ok(),
+            JsonValue::Signed(n) => Some(n),
+            _ => None,
+        }
+    }
+    /// The value as an `f64`, if it is a number.
+    #[inline] pub fn as_f64(&self) -> Option<f64> {
+        match self.value() {
+            JsonValue::Unsigned(n) => Some(n as f64),
+            JsonValue::Signed(n) => Some(n as f64),
+            JsonValue::Float(n) => Some(n),
+            _ => None,
+        }
+    }
+    /// The value as a `&str`, if it is a string of valid UTF-8.
+    #[inline] pub fn as_str(&self) -> Option<&'a str> { if let JsonValue::String(s) = self.value() { core::str::from_utf8(s).ok() } else { None } }
+    #[inline] pub fn as_array(&self) -> Option<JsonArray<'a>> { if let JsonValue::Array(a) = self.value() { Some(a) } else { None } }
+    #[inline] pub fn as_object(&self) -> Option<JsonObject<'a>> { if let JsonValue::Object(o) = self.value() { Some(o) } else { None } }
+    /// The value for `key`, if the value is an object containing that key.
+    #[inline] pub fn key(&self, key: &str) -> Option<JsonRef<'a>> { self.as_object()?.find(key) }
+    /// The `index`-th item, if the value is an array with at least that many items.
+    #[inline] pub fn item(&self, index: usize) -> Option<JsonRef<'a>> {
+        let array = self.as_array()?;
+        (index < array.len()).then(|| array.get(index))
+    }
+}
+
+/// Stand-in for `&'a [serde_json::Value]`.
+#[derive(Copy, Clone, Debug)]
+pub struct JsonArray<'a> {
+    slice: crate::Slice<&'a [u64]>,
+    store: JsonsBorrowed<'a>,
+}
+
+impl<'a> Len for JsonArray<'a> {
+    #[inline(always)] fn len(&self) -> usize { self.slice.len() }
+}
+impl<'a> Index for JsonArray<'a> {
+    type Ref = JsonRef<'a>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        JsonRef { idx: JsonIdx::decode(*self.slice.get(index)), store: self.store }
+    }
+}
+
+/// Stand-in for `&'a serde_json::Map<String, serde_json::Value>`.
+#[derive(Copy, Clone, Debug)]
+pub struct JsonObject<'a> {
+    slice: crate::Slice<(<Lookbacks<Strings> as Borrow>::Borrowed<'a>, &'a [u64])>,
+    store: JsonsBorrowed<'a>,
+}
+
+impl<'a> JsonObject<'a> {
+    /// The value of the first entry with key `key`, should one exist.
+    pub fn find(&self, key: &str) -> Option<JsonRef<'a>> {
+        self.into_index_iter().find(|(k, _)| *k == key.as_bytes()).map(|(_, v)| v)
+    }
+}
+impl<'a> Len for JsonObject<'a> {
+    #[inline(always)] fn len(&self) -> usize { self.slice.len() }
+}
+impl<'a> Index for JsonObject<'a> {
+    type Ref = (&'a [u8], JsonRef<'a>);
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        let (key, value) = self.slice.get(index);
+        (key, JsonRef { idx: JsonIdx::decode(*value), store: self.store })
+    }
+}
+
+impl<'a> serde::Serialize for JsonRef<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeSeq, SerializeMap};
+        match self.value() {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(b) => serializer.serialize_bool(b),
+            JsonValue::Unsigned(n) => serializer.serialize_u64(n),
+            JsonValue::Signed(n) => serializer.serialize_i64(n),
+            JsonValue::Float(n) => serializer.serialize_f64(n),
+            JsonValue::String(s) => serializer.serialize_str(core::str::from_utf8(s).map_err(S::Error::custom)?),
+            JsonValue::Array(array) => {
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                for item in array.into_index_iter() { seq.serialize_element(&item)?; }
+                seq.end()
+            },
+            JsonValue::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (key, value) in object.into_index_iter() {
+                    map.serialize_entry(core::str::from_utf8(key).map_err(S::Error::custom)?, &value)?;
+                }
+                map.end()
+            },
+        }
+    }
+}
+
+/// Deserializes a value into a `Jsons`, producing its encoded `JsonIdx`.
+struct JsonSeed<'s> {
+    store: &'s mut Jsons,
+}
+
+impl<'de, 's> serde::de::DeserializeSeed<'de> for JsonSeed<'s> {
+    type Value = u64;
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 's> serde::de::Visitor<'de> for JsonSeed<'s> {
+    type Value = u64;
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+    fn visit_unit<E>(self) -> Result<u64, E> { Ok(JsonIdx::Null.encode()) }
+    fn visit_none<E>(self) -> Result<u64, E> { Ok(JsonIdx::Null.encode()) }
+    fn visit_some<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<u64, D::Error> {
+        serde::de::DeserializeSeed::deserialize(self, deserializer)
+    }
+    fn visit_newtype_struct<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<u64, D::Error> {
+        serde::de::DeserializeSeed::deserialize(self, deserializer)
+    }
+    fn visit_bool<E>(self, b: bool) -> Result<u64, E> { Ok(JsonIdx::Bool(b).encode()) }
+    fn visit_u64<E>(self, n: u64) -> Result<u64, E> {
+        self.store.unsigned.push(n);
+        Ok(JsonIdx::Unsigned(self.store.unsigned.len() - 1).encode())
+    }
+    fn visit_i64<E>(self, n: i64) -> Result<u64, E> {
+        if n >= 0 {
+            self.store.unsigned.push(n as u64);
+            return Ok(JsonIdx::Unsigned(self.store.unsigned.len() - 1).encode());
+        }
+        self.store.signed.push(n);
+        Ok(JsonIdx::Signed(self.store.signed.len() - 1).encode())
+    }
+    fn visit_f64<E>(self, n: f64) -> Result<u64, E> {
+        self.store.floats.push(n);
+        Ok(JsonIdx::Float(self.store.floats.len() - 1).encode())
+    }
+    fn visit_str<E>(self, s: &str) -> Result<u64, E> {
+        self.store.strings.push(s.as_bytes());
+        Ok(JsonIdx::String(self.store.strings.len() - 1).encode())
+    }
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<u64, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element_seed(JsonSeed { store: self.store })? {
+            items.push(item);
+        }
+        self.store.arrays.push_iter(items);
+        Ok(JsonIdx::Array(self.store.arrays.len() - 1).encode())
+    }
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<u64, A::Error> {
+        let mut items: Vec<(String, u64)> = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map.next_value_seed(JsonSeed { store: self.store })?;
+            items.push((key, value));
+        }
+        self.store.objects.push_iter(items.iter().map(|(key, value)| (key.as_bytes(), value)));
+        Ok(JsonIdx::Object(self.store.objects.len() - 1).encode())
+    }
+}
+
+impl<'a, IC, UC, NC, FC, SC, AC, OC> crate::AsBytes<'a> for Jsons<IC, UC, NC, FC, SC, AC, OC>
+where
+    IC: crate::AsBytes<'a>,
+    UC: crate::AsBytes<'a>,
+    NC: crate::AsBytes<'a>,
+    FC: crate::AsBytes<'a>,
+    SC: crate::AsBytes<'a>,
+    AC: crate::AsBytes<'a>,
+    OC: crate::AsBytes<'a>,
+{
+    const SLICE_COUNT: usize = IC::SLICE_COUNT + UC::SLICE_COUNT + NC::SLICE_COUNT + FC::SLICE_COUNT + SC::SLICE_COUNT + AC::SLICE_COUNT + OC::SLICE_COUNT;
+    #[inline]
+    fn get_byte_slice(&self, mut index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        if index < IC::SLICE_COUNT { return self.roots.get_byte_slice(index); }
+        index -= IC::SLICE_COUNT;
+        if index < UC::SLICE_COUNT { return self.unsigned.get_byte_slice(index); }
+        index -= UC::SLICE_COUNT;
+        if index < NC::SLICE_COUNT { return self.signed.get_byte_slice(index); }
+        index -= NC::SLICE_COUNT;
+        if index < FC::SLICE_COUNT { return self.floats.get_byte_slice(index); }
+        index -= FC::SLICE_COUNT;
+        if index < SC::SLICE_COUNT { return self.strings.get_byte_slice(index); }
+        index -= SC::SLICE_COUNT;
+        if index < AC::SLICE_COUNT { return self.arrays.get_byte_slice(index); }
+        index -= AC::SLICE_COUNT;
+        self.objects.get_byte_slice(index)
+    }
+}
+
+impl<IC, UC, NC, FC, SC, AC, OC> crate::schema::Schema for Jsons<IC, UC, NC, FC, SC, AC, OC>
+where
+    IC: crate::schema::Schema,
+    UC: crate::schema::Schema,
+    NC: crate::schema::Schema,
+    FC: crate::schema::Schema,
+    SC: crate::schema::Schema,
+    AC: crate::schema::Schema,
+    OC: crate::schema::Schema,
+{
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Jsons", [
+            ("roots", IC::schema()),
+            ("unsigned", UC::schema()),
+            ("signed", NC::schema()),
+            ("floats", FC::schema()),
+            ("strings", SC::schema()),
+            ("arrays", AC::schema()),
+            ("objects", OC::schema()),
+        ])
+    }
+}
+
+impl<'a> crate::FromBytes<'a> for JsonsBorrowed<'a> {
+    const SLICE_COUNT: usize = <JsonsBorrowed<'a> as crate::AsBytes<'a>>::SLICE_COUNT;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            roots: crate::FromBytes::from_bytes(bytes),
+            unsigned: crate::FromBytes::from_bytes(bytes),
+            signed: crate::FromBytes::from_bytes(bytes),
+            floats: crate::FromBytes::from_bytes(bytes),
+            strings: crate::FromBytes::from_bytes(bytes),
+            arrays: crate::FromBytes::from_bytes(bytes),
+            objects: crate::FromBytes::from_bytes(bytes),
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self {
+            roots: crate::FromBytes::from_store(store, offset),
+            unsigned: crate::FromBytes::from_store(store, offset),
+            signed: crate::FromBytes::from_store(store, offset),
+            floats: crate::FromBytes::from_store(store, offset),
+            strings: crate::FromBytes::from_store(store, offset),
+            arrays: crate::FromBytes::from_store(store, offset),
+            objects: crate::FromBytes::from_store(store, offset),
+        }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        <&[u64]>::element_sizes(sizes)?;
+        <&[u64]>::element_sizes(sizes)?;
+        <&[i64]>::element_sizes(sizes)?;
+        <&[f64]>::element_sizes(sizes)?;
+        <<Lookbacks<Strings> as Borrow>::Borrowed<'a>>::element_sizes(sizes)?;
+        <Vecs<&[u64], &[u64]>>::element_sizes(sizes)?;
+        <Vecs<(<Lookbacks<Strings> as Borrow>::Borrowed<'a>, &[u64]), &[u64]>>::element_sizes(sizes)?;
+        Ok(())
+    }
+    /// Validates each container, and that each encoded `JsonIdx` references an existing value.
+    ///
+    /// Each array and object must be reached at most once from the roots, which rules out cycles.
+    fn validate_deep(&self) -> Result<usize, String> {
+        let lens = [
+            self.unsigned.validate_deep()?,
+            self.signed.validate_deep()?,
+            self.floats.validate_deep()?,
+            self.strings.validate_deep()?,
+            self.arrays.validate_deep()?,
+            self.objects.validate_deep()?,
+        ];
+        let encoded = self.roots.iter().chain(self.arrays.values).chain(self.objects.values.1);
+        for idx in encoded.map(|encoded| JsonIdx::decode(*encoded)) {
+            let (index, len) = match idx {
+                JsonIdx::Null | JsonIdx::Bool(_) => continue,
+                JsonIdx::Unsigned(i) => (i, lens[0]),
+                JsonIdx::Signed(i) => (i, lens[1]),
+                JsonIdx::Float(i) => (i, lens[2]),
+                JsonIdx::String(i) => (i, lens[3]),
+                JsonIdx::Array(i) => (i, lens[4]),
+                JsonIdx::Object(i) => (i, lens[5]),
+            };
+            if index >= len {
+                return Err(format!("Jsons: {:?} references beyond {} values", idx, len));
+            }
+        }
+        // Visit each value, as copying and serializing values will, and bound the arrays and objects visited.
+        let mut budget = lens[4] + lens[5];
+        let mut todo = Vec::new();
+        for (index, root) in self.roots.iter().enumerate() {
+            todo.push(*root);
+            while let Some(encoded) = todo.pop() {
+                match JsonIdx::decode(encoded) {
+                    JsonIdx::Array(i) => Extend::extend(&mut todo, self.arrays.get(i).into_iter().copied()),
+                    JsonIdx::Object(i) => Extend::extend(&mut todo, self.objects.get(i).into_iter().map(|(_, value)| *value)),
+                    _ => continue,
+                }
+                budget = budget.checked_sub(1).ok_or_else(|| format!("Jsons: value {} visits more arrays and objects than exist", index))?;
+            }
+        }
+        Ok(self.roots.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use serde_json::json;
+    use crate::{Borrow, Container, Index, Len};
+    use crate::bytes::stash::Stash;
+    use super::Jsons;
+
+    fn values() -> Vec<serde_json::Value> {
+        vec![
+            json!(null),
+            json!(true),
+            json!([1, -2, 3.5, "four", [], {}]),
+            json!({"name": "columnar", "version": [0, 12, 1], "nested": {"deep": [null, false, {"x": -1}]}}),
+            json!({"name": "roaring", "version": [0, 1], "nested": {}}),
+            json!(u64::MAX),
+            json!("\u{1F980}"),
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut jsons = Jsons::default();
+        for value in values() { jsons.push_value(value).unwrap(); }
+        // References to `Value` are deserializers too.
+        for value in values().iter() { jsons.push_value(value).unwrap(); }
+        assert_eq!(jsons.len(), 2 * values().len());
+
+        let borrowed = jsons.borrow();
+        for (index, value) in values().iter().chain(values().iter()).enumerate() {
+            assert_eq!(&serde_json::to_value(borrowed.get(index)).unwrap(), value);
+        }
+
+        // Re-pushing references produces the same contents.
+        let mut copy = Jsons::default();
+        copy.extend_from_self(jsons.borrow(), 0 .. jsons.len());
+        assert_eq!(copy, jsons);
+    }
+
+    #[test]
+    fn navigate() {
+        let mut jsons = Jsons::default();
+        jsons.push_value(serde_json::from_str::<serde_json::Value>(r#"{"a": [10, -20, 1.5, "x"], "b": {"c": true}}"#).unwrap()).unwrap();
+        // Deserialize directly from JSON text, without an intermediate `Value`.
+        jsons.push_value(&mut serde_json::Deserializer::from_str(r#"[{"k": null}]"#)).unwrap();
+
+        let root = (&jsons).get(0);
+        let array = root.key("a").unwrap();
+        assert_eq!(array.as_array().unwrap().len(), 4);
+        assert_eq!(array.item(0).unwrap().as_u64(), Some(10));
+        assert_eq!(array.item(1).unwrap().as_i64(), Some(-20));
+        assert_eq!(array.item(2).unwrap().as_f64(), Some(1.5));
+        assert_eq!(array.item(3).unwrap().as_str(), Some("x"));
+        assert!(array.item(4).is_none());
+        assert_eq!(root.key("b").and_then(|b| b.key("c")).and_then(|c| c.as_bool()), Some(true));
+        assert!(root.key("z").is_none());
+        assert!((&jsons).get(1).item(0).unwrap().key("k").unwrap().is_null());
+    }
+
+    #[test]
+    fn round_trip_stash() {
+        let mut jsons = Jsons::default();
+        for value in values() { jsons.push_value(value).unwrap(); }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &jsons.borrow()).unwrap();
+        let stash: Stash<Jsons, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+        let borrowed = stash.borrow();
+        assert_eq!(borrowed.len(), values().len());
+        for (index, value) in values().iter().enumerate() {
+            assert_eq!(&serde_json::to_value(borrowed.get(index)).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn validate_cycles() {
+        use crate::FromBytes;
+        use super::JsonIdx;
+
+        let mut jsons = Jsons::default();
+        jsons.push_value(json!([[1], {"a": [2]}])).unwrap();
+        let borrowed = jsons.borrow();
+        assert_eq!(borrowed.validate_deep(), Ok(1));
+
+        // Arrays are `[1]`, `[2]`, and the root, and the object contains the second array.
+        assert_eq!(borrowed.arrays.values.len(), 4);
+        let (u0, u1) = (JsonIdx::Unsigned(0).encode(), JsonIdx::Unsigned(1).encode());
+        let (a0, o0) = (JsonIdx::Array(0).encode(), JsonIdx::Object(0).encode());
+        // An array that contains itself, and an object and array that contain each other.
+        let mut broken = borrowed;
+        let values = [a0, u1, a0, o0];
+        broken.arrays.values = &values[..];
+        assert!(broken.validate_deep().is_err());
+        let mut broken = borrowed;
+        let values = [u0, o0, a0, o0];
+        broken.arrays.values = &values[..];
+        assert!(broken.validate_deep().is_err());
+
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &broken).unwrap();
+        assert!(Stash::<Jsons, Vec<u8>>::try_from_bytes_deep(bytes).is_err());
+    }
+}
@@ -18,10 +18,27 @@ extern crate columnar_derive;
 pub use columnar_derive::Columnar;
 
 pub mod adts;
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod boxed;
 pub mod bytes;
+pub mod dictionary;
+#[cfg(feature = "serde")]
+pub mod dynamic;
+#[cfg(feature = "serde")]
+pub mod json;
 pub mod lookback;
+pub mod maps;
+pub mod packed;
+#[cfg(feature = "parquet")]
+pub mod parquet;
 pub mod primitive;
+pub mod recursive;
+pub mod roaring;
+pub mod runs;
+pub mod schema;
+pub mod search;
+pub mod sort;
 pub mod string;
 pub mod sums;
 pub mod vector;
@@ -35,6 +52,11 @@ pub use vector::Vecs;
 pub use string::Strings;
 pub use sums::{rank_select::RankSelect, result::Results, option::Options, discriminant::Discriminant};
 pub use lookback::{Repeats, Lookbacks};
+pub use dictionary::Dictionary;
+pub use runs::Runs;
+pub use recursive::Recursive;
+pub use maps::Maps;
+pub use schema::Schema;
 
 /// A type that can be represented in columnar form.
 ///
@@ -159,6 +181,26 @@ pub trait Container : Borrow + Clear + for<'a> Push<Self::Ref<'a>> + Default + S
     fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
         self.extend(range.map(|i| other.get(i)))
     }
+
+    /// Extends `self` by the elements of `other` at `indices`, in order.
+    ///
+    /// The default implementation calls `extend_from_self` for each run of consecutive indices.
+    /// This method can be specialized when the container is a collection of independent columns,
+    /// as tuples and derived structs are, each of which can then be gathered independently.
+    #[inline]
+    fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+        let mut position = 0;
+        while position < indices.len() {
+            let lower = indices[position];
+            let mut upper = lower + 1;
+            position += 1;
+            while indices.get(position) == Some(&upper) {
+                upper += 1;
+                position += 1;
+            }
+            self.extend_from_self(other, lower .. upper);
+        }
+    }
 }
 
 impl<T: Clone + 'static> Borrow for Vec<T> {
@@ -173,6 +215,12 @@ impl<T: Clone + Send + 'static> Container for Vec<T> {
     fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
         self.extend_from_slice(&other[range])
     }
+    fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+        self.reserve(indices.len());
+        for &index in indices {
+            self.push(other[index].clone());
+        }
+    }
     fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
         self.reserve(selves.map(|x| x.len()).sum::<usize>())
     }
@@ -461,6 +509,7 @@ pub mod common {
             Self { lower, upper, slice }
         }
         pub fn len(&self) -> usize { self.upper - self.lower }
+        pub fn is_empty(&self) -> bool { self.upper == self.lower }
         /// Map the slice to another type.
         pub(crate) fn map<T>(self, f: impl Fn(S) -> T) -> Slice<T> {
             Slice {
@@ -483,8 +532,8 @@ pub mod common {
     impl<S: Index> PartialEq<[S::Ref]> for Slice<S> where S::Ref: PartialEq {
         fn eq(&self, other: &[S::Ref]) -> bool {
             if self.len() != other.len() { return false; }
-            for i in 0 .. self.len() {
-                if self.get(i) != other[i] { return false; }
+            for (i, other) in other.iter().enumerate() {
+                if self.get(i) != *other { return false; }
             }
             true
         }
@@ -492,8 +541,8 @@ pub mod common {
     impl<S: Index> PartialEq<Vec<S::Ref>> for Slice<S> where S::Ref: PartialEq {
         fn eq(&self, other: &Vec<S::Ref>) -> bool {
             if self.len() != other.len() { return false; }
-            for i in 0 .. self.len() {
-                if self.get(i) != other[i] { return false; }
+            for (i, other) in other.iter().enumerate() {
+                if self.get(i) != *other { return false; }
             }
             true
         }
@@ -568,6 +617,7 @@ pub mod common {
         ///
         /// This method exists rather than an `IntoIterator` implementation to avoid
         /// a conflicting implementation for pushing an `I: IntoIterator` into `Vecs`.
+        #[allow(clippy::should_implement_trait)]
         pub fn into_iter(self) -> IterOwn<Slice<S>> {
             self.into_index_iter()
         }
@@ -713,25 +763,47 @@ pub mod common {
             }
             Ok(())
         }
+        /// Validates the internal structure of a decoded `self`, and returns its length.
+        ///
+        /// Where [`validate`](Self::validate) checks the framing and element sizes of slices,
+        /// this method checks the invariants that indexing relies on, for example that vector
+        /// bounds are non-decreasing and within their values. The length is returned so that
+        /// containers can check their references into their fields. If this method returns
+        /// `Ok(len)`, then indexing `self` at any position less than `len` should not panic.
+        ///
+        /// The default returns `Err`, so that deep validation rejects data for types that have
+        /// not implemented this method. Deep validation is only performed when requested, as by
+        /// [`Stash::try_from_bytes_deep`](crate::bytes::stash::Stash::try_from_bytes_deep), and
+        /// types without this method remain usable through the structural validation of `validate`.
+        fn validate_deep(&self) -> Result<usize, String> {
+            Err(format!("validate_deep not implemented for this type (SLICE_COUNT = {})", Self::SLICE_COUNT))
+        }
     }
 
-}
-
-/// Roaring bitmap (and similar) containers.
-pub mod roaring {
-
-    use alloc::vec::Vec;
-    use crate::Results;
-
-    /// A container for `bool` that uses techniques from Roaring bitmaps.
+    /// Checks that all `lens` are equal, and returns their common value.
     ///
-    /// These techniques are to block the bits into blocks of 2^16 bits,
-    /// and to encode each block based on its density. Either a bitmap
-    /// for dense blocks or a list of set bits for sparse blocks.
-    ///
-    /// Additionally, other representations encode runs of set bits.
-    pub struct RoaringBits {
-        _inner: Results<[u64; 1024], Vec<u16>>,
+    /// This validates containers whose fields must have equal lengths, like tuples and derived structs.
+    pub fn validate_lengths(name: &str, lens: &[usize]) -> Result<usize, String> {
+        match lens.first() {
+            Some(first) if lens.iter().any(|len| len != first) => Err(format!("{}: fields have unequal lengths {:?}", name, lens)),
+            first => Ok(first.copied().unwrap_or(0)),
+        }
     }
-}
 
+    /// Checks that the first `count` of `bounds` are non-decreasing and that the last is at most `values`.
+    pub(crate) fn validate_bounds<B: IndexAs<u64>>(name: &str, bounds: &B, count: usize, values: usize) -> Result<(), String> {
+        let mut prior = 0;
+        for index in 0 .. count {
+            let bound = bounds.index_as(index);
+            if bound < prior {
+                return Err(format!("{}: bound {} at {} precedes prior bound {}", name, bound, index, prior));
+            }
+            prior = bound;
+        }
+        if prior > values as u64 {
+            return Err(format!("{}: last bound {} exceeds {} values", name, prior, values));
+        }
+        Ok(())
+    }
+
+}
@@ -2,6 +2,9 @@
 //!
 //! This has the potential to be more efficient than a list of `T` when many values repeat in
 //! close proximity. Values must be equatable, and the degree of lookback can be configured.
+//!
+//! Either can wrap the container of a field of a derived type, with the derive attribute
+//! `#[columnar(with = Repeats)]` or `#[columnar(with = Lookbacks)]`.
 use alloc::{vec::Vec, string::String};
 
 use crate::{Options, Results, Push, Index, Len, Clear, Borrow, Container, IndexAs};
@@ -118,7 +121,13 @@ impl<'a, TC: crate::AsBytes<'a>, CC: crate::AsBytes<'a>, VC: crate::AsBytes<'a>>
     }
 }
 
-impl<'a, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for Repeats<TC, CC, VC, &'a [u64]> {
+impl<TC: crate::schema::Schema, CC: crate::schema::Schema, VC: crate::schema::Schema, WC> crate::schema::Schema for Repeats<TC, CC, VC, WC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Repeats", [("inner", <Options<TC, CC, VC, WC>>::schema())])
+    }
+}
+
+impl<'a, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a> + IndexAs<u64> + Len, VC: crate::FromBytes<'a> + IndexAs<u64> + Len> crate::FromBytes<'a> for Repeats<TC, CC, VC, &'a [u64]> {
     const SLICE_COUNT: usize = <Options<TC, CC, VC, &'a [u64]>>::SLICE_COUNT;
     #[inline(always)]
     fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -131,6 +140,14 @@ impl<'a, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a>, VC: crate::FromByte
     fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
         <Options<TC, CC, VC, &'a [u64]>>::element_sizes(sizes)
     }
+    /// Validates the inner options, and that the first element is a value rather than a repeat.
+    fn validate_deep(&self) -> Result<usize, String> {
+        let len = self.inner.validate_deep()?;
+        if len > 0 && !self.inner.indexes.get(0) {
+            return Err("Repeats: the first element repeats a prior value, but there is none".into());
+        }
+        Ok(len)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -235,7 +252,13 @@ impl<'a, TC: crate::AsBytes<'a>, VC: crate::AsBytes<'a>, CC: crate::AsBytes<'a>,
     }
 }
 
-impl<'a, TC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>, CC: crate::FromBytes<'a>, RC: crate::FromBytes<'a>> crate::FromBytes<'a> for Lookbacks<TC, VC, CC, RC, &'a [u64]> {
+impl<TC: crate::schema::Schema, VC: crate::schema::Schema, CC: crate::schema::Schema, RC: crate::schema::Schema, WC, const N: u8> crate::schema::Schema for Lookbacks<TC, VC, CC, RC, WC, N> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Lookbacks", [("inner", <Results<TC, VC, CC, RC, WC>>::schema())])
+    }
+}
+
+impl<'a, TC: crate::FromBytes<'a>, VC: crate::FromBytes<'a> + IndexAs<u8>, CC: crate::FromBytes<'a> + IndexAs<u64> + Len, RC: crate::FromBytes<'a> + IndexAs<u64> + Len> crate::FromBytes<'a> for Lookbacks<TC, VC, CC, RC, &'a [u64]> {
     const SLICE_COUNT: usize = <Results<TC, VC, CC, RC, &'a [u64]>>::SLICE_COUNT;
     #[inline(always)]
     fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -248,6 +271,21 @@ impl<'a, TC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>, CC: crate::FromByte
     fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
         <Results<TC, VC, CC, RC, &'a [u64]>>::element_sizes(sizes)
     }
+    /// Validates the inner results, and that each lookback references a prior value.
+    fn validate_deep(&self) -> Result<usize, String> {
+        let len = self.inner.validate_deep()?;
+        let mut rank = 0;
+        for index in 0 .. len {
+            if self.inner.indexes.get(index) { rank += 1; }
+            else {
+                let back: u8 = self.inner.errs.index_as(index - rank);
+                if back as usize >= rank {
+                    return Err(format!("Lookbacks: element {} looks back {} values, but only {} precede it", index, back as usize + 1, rank));
+                }
+            }
+        }
+        Ok(len)
+    }
 }
 
 #[cfg(test)]
@@ -273,11 +311,11 @@ mod test {
         let repeats = repeats_from(&[1, 1, 2, 2, 1]);
 
         assert_eq!(repeats.len(), 5);
-        assert_eq!((&repeats).get(0), 1);
-        assert_eq!((&repeats).get(1), 1);
-        assert_eq!((&repeats).get(2), 2);
-        assert_eq!((&repeats).get(3), 2);
-        assert_eq!((&repeats).get(4), 1);
+        assert_eq!(repeats.get(0), 1);
+        assert_eq!(repeats.get(1), 1);
+        assert_eq!(repeats.get(2), 2);
+        assert_eq!(repeats.get(3), 2);
+        assert_eq!(repeats.get(4), 1);
 
         // Verify compression: only 3 distinct values stored (1, 2, 1).
         assert_eq!(repeats.inner.somes.len(), 3);
@@ -305,9 +343,9 @@ mod test {
     fn ref_index() {
         let repeats = repeats_from(&[10, 10, 20]);
 
-        assert_eq!((&repeats).get(0), 10u64);
-        assert_eq!((&repeats).get(1), 10u64);
-        assert_eq!((&repeats).get(2), 20u64);
+        assert_eq!(repeats.get(0), 10u64);
+        assert_eq!(repeats.get(1), 10u64);
+        assert_eq!(repeats.get(2), 20u64);
     }
 
     #[test]
@@ -320,7 +358,7 @@ mod test {
 
         repeats.push(&3u64);
         assert_eq!(repeats.len(), 1);
-        assert_eq!((&repeats).get(0), 3);
+        assert_eq!(repeats.get(0), 3);
     }
 
     #[test]
@@ -438,11 +476,11 @@ mod test {
         let lookbacks = lookbacks_from(&[10, 20, 10, 30, 20]);
 
         assert_eq!(lookbacks.len(), 5);
-        assert_eq!((&lookbacks).get(0), 10);
-        assert_eq!((&lookbacks).get(1), 20);
-        assert_eq!((&lookbacks).get(2), 10);
-        assert_eq!((&lookbacks).get(3), 30);
-        assert_eq!((&lookbacks).get(4), 20);
+        assert_eq!(lookbacks.get(0), 10);
+        assert_eq!(lookbacks.get(1), 20);
+        assert_eq!(lookbacks.get(2), 10);
+        assert_eq!(lookbacks.get(3), 30);
+        assert_eq!(lookbacks.get(4), 20);
 
         // Values 10 and 20 at indices 2 and 4 should be lookbacks, not new oks.
         assert_eq!(lookbacks.inner.oks.len(), 3);
@@ -476,7 +514,7 @@ mod test {
 
         lookbacks.push(&3u64);
         assert_eq!(lookbacks.len(), 1);
-        assert_eq!((&lookbacks).get(0), 3);
+        assert_eq!(lookbacks.get(0), 3);
     }
 
     #[test]
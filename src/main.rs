@@ -8,14 +8,15 @@ enum Group<T> {
 
 fn main() {
 
-    let mut roster = Vec::new();
-    roster.push(Group::Solo(
-        ("Alice".to_string(), 20u64)
-    ));
-    roster.push(Group::Team(vec![
-        ("Bob".to_string(), 21),
-        ("Carol".to_string(), 22),
-    ]));
+    let roster = vec![
+        Group::Solo(
+            ("Alice".to_string(), 20u64)
+        ),
+        Group::Team(vec![
+            ("Bob".to_string(), 21),
+            ("Carol".to_string(), 22),
+        ]),
+    ];
 
     // An appendable replacement for `&[T]`: indexable, shareable.
     // Layout in memory is a small number of contiguous buffers,
@@ -130,6 +131,54 @@ mod test {
         is_ord_eq::<Test3Reference<u8, u8, u8>>();
     }
 
+    #[test]
+    fn search_derived() {
+        use columnar::{Borrow, Columnar};
+        use columnar::search::{Search, merge};
+
+        let evens: Vec<_> = (0 .. 10).map(|i| Test6 { bar: 2 * i }).collect();
+        let odds: Vec<_> = (0 .. 10).map(|i| Test6 { bar: 2 * i + 1 }).collect();
+        let evens = Columnar::as_columns(evens.iter());
+        let odds = Columnar::as_columns(odds.iter());
+
+        let probe = Columnar::as_columns([Test6 { bar: 7 }].iter());
+        let probe = columnar::Index::get(&probe.borrow(), 0);
+        assert_eq!(evens.borrow().lower_bound(&probe), 4);
+        assert_eq!(odds.borrow().equal_range(&probe), 3 .. 4);
+
+        let mut merged: <Test6 as Columnar>::Container = Default::default();
+        merge(&[evens.borrow(), odds.borrow()], &mut merged);
+        let values: Vec<i16> = merged.bar.clone();
+        assert_eq!(values, (0 .. 20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sort_derived() {
+        use columnar::{Borrow, Columnar, Container, Index};
+
+        let items: Vec<_> = [3u8, 1, 2, 1].iter().map(|&i| Test1 { foo: vec![i; i as usize], bar: i16::from(i) }).collect();
+        let mut columns = Columnar::as_columns(items.iter());
+
+        // Derived structs gather each field independently.
+        let mut gathered: <Test1<u8> as Columnar>::Container = Default::default();
+        gathered.gather(columns.borrow(), &[3, 0]);
+        assert_eq!(gathered.bar, [1, 3]);
+        assert_eq!(gathered.foo.borrow().get(1).len(), 3);
+
+        columnar::sort::sort_by_key(&mut columns, |x| *x.bar);
+        assert_eq!(columns.bar, [1, 1, 2, 3]);
+        for (index, item) in columns.borrow().into_index_iter().enumerate() {
+            assert_eq!(item.foo.len(), [1, 1, 2, 3][index]);
+        }
+
+        // Enums use the default gather.
+        let items = [Test3::Foo(vec![1u8], 1), Test3::Bar(2), Test3::Void, Test3::Bar(0)];
+        let mut columns = Columnar::as_columns(items.iter());
+        columnar::sort::sort(&mut columns);
+        let sorted: Vec<Test3<u8>> = columns.borrow().into_index_iter().map(Columnar::into_owned).collect();
+        assert!(matches!(sorted[..], [Test3::Foo(_, 1), Test3::Bar(0), Test3::Bar(2), Test3::Void]));
+    }
+
     #[test]
     fn round_trip() {
 
@@ -182,7 +231,7 @@ mod test {
     fn named_enum_fields() {
         use columnar::{Borrow, Index, Len, Push, Columnar};
 
-        let items = vec![
+        let items = [
             Test7::Click { x: 10, y: 20 },
             Test7::Scroll(-5),
             Test7::Idle,
@@ -193,19 +242,19 @@ mod test {
         assert_eq!(columns.len(), 4);
 
         // Check indexing.
-        match (&columns).get(0) {
+        match columns.get(0) {
             Test7Reference::Click((x, y)) => { assert_eq!(x, 10); assert_eq!(y, 20); },
             _ => panic!("expected Click"),
         }
-        match (&columns).get(1) {
+        match columns.get(1) {
             Test7Reference::Scroll(d) => { assert_eq!(d, -5); },
             _ => panic!("expected Scroll"),
         }
-        match (&columns).get(2) {
+        match columns.get(2) {
             Test7Reference::Idle(_) => {},
             _ => panic!("expected Idle"),
         }
-        match (&columns).get(3) {
+        match columns.get(3) {
             Test7Reference::Click((x, y)) => { assert_eq!(x, 30); assert_eq!(y, 40); },
             _ => panic!("expected Click"),
         }
@@ -221,7 +270,7 @@ mod test {
         // Check push by reference.
         let mut columns2: columnar::ContainerOf<Test7> = Default::default();
         columns2.push(&Test7::Click { x: 99, y: 100 });
-        match (&columns2).get(0) {
+        match columns2.get(0) {
             Test7Reference::Click((x, y)) => { assert_eq!(x, 99); assert_eq!(y, 100); },
             _ => panic!("expected Click"),
         }
@@ -289,4 +338,695 @@ mod test {
     struct BoxedStr {
         value: Box<str>,
     }
+
+    // Tests derived implementations with bit-packed fields.
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    struct Reading {
+        #[columnar(packed)]
+        sensor: u32,
+        #[columnar(packed)]
+        delta: i64,
+        label: String,
+    }
+
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    struct Pair(#[columnar(packed)] u16, u16);
+
+    #[test]
+    fn packed_fields() {
+        use columnar::{Borrow, Columnar, Index, Len};
+
+        let readings: Vec<Reading> = (0 .. 200).map(|i| Reading { sensor: i % 4, delta: 50 - i as i64, label: format!("r{i}") }).collect();
+        let columns = Columnar::as_columns(readings.iter());
+        let _: &columnar::packed::Packed<u32> = &columns.sensor;
+        assert_eq!(columns.len(), readings.len());
+        // Sensors take two bits each, in three complete blocks.
+        assert_eq!(columns.sensor.words.len(), 6);
+        for (index, reading) in readings.iter().enumerate() {
+            let reference = columns.borrow().get(index);
+            assert_eq!(reference.sensor, reading.sensor);
+            assert_eq!(reference.delta, reading.delta);
+            assert_eq!(Reading::into_owned(reference), *reading);
+        }
+
+        let mut owned = readings[0].clone();
+        owned.copy_from(columns.borrow().get(7));
+        assert_eq!(owned, readings[7]);
+
+        let pairs = [Pair(1, 2), Pair(3, 4)];
+        let columns = Columnar::as_columns(pairs.iter());
+        assert_eq!(Pair::into_owned(columns.borrow().get(1)), Pair(3, 4));
+    }
+
+    // Tests derived implementations with fields that name their containers.
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    struct Visit {
+        #[columnar(container = columnar::Dictionary<Vec<u16>>)]
+        status: u16,
+        #[columnar(with = columnar::Repeats)]
+        session: u64,
+        #[columnar(with = columnar::Lookbacks)]
+        page: u64,
+        duration: u32,
+    }
+
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    enum Action {
+        View(#[columnar(with = columnar::Repeats)] u64),
+        Search { query: String, #[columnar(with = columnar::Dictionary)] results: u32 },
+        Leave,
+    }
+
+    #[test]
+    fn container_fields() {
+        use columnar::{Borrow, Columnar, Index, Len};
+
+        let visits: Vec<Visit> = (0 .. 100u64).map(|i| Visit {
+            status: [200, 404, 500][i as usize % 3],
+            session: i / 10,
+            page: i % 4,
+            duration: i as u32,
+        }).collect();
+        let columns = Columnar::as_columns(visits.iter());
+        let _: &columnar::Dictionary<Vec<u16>> = &columns.status;
+        assert_eq!(columns.status.distinct(), 3);
+        let _: &columnar::Repeats<Vec<u64>> = &columns.session;
+        assert_eq!(columns.session.inner.somes.len(), 10);
+        assert_eq!(columns.page.inner.oks.len(), 4);
+        assert_eq!(columns.len(), visits.len());
+        for (index, visit) in visits.iter().enumerate() {
+            assert_eq!(Visit::into_owned(columns.borrow().get(index)), *visit);
+        }
+        let mut owned = visits[0].clone();
+        owned.copy_from(columns.borrow().get(42));
+        assert_eq!(owned, visits[42]);
+
+        let actions = [
+            Action::View(3), Action::View(3), Action::Search { query: "columnar".to_string(), results: 2 },
+            Action::Leave, Action::Search { query: "columnar".to_string(), results: 2 },
+        ];
+        let columns = Columnar::as_columns(actions.iter());
+        let _: &columnar::Repeats<Vec<u64>> = &columns.View;
+        assert_eq!(columns.Search.1.distinct(), 1);
+        for (index, action) in actions.iter().enumerate() {
+            assert_eq!(Action::into_owned(columns.borrow().get(index)), *action);
+        }
+    }
+
+    // Tests derived implementations with fields that are not stored.
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    struct Sample {
+        value: f64,
+        #[columnar(skip)]
+        cache: Option<String>,
+        #[columnar(skip = unit)]
+        unit: &'static str,
+    }
+
+    fn unit() -> &'static str { "ms" }
+
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    struct Span(u64, #[columnar(skip)] Vec<u8>, u64);
+
+    #[test]
+    fn skip_fields() {
+        use columnar::{AsBytes, Borrow, Columnar, ContainerOf, FromBytes, Index, Len};
+        use columnar::schema::Schema;
+
+        let samples: Vec<Sample> = (0 .. 10).map(|i| Sample {
+            value: i as f64,
+            cache: Some(i.to_string()),
+            unit: "s",
+        }).collect();
+        let columns = Columnar::as_columns(samples.iter());
+        let _: &Vec<f64> = &columns.value;
+        assert_eq!(columns.len(), samples.len());
+        assert_eq!(columns.borrow().as_bytes().count(), 1);
+        let borrowed = <<ContainerOf<Sample> as Borrow>::Borrowed<'_> as FromBytes>::from_bytes(&mut columns.borrow().as_bytes().map(|(_, bytes)| bytes));
+        for (index, sample) in samples.iter().enumerate() {
+            let owned = Sample::into_owned(borrowed.get(index));
+            assert_eq!(owned, Sample { cache: None, unit: "ms", ..sample.clone() });
+        }
+        let mut owned = samples[0].clone();
+        owned.copy_from(columns.borrow().get(7));
+        assert_eq!(owned, Sample { value: 7.0, cache: None, unit: "ms" });
+
+        let spans = [Span(1, vec![1, 2], 3), Span(4, vec![5], 6)];
+        let columns = Columnar::as_columns(spans.iter());
+        assert_eq!(Span::into_owned(columns.borrow().get(1)), Span(4, vec![], 6));
+        let labels = ContainerOf::<Span>::schema().slices().into_iter().map(|(path, _, _)| path).collect::<Vec<_>>();
+        assert_eq!(labels, ["0", "2"]);
+    }
+
+    // Tests reference implementations that behave as the owned type's.
+    #[derive(Columnar, Debug, Clone, PartialEq, Eq, Hash)]
+    #[columnar(derive(PartialEq, Eq), reference(PartialEq, Debug, Hash))]
+    struct Page {
+        url: String,
+        links: Vec<u64>,
+        rank: Option<u32>,
+    }
+
+    #[derive(Columnar, Debug, Clone, PartialEq, Eq, Hash)]
+    #[columnar(derive(PartialEq, Eq), reference(PartialEq, Debug, Hash))]
+    enum Fetch {
+        Found(Page),
+        Moved { from: String, to: String },
+        Missing(u16, bool),
+        Timeout,
+    }
+
+    #[test]
+    fn reference_traits() {
+        use std::collections::HashMap;
+        use std::hash::{BuildHasher, RandomState};
+        use columnar::{Borrow, Columnar, Index};
+
+        let page = Page { url: "a.com".to_string(), links: vec![1, 2, 3], rank: Some(4) };
+        let fetches = [
+            Fetch::Found(page.clone()),
+            Fetch::Found(Page { rank: None, ..page.clone() }),
+            Fetch::Moved { from: "b.com".to_string(), to: "c.com".to_string() },
+            Fetch::Missing(404, true),
+            Fetch::Timeout,
+            Fetch::Missing(404, true),
+        ];
+        let columns = Columnar::as_columns(fetches.iter());
+        let hasher = RandomState::new();
+        let mut counts = HashMap::new();
+        for (index, fetch) in fetches.iter().enumerate() {
+            let reference = columns.borrow().get(index);
+            assert_eq!(reference, *fetch);
+            assert_eq!(format!("{:?}", reference), format!("{:?}", fetch));
+            assert_eq!(hasher.hash_one(reference), hasher.hash_one(fetch));
+            *counts.entry(reference).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 5);
+        assert!(columns.borrow().get(0) != fetches[1]);
+        assert!(columns.borrow().get(3) != Fetch::Missing(404, false));
+
+        let columns = Columnar::as_columns([page.clone()].iter());
+        let reference = columns.borrow().get(0);
+        assert_eq!(reference, page);
+        assert_eq!(format!("{:#?}", reference), format!("{:#?}", page));
+        assert_eq!(hasher.hash_one(reference), hasher.hash_one(&page));
+    }
+
+    // Tests explicit discriminants, which are stored as the tags of their variants.
+    #[derive(Columnar, Debug, PartialEq)]
+    #[repr(u16)]
+    enum Opcode {
+        Halt = 1000,
+        Jump(u64) = 2,
+        Load { addr: u32 } = 40000,
+        Nop,
+    }
+
+    #[derive(Columnar, Debug, Copy, Clone, PartialEq)]
+    #[repr(u16)]
+    enum Level {
+        Low = 7,
+        High = 300,
+    }
+
+    // Tests 64 bit reprs, including the largest discriminant, which has no homogeneous form.
+    #[derive(Columnar, Debug, PartialEq)]
+    #[repr(u64)]
+    enum Status {
+        Ok = 200,
+        Moved(u32) = 1 << 40,
+        Gone,
+        Last = u64::MAX,
+    }
+
+    // Tests reprs wider than tags, whose discriminants must fit the tag for their variant count.
+    #[derive(Columnar, Debug, PartialEq)]
+    #[repr(i128)]
+    enum Huge {
+        Small = 3,
+        Large(u8),
+    }
+
+    // Tests enums with more variants than fit in a `u8` tag.
+    #[derive(Columnar, Debug, PartialEq)]
+    enum Wide {
+        V0, V1, V2, V3, V4, V5, V6, V7, V8, V9, V10, V11, V12, V13, V14, V15, V16, V17, V18, V19,
+        V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30, V31, V32, V33, V34, V35, V36, V37, V38, V39,
+        V40, V41, V42, V43, V44, V45, V46, V47, V48, V49, V50, V51, V52, V53, V54, V55, V56, V57, V58, V59,
+        V60, V61, V62, V63, V64, V65, V66, V67, V68, V69, V70, V71, V72, V73, V74, V75, V76, V77, V78, V79,
+        V80, V81, V82, V83, V84, V85, V86, V87, V88, V89, V90, V91, V92, V93, V94, V95, V96, V97, V98, V99,
+        V100, V101, V102, V103, V104, V105, V106, V107, V108, V109, V110, V111, V112, V113, V114, V115, V116, V117, V118, V119,
+        V120, V121, V122, V123, V124, V125, V126, V127, V128, V129, V130, V131, V132, V133, V134, V135, V136, V137, V138, V139,
+        V140, V141, V142, V143, V144, V145, V146, V147, V148, V149, V150, V151, V152, V153, V154, V155, V156, V157, V158, V159,
+        V160, V161, V162, V163, V164, V165, V166, V167, V168, V169, V170, V171, V172, V173, V174, V175, V176, V177, V178, V179,
+        V180, V181, V182, V183, V184, V185, V186, V187, V188, V189, V190, V191, V192, V193, V194, V195, V196, V197, V198, V199,
+        V200, V201, V202, V203, V204, V205, V206, V207, V208, V209, V210, V211, V212, V213, V214, V215, V216, V217, V218, V219,
+        V220, V221, V222, V223, V224, V225, V226, V227, V228, V229, V230, V231, V232, V233, V234, V235, V236, V237, V238, V239,
+        V240, V241, V242, V243, V244, V245, V246, V247, V248, V249, V250, V251, V252, V253, V254, V255, V256, V257, V258, V259,
+        V260, V261, V262, V263, V264, V265, V266, V267, V268, V269, V270, V271, V272, V273, V274, V275, V276, V277, V278, V279,
+        V280, V281, V282, V283, V284, V285, V286, V287, V288, V289, V290, V291, V292, V293, V294, V295, V296, V297, V298,
+        V299(u64),
+    }
+
+    #[test]
+    fn explicit_discriminants() {
+        use columnar::{Borrow, Columnar, FromBytes, Index, Len};
+
+        let opcodes = [Opcode::Load { addr: 3 }, Opcode::Halt, Opcode::Jump(5), Opcode::Nop, Opcode::Jump(6)];
+        let columns = Columnar::as_columns(opcodes.iter());
+        assert_eq!(columns.indexes.variant, [40000u16, 1000, 2, 40001, 2]);
+        assert_eq!(columns.len(), 5);
+        for (index, opcode) in opcodes.iter().enumerate() {
+            assert_eq!(&Opcode::into_owned(columns.borrow().get(index)), opcode);
+        }
+        assert_eq!(columns.borrow().validate_deep(), Ok(5));
+        let mut borrowed = columns.borrow();
+        borrowed.indexes.variant = &[40000, 1000, 3, 40001, 2];
+        assert!(borrowed.validate_deep().is_err());
+
+        let levels = [Level::High, Level::Low, Level::High];
+        let columns = Columnar::as_columns(levels.iter());
+        assert_eq!(columns.variant, [300u16, 7, 300]);
+        assert_eq!(columns.borrow().get(1), Level::Low);
+        assert_eq!(columns.borrow().validate_deep(), Ok(3));
+        let mut borrowed = columns.borrow();
+        borrowed.variant = &[300, 8];
+        assert!(borrowed.validate_deep().is_err());
+
+        let statuses = [Status::Gone, Status::Ok, Status::Moved(3), Status::Last];
+        let columns = Columnar::as_columns(statuses.iter());
+        assert_eq!(columns.indexes.variant, [(1u64 << 40) + 1, 200, 1 << 40, u64::MAX]);
+        for (index, status) in statuses.iter().enumerate() {
+            assert_eq!(&Status::into_owned(columns.borrow().get(index)), status);
+        }
+        assert_eq!(columns.borrow().validate_deep(), Ok(4));
+        let lasts = [Status::Last, Status::Last];
+        let columns = Columnar::as_columns(lasts.iter());
+        assert_eq!(columns.indexes.variant, [u64::MAX, u64::MAX]);
+        assert_eq!(Status::into_owned(columns.borrow().get(1)), Status::Last);
+        assert_eq!(columns.borrow().validate_deep(), Ok(2));
+
+        let huges = [Huge::Large(5), Huge::Small];
+        let columns = Columnar::as_columns(huges.iter());
+        assert_eq!(columns.indexes.variant, [4u8, 3]);
+        assert_eq!(Huge::into_owned(columns.borrow().get(0)), Huge::Large(5));
+
+        let wides = [Wide::V299(5), Wide::V0, Wide::V298, Wide::V299(6)];
+        let columns = Columnar::as_columns(wides.iter());
+        assert_eq!(columns.indexes.variant, [299u16, 0, 298, 299]);
+        for (index, wide) in wides.iter().enumerate() {
+            assert_eq!(&Wide::into_owned(columns.borrow().get(index)), wide);
+        }
+        let mut borrowed = columns.borrow();
+        borrowed.indexes.variant = &[299, 0, 300, 299];
+        assert!(borrowed.validate_deep().is_err());
+    }
+
+    #[test]
+    fn explicit_discriminants_bytes() {
+        use columnar::{Borrow, Columnar, Index};
+        use columnar::bytes::{indexed, stash::Stash};
+
+        // Writes the columns of `items` and reads them back as a validated stash.
+        fn round_trip<T: Columnar + PartialEq + std::fmt::Debug>(items: &[T]) {
+            let columns = Columnar::as_columns(items.iter());
+            let mut bytes: Vec<u8> = Vec::new();
+            indexed::write(&mut bytes, &columns.borrow()).unwrap();
+            let stash: Stash<T::Container, Vec<u8>> = Stash::try_from_bytes_deep(bytes).unwrap();
+            for (index, item) in items.iter().enumerate() {
+                assert_eq!(&T::into_owned(stash.borrow().get(index)), item);
+            }
+        }
+
+        round_trip(&[Opcode::Load { addr: 3 }, Opcode::Halt, Opcode::Jump(5), Opcode::Nop]);
+        round_trip(&[Level::High, Level::Low]);
+        round_trip(&[Status::Gone, Status::Ok, Status::Moved(3), Status::Last]);
+        round_trip(&[Status::Last, Status::Last]);
+        round_trip(&[Status::Moved(1), Status::Moved(2)]);
+        round_trip(&[Huge::Large(5), Huge::Small]);
+    }
+
+    // Tests derived implementations for recursive structs, whose children are stored as linked nodes.
+    #[derive(Columnar, Debug, PartialEq)]
+    #[columnar(reference(PartialEq))]
+    struct Node {
+        label: String,
+        kids: Vec<Node>,
+    }
+
+    #[derive(Columnar, Debug, PartialEq)]
+    #[columnar(reference(PartialEq))]
+    struct List<T> {
+        value: T,
+        next: Option<Box<List<T>>>,
+    }
+
+    // Tests that fields of other types with the same name are not recursive.
+    mod labels {
+        #[derive(columnar::Columnar, Debug, PartialEq)]
+        pub struct Label(pub String);
+    }
+    #[derive(Columnar, Debug, PartialEq)]
+    struct Label {
+        primary: labels::Label,
+        others: Vec<labels::Label>,
+    }
+
+    #[test]
+    fn recursive_structs() {
+        use columnar::{Borrow, Columnar, Container, FromBytes, Index, Len};
+
+        let labels = [Label { primary: labels::Label("a".to_string()), others: vec![labels::Label("b".to_string())] }];
+        let columns = Columnar::as_columns(labels.iter());
+        assert_eq!(Label::into_owned(columns.borrow().get(0)), labels[0]);
+
+        let leaf = |label: &str| Node { label: label.to_string(), kids: vec![] };
+        let nodes = [
+            Node { label: "a".to_string(), kids: vec![leaf("b"), Node { label: "c".to_string(), kids: vec![leaf("d")] }] },
+            leaf("e"),
+        ];
+        let columns = Columnar::as_columns(nodes.iter());
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns.nodes.len(), 5);
+        assert_eq!(columns.roots, [3, 4]);
+
+        let borrowed = columns.borrow();
+        let root = borrowed.get(0);
+        assert_eq!(root.label, b"a");
+        assert_eq!(root.kids.len(), 2);
+        assert_eq!(root.kids.get(1).kids.get(0).label, b"d");
+        assert_eq!(root.kids.into_iter().map(|kid| kid.label).collect::<Vec<_>>(), [b"b", b"c"]);
+        for (index, node) in nodes.iter().enumerate() {
+            assert_eq!(borrowed.get(index), *node);
+            assert_eq!(&Node::into_owned(borrowed.get(index)), node);
+        }
+
+        // Values pushed from references, or copied from other containers, bring their children along.
+        let mut copied: <Node as Columnar>::Container = Default::default();
+        copied.extend_from_self(borrowed, 0 .. 1);
+        columnar::Push::push(&mut copied, borrowed.get(1));
+        assert_eq!(copied.len(), 2);
+        assert_eq!(copied.nodes.len(), 5);
+        assert_eq!(Node::into_owned(copied.borrow().get(0)), nodes[0]);
+        assert_eq!(Node::into_owned(copied.borrow().get(1)), nodes[1]);
+
+        assert_eq!(borrowed.validate_deep(), Ok(2));
+        let mut broken = borrowed;
+        broken.roots = &[3, 5];
+        assert!(broken.validate_deep().is_err());
+        let mut broken = borrowed;
+        broken.nodes.kids.values = &[2, 0, 2];
+        assert!(broken.validate_deep().is_err());
+
+        let lists = [List { value: 1u8, next: Some(Box::new(List { value: 2, next: None })) }, List { value: 3, next: None }];
+        let columns = Columnar::as_columns(lists.iter());
+        let list = columns.borrow().get(0);
+        assert_eq!(list.value, &1);
+        assert_eq!(list.next.map(|next| *next.get().value), Some(2));
+        assert!(list.next.unwrap().get().next.is_none());
+        assert_eq!(format!("{:?}", list.next), "Some(Child(0))");
+        for (index, list) in lists.iter().enumerate() {
+            assert_eq!(columns.borrow().get(index), *list);
+            assert_eq!(&List::into_owned(columns.borrow().get(index)), list);
+        }
+        let mut copy = List { value: 3, next: None };
+        List::copy_from(&mut copy, columns.borrow().get(0));
+        assert_eq!(copy, lists[0]);
+    }
+
+    #[derive(Columnar, Debug, PartialEq)]
+    #[columnar(reference(PartialEq))]
+    enum Expr {
+        Num(i64),
+        Neg(Box<Expr>),
+        Add(Box<Expr>, Box<Expr>),
+        Call(String, Vec<Expr>),
+        Let { name: String, body: Option<Box<Expr>> },
+    }
+
+    #[test]
+    fn recursive_enums() {
+        use columnar::{Borrow, Columnar, Container, FromBytes, Index, Len};
+
+        let num = |n: i64| Box::new(Expr::Num(n));
+        let exprs = [
+            Expr::Add(num(1), Box::new(Expr::Neg(num(2)))),
+            Expr::Call("f".to_string(), vec![Expr::Num(3), Expr::Let { name: "x".to_string(), body: None }]),
+            Expr::Let { name: "y".to_string(), body: Some(num(4)) },
+        ];
+        let columns = Columnar::as_columns(exprs.iter());
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns.nodes.len(), 9);
+
+        let borrowed = columns.borrow();
+        let ExprReference::Add((left, right)) = borrowed.get(0) else { panic!("expected Add") };
+        assert!(matches!(left.get(), ExprReference::Num(&1)));
+        let ExprReference::Neg(inner) = right.get() else { panic!("expected Neg") };
+        assert!(matches!(inner.get(), ExprReference::Num(&2)));
+        let ExprReference::Call((name, args)) = borrowed.get(1) else { panic!("expected Call") };
+        assert_eq!(name, b"f");
+        assert_eq!(args.len(), 2);
+        assert!(matches!(args.get(0), ExprReference::Num(&3)));
+        for (index, expr) in exprs.iter().enumerate() {
+            assert_eq!(borrowed.get(index), *expr);
+            assert_eq!(&Expr::into_owned(borrowed.get(index)), expr);
+        }
+
+        let mut copied: <Expr as Columnar>::Container = Default::default();
+        copied.extend_from_self(borrowed, 1 .. 3);
+        assert_eq!(copied.len(), 2);
+        assert_eq!(Expr::into_owned(copied.borrow().get(0)), exprs[1]);
+        assert_eq!(Expr::into_owned(copied.borrow().get(1)), exprs[2]);
+
+        let mut copy = Expr::Num(0);
+        Expr::copy_from(&mut copy, borrowed.get(2));
+        assert_eq!(copy, exprs[2]);
+
+        assert_eq!(borrowed.validate_deep(), Ok(3));
+        let mut broken = borrowed;
+        broken.nodes.Neg = &[5];
+        assert!(broken.validate_deep().is_err());
+    }
+
+    #[test]
+    fn validate_derived() {
+        use columnar::{Borrow, Columnar, FromBytes};
+
+        let test1s = [
+            Test1 { foo: vec![1, 2, 3], bar: 4 },
+            Test1 { foo: vec![5, 6, 7], bar: 8 },
+        ];
+        let test1c = Columnar::as_columns(test1s.iter());
+        assert_eq!(test1c.borrow().validate_deep(), Ok(2));
+        let mut borrowed = test1c.borrow();
+        borrowed.bar = &borrowed.bar[1..];
+        assert!(borrowed.validate_deep().is_err());
+
+        let test7s = [Test7::Click { x: 1, y: 2 }, Test7::Scroll(3), Test7::Idle];
+        let test7c = Columnar::as_columns(test7s.iter());
+        assert_eq!(test7c.borrow().validate_deep(), Ok(3));
+        let mut borrowed = test7c.borrow();
+        borrowed.Scroll = &borrowed.Scroll[1..];
+        assert!(borrowed.validate_deep().is_err());
+
+        let test4c = Columnar::as_columns([Test4::Foo, Test4::Bar].iter());
+        assert_eq!(test4c.borrow().validate_deep(), Ok(2));
+        let mut borrowed = test4c.borrow();
+        borrowed.variant = &[0, 2];
+        assert!(borrowed.validate_deep().is_err());
+    }
+
+    #[test]
+    fn schema_derived() {
+        use columnar::{Borrow, ContainerOf, FromBytes};
+        use columnar::schema::Schema;
+
+        fn paths<C>() -> Vec<String> where for<'a> C: Schema + Borrow<Borrowed<'a>: Schema + FromBytes<'a>> {
+            let schema = C::schema();
+            assert_eq!(schema.slice_count(), <C::Borrowed<'_> as FromBytes>::SLICE_COUNT);
+            assert_eq!(schema, <C::Borrowed<'_>>::schema());
+            schema.slices().into_iter().map(|(path, _, _)| path).collect()
+        }
+
+        assert_eq!(paths::<ContainerOf<Test1<u8>>>(), ["foo.bounds", "foo.values", "bar"]);
+        assert_eq!(paths::<ContainerOf<Test2<u8>>>(), ["0.bounds", "0.values", "1"]);
+        assert_eq!(paths::<ContainerOf<Test3<u8>>>(), [
+            "Foo.0.bounds", "Foo.0.values", "Foo.1", "Bar", "Void.count",
+            "indexes.variant", "indexes.offset",
+        ]);
+        assert_eq!(paths::<ContainerOf<Test4>>(), ["indexes"]);
+        assert_eq!(paths::<ContainerOf<Test5>>(), ["count"]);
+    }
+
+    // Two versions of a record, the second adding fields and removing `legacy`.
+    mod v1 {
+        #[derive(columnar::Columnar, Debug)]
+        pub struct Record {
+            pub id: u64,
+            pub name: String,
+            pub legacy: u32,
+        }
+    }
+    mod v2 {
+        #[derive(columnar::Columnar, Debug, PartialEq)]
+        pub struct Record {
+            pub id: u64,
+            #[columnar(added_in = 2)]
+            pub tags: Vec<String>,
+            pub name: String,
+            #[columnar(default)]
+            pub score: i32,
+        }
+    }
+
+    #[test]
+    fn versioned_derived() {
+        use columnar::{Borrow, Columnar, Index, Len};
+        use columnar::bytes::{versioned, stash::Stash};
+        use columnar::schema::Evolve;
+
+        assert_eq!(<v1::Record as Evolve>::VERSION, 0);
+        assert_eq!(<v2::Record as Evolve>::VERSION, 2);
+
+        let olds: Vec<v1::Record> = (0 .. 10).map(|i| v1::Record { id: i, name: format!("{i}"), legacy: 7 }).collect();
+        let mut bytes: Vec<u8> = Vec::new();
+        versioned::write::<v1::Record, _>(&mut bytes, &Columnar::as_columns(olds.iter()).borrow()).unwrap();
+
+        // Read as the newer version, with the added fields as defaults and the removed field skipped.
+        let stash = versioned::read::<v2::Record, _>(bytes.clone()).unwrap();
+        assert!(matches!(stash, Stash::Align(_)));
+        assert_eq!(stash.len(), 10);
+        for (index, old) in olds.iter().enumerate() {
+            let new = v2::Record::into_owned(stash.borrow().get(index));
+            assert_eq!(new, v2::Record { id: old.id, tags: Vec::new(), name: old.name.clone(), score: 0 });
+        }
+
+        // Data of the current version are used in place.
+        let news: Vec<v2::Record> = (0 .. 5).map(|i| v2::Record { id: i, tags: vec![format!("tag{i}")], name: format!("{i}"), score: -1 }).collect();
+        let mut bytes2: Vec<u8> = Vec::new();
+        versioned::write::<v2::Record, _>(&mut bytes2, &Columnar::as_columns(news.iter()).borrow()).unwrap();
+        let stash = versioned::read::<v2::Record, _>(bytes2.clone()).unwrap();
+        assert!(matches!(stash, Stash::Bytes(_)));
+        assert_eq!(v2::Record::into_owned(stash.borrow().get(3)), news[3]);
+
+        // The older version cannot read data without the fields it requires, nor corrupted headers.
+        assert!(versioned::read::<v1::Record, _>(bytes2).err().unwrap().contains("`legacy` is absent"));
+        // A header recording more elements than the data could hold is rejected before defaults are built.
+        let mut words: Vec<u64> = bytes.chunks(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap())).collect();
+        let position = (3 .. 2 + words[1] as usize).find(|p| words[*p - 1] == 0 && words[*p] == 10).unwrap();
+        words[position] = 1 << 40;
+        let hostile = words.iter().flat_map(|word| word.to_le_bytes()).collect::<Vec<u8>>();
+        assert!(versioned::read::<v2::Record, _>(hostile).err().unwrap().contains("exceeds"));
+        bytes[8] ^= 1;
+        assert!(versioned::read::<v2::Record, _>(bytes).is_err());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn arrow_derived() {
+        use columnar::{Borrow, Columnar, ContainerOf, Index, Len};
+        use columnar::arrow::{IntoArrow, FromArrow};
+
+        let test1s: Vec<Test1<u8>> = (0 .. 10).map(|i| Test1 { foo: vec![i; i as usize], bar: -(i as i16) }).collect();
+        let test1c = Columnar::as_columns(test1s.iter());
+        let data = test1c.borrow().into_arrow().unwrap();
+        assert_eq!(data.data_type(), &<ContainerOf<Test1<u8>> as IntoArrow>::data_type());
+        let back = ContainerOf::<Test1<u8>>::from_arrow(&data).unwrap();
+        for (index, test1) in test1s.iter().enumerate() {
+            let test1b = Test1::into_owned(back.borrow().get(index));
+            assert_eq!((test1b.foo, test1b.bar), (test1.foo.clone(), test1.bar));
+        }
+
+        let test7s = [Test7::Click { x: 1, y: 2 }, Test7::Idle, Test7::Scroll(3), Test7::Scroll(4), Test7::Idle];
+        let test7c = Columnar::as_columns(test7s.iter());
+        let data = test7c.into_arrow().unwrap();
+        assert_eq!(data.data_type(), &<ContainerOf<Test7> as IntoArrow>::data_type());
+        let back = ContainerOf::<Test7>::from_arrow(&data).unwrap();
+        assert_eq!(back.len(), 5);
+        assert!(matches!(Test7::into_owned(back.borrow().get(0)), Test7::Click { x: 1, y: 2 }));
+        assert!(matches!(Test7::into_owned(back.borrow().get(3)), Test7::Scroll(4)));
+        assert!(matches!(Test7::into_owned(back.borrow().get(4)), Test7::Idle));
+
+        let test4c = Columnar::as_columns([Test4::Bar, Test4::Foo, Test4::Bar].iter());
+        let back = ContainerOf::<Test4>::from_arrow(&test4c.borrow().into_arrow().unwrap()).unwrap();
+        assert!(matches!(back.borrow().get(2), Test4::Bar));
+        let test5c = Columnar::as_columns([Test5, Test5].iter());
+        assert_eq!(ContainerOf::<Test5>::from_arrow(&test5c.into_arrow().unwrap()).unwrap().len(), 2);
+
+        let opcodes = [Opcode::Nop, Opcode::Jump(5), Opcode::Halt];
+        let back = ContainerOf::<Opcode>::from_arrow(&Columnar::as_columns(opcodes.iter()).into_arrow().unwrap()).unwrap();
+        assert_eq!(back.indexes.variant, [40001u16, 2, 1000]);
+        assert_eq!(Opcode::into_owned(back.borrow().get(1)), Opcode::Jump(5));
+        let back = ContainerOf::<Level>::from_arrow(&Columnar::as_columns([Level::High, Level::Low].iter()).into_arrow().unwrap()).unwrap();
+        assert_eq!(back.borrow().get(0), Level::High);
+    }
+
+    // Tests derived Parquet columns for nested structs, enums, options, and lists.
+    #[cfg(feature = "parquet")]
+    #[derive(Columnar, Debug)]
+    struct Event {
+        id: u64,
+        kind: Test7,
+        tag: Test4,
+        unit: Test5,
+        notes: Option<Vec<String>>,
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_derived() {
+        use columnar::{Borrow, Columnar, ContainerOf};
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let events = [
+            Event { id: 0, kind: Test7::Click { x: 1, y: 2 }, tag: Test4::Foo, unit: Test5, notes: None },
+            Event { id: 1, kind: Test7::Scroll(-3), tag: Test4::Bar, unit: Test5, notes: Some(vec!["a".to_string(), "b".to_string()]) },
+            Event { id: 2, kind: Test7::Idle, tag: Test4::Foo, unit: Test5, notes: Some(vec![]) },
+        ];
+        let column = Columnar::as_columns(events.iter());
+
+        let schema = columnar::parquet::schema::<ContainerOf<Event>>();
+        let names = schema.get_fields().iter().map(|field| field.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names, ["id", "kind", "tag", "unit", "notes"]);
+
+        let path = std::env::temp_dir().join(format!("columnar-parquet-derived-{}.parquet", std::process::id()));
+        columnar::parquet::write(std::fs::File::create(&path).unwrap(), &column.borrow()).unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let rows = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(rows, [
+            "{id: 0, kind: {Click: {0: 1, 1: 2}, Scroll: null, Idle: null}, tag: \"Foo\", unit: true, notes: null}",
+            "{id: 1, kind: {Click: null, Scroll: -3, Idle: null}, tag: \"Bar\", unit: true, notes: [\"a\", \"b\"]}",
+            "{id: 2, kind: {Click: null, Scroll: null, Idle: true}, tag: \"Foo\", unit: true, notes: []}",
+        ]);
+
+        // Discriminants that are not variants are errors rather than panics.
+        let mut borrowed = column.borrow();
+        borrowed.tag.variant = &[0, 7, 1];
+        assert!(columnar::parquet::write(std::fs::File::create(&path).unwrap(), &borrowed).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    // Tests derived implementations with map fields.
+    #[derive(Columnar, Debug, Clone, PartialEq)]
+    struct Inventory {
+        name: String,
+        counts: std::collections::BTreeMap<String, u64>,
+    }
+
+    #[test]
+    fn map_fields() {
+        use columnar::{Borrow, Columnar, Index};
+
+        let inventories: Vec<Inventory> = (0 .. 10u64).map(|i| Inventory {
+            name: format!("store{i}"),
+            counts: (0 .. i).map(|j| (format!("item{j}"), i * j)).collect(),
+        }).collect();
+        let columns = Columnar::as_columns(inventories.iter());
+        for (index, inventory) in inventories.iter().enumerate() {
+            let reference = columns.borrow().get(index);
+            assert_eq!(reference.counts.len(), inventory.counts.len());
+            assert_eq!(reference.counts.get(&b"item2"[..]), inventory.counts.get("item2"));
+            assert_eq!(Inventory::into_owned(reference), *inventory);
+        }
+    }
 }
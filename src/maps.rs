@@ -0,0 +1,265 @@
+//! Columnar support for maps, as lists of key-value entries.
+//!
+//! Maps are stored as `Vecs<(KC, VC)>`, with the entries of each map in the order the map iterates
+//! over them. For `BTreeMap` this is sorted by key, and lookups in the reference type use binary search.
+//! For `HashMap` there is no meaningful order, and lookups scan the entries.
+//!
+//! The binary search compares the reference types of keys, and relies on their order agreeing with the
+//! order of the owned keys. This is the case for the containers in this crate, with the exception that
+//! `Strings` compares `[u8]` rather than `str`, which agree as `str` orders by its bytes.
+use alloc::{vec::Vec, string::String, collections::BTreeMap};
+
+use crate::{Clear, Columnar, Container, Len, Index, IndexAs, Push, Slice, Borrow, Vecs};
+use crate::common::IterOwn;
+
+/// A container for maps, storing the entries of each map as a list.
+///
+/// When `SORTED` is set the entries of each map are sorted by key, and the reference type
+/// can use binary search to find keys.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Maps<KC, VC, const SORTED: bool, BC = Vec<u64>> {
+    /// The entries of each map.
+    pub entries: Vecs<(KC, VC), BC>,
+}
+
+impl<K: Columnar + Ord, V: Columnar> Columnar for BTreeMap<K, V> {
+    #[inline(always)]
+    fn into_owned<'a>(other: crate::Ref<'a, Self>) -> Self {
+        other.iter().map(|(k, v)| (K::into_owned(k), V::into_owned(v))).collect()
+    }
+    type Container = Maps<K::Container, V::Container, true>;
+}
+
+#[cfg(feature = "std")]
+impl<K: Columnar + Eq + core::hash::Hash, V: Columnar, S: core::hash::BuildHasher + Default + 'static> Columnar for std::collections::HashMap<K, V, S> {
+    #[inline(always)]
+    fn copy_from<'a>(&mut self, other: crate::Ref<'a, Self>) {
+        self.clear();
+        for (k, v) in other.iter() {
+            self.insert(K::into_owned(k), V::into_owned(v));
+        }
+    }
+    #[inline(always)]
+    fn into_owned<'a>(other: crate::Ref<'a, Self>) -> Self {
+        other.iter().map(|(k, v)| (K::into_owned(k), V::into_owned(v))).collect()
+    }
+    type Container = Maps<K::Container, V::Container, false>;
+}
+
+/// A reference to the entries of a map in a `Maps` container.
+///
+/// Entries can be accessed by position through `Index`, or by key through `get`.
+#[derive(Copy, Clone, Debug)]
+pub struct MapRef<KB, VB, const SORTED: bool> {
+    /// The entries of the map, as a slice of the entries of all maps.
+    pub entries: Slice<(KB, VB)>,
+}
+
+impl<KB: Index + Copy, VB: Index + Copy, const SORTED: bool> MapRef<KB, VB, SORTED> {
+    /// The number of entries.
+    #[inline(always)] pub fn len(&self) -> usize { self.entries.len() }
+    /// True when there are no entries.
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+    /// The key of the entry at `index`.
+    #[inline(always)] pub fn key(&self, index: usize) -> KB::Ref { self.keys().get(index) }
+    /// The value of the entry at `index`.
+    #[inline(always)] pub fn value(&self, index: usize) -> VB::Ref { self.values().get(index) }
+    /// The keys of the entries, in order.
+    #[inline(always)] pub fn keys(&self) -> Slice<KB> { self.entries.map(|(k, _)| k) }
+    /// The values of the entries, in order.
+    #[inline(always)] pub fn values(&self) -> Slice<VB> { self.entries.map(|(_, v)| v) }
+    /// An iterator over the key-value entries, in order.
+    #[inline(always)] pub fn iter(&self) -> IterOwn<Slice<(KB, VB)>> { self.entries.into_index_iter() }
+}
+
+impl<KB: Index + Copy, VB: Index + Copy> MapRef<KB, VB, true> {
+    /// The value associated with `key`, found by binary search.
+    pub fn get<T>(&self, key: T) -> Option<VB::Ref> where KB::Ref: PartialOrd<T> {
+        let index = crate::search::Search::partition_point(&self.keys(), |k| k < key);
+        if index < self.len() && self.key(index) == key { Some(self.value(index)) } else { None }
+    }
+    /// True when `key` is present.
+    #[inline(always)]
+    pub fn contains_key<T>(&self, key: T) -> bool where KB::Ref: PartialOrd<T> { self.get(key).is_some() }
+}
+
+impl<KB: Index + Copy, VB: Index + Copy> MapRef<KB, VB, false> {
+    /// The value associated with `key`, found by scanning the entries.
+    pub fn get<T>(&self, key: T) -> Option<VB::Ref> where KB::Ref: PartialEq<T> {
+        (0 .. self.len()).find(|i| self.key(*i) == key).map(|i| self.value(i))
+    }
+    /// True when `key` is present.
+    #[inline(always)]
+    pub fn contains_key<T>(&self, key: T) -> bool where KB::Ref: PartialEq<T> { self.get(key).is_some() }
+}
+
+impl<KB, VB, const SORTED: bool> Len for MapRef<KB, VB, SORTED> {
+    #[inline(always)] fn len(&self) -> usize { self.entries.len() }
+}
+impl<KB: Index, VB: Index, const SORTED: bool> Index for MapRef<KB, VB, SORTED> {
+    type Ref = (KB::Ref, VB::Ref);
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref { self.entries.get(index) }
+}
+
+/// Compares entries in order, which for unsorted maps may differ between equal maps.
+impl<KB: Index, VB: Index, const SORTED: bool> PartialEq for MapRef<KB, VB, SORTED> where KB::Ref: PartialEq, VB::Ref: PartialEq {
+    #[inline(always)] fn eq(&self, other: &Self) -> bool { self.entries == other.entries }
+}
+
+impl<KC: Container, VC: Container, BC: crate::common::BorrowIndexAs<u64>, const SORTED: bool> Borrow for Maps<KC, VC, SORTED, BC> {
+    type Ref<'a> = MapRef<KC::Borrowed<'a>, VC::Borrowed<'a>, SORTED> where KC: 'a, VC: 'a;
+    type Borrowed<'a> = Maps<KC::Borrowed<'a>, VC::Borrowed<'a>, SORTED, BC::Borrowed<'a>> where KC: 'a, VC: 'a, BC: 'a;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        Maps { entries: self.entries.borrow() }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a {
+        Maps { entries: Vecs::<(KC, VC), BC>::reborrow(thing.entries) }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a {
+        MapRef { entries: thing.entries.map(|(k, v)| (KC::reborrow(k), VC::reborrow(v))) }
+    }
+}
+
+impl<KC: Container, VC: Container, BC: crate::common::PushIndexAs<u64>, const SORTED: bool> Container for Maps<KC, VC, SORTED, BC> {
+    #[inline(always)]
+    fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
+        self.entries.extend_from_self(other.entries, range);
+    }
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.entries.reserve_for(selves.map(|x| x.entries));
+    }
+}
+
+impl<'a, KC: Container, VC: Container, BC: for<'b> Push<&'b u64>, const SORTED: bool> Push<MapRef<KC::Borrowed<'a>, VC::Borrowed<'a>, SORTED>> for Maps<KC, VC, SORTED, BC> {
+    #[inline(always)]
+    fn push(&mut self, item: MapRef<KC::Borrowed<'a>, VC::Borrowed<'a>, SORTED>) {
+        self.entries.push(item.entries);
+    }
+}
+impl<'a, K, V, KC: Push<&'a K> + Len, VC: Push<&'a V> + Len, BC: for<'b> Push<&'b u64>> Push<&'a BTreeMap<K, V>> for Maps<KC, VC, true, BC> {
+    #[inline(always)]
+    fn push(&mut self, item: &'a BTreeMap<K, V>) {
+        self.entries.push(item.iter());
+    }
+}
+#[cfg(feature = "std")]
+impl<'a, K, V, S, KC: Push<&'a K> + Len, VC: Push<&'a V> + Len, BC: for<'b> Push<&'b u64>> Push<&'a std::collections::HashMap<K, V, S>> for Maps<KC, VC, false, BC> {
+    #[inline(always)]
+    fn push(&mut self, item: &'a std::collections::HashMap<K, V, S>) {
+        self.entries.push(item.iter());
+    }
+}
+
+impl<KC, VC, BC: Len, const SORTED: bool> Len for Maps<KC, VC, SORTED, BC> {
+    #[inline(always)] fn len(&self) -> usize { self.entries.len() }
+}
+impl<KB: Copy, VB: Copy, BC: Len + IndexAs<u64>, const SORTED: bool> Index for Maps<KB, VB, SORTED, BC> {
+    type Ref = MapRef<KB, VB, SORTED>;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        MapRef { entries: self.entries.get(index) }
+    }
+}
+impl<KC: Clear, VC: Clear, BC: Clear, const SORTED: bool> Clear for Maps<KC, VC, SORTED, BC> {
+    #[inline(always)] fn clear(&mut self) { self.entries.clear(); }
+}
+
+impl<'a, KC: crate::AsBytes<'a>, VC: crate::AsBytes<'a>, BC: crate::AsBytes<'a>, const SORTED: bool> crate::AsBytes<'a> for Maps<KC, VC, SORTED, BC> {
+    const SLICE_COUNT: usize = <Vecs<(KC, VC), BC> as crate::AsBytes<'a>>::SLICE_COUNT;
+    #[inline(always)]
+    fn get_byte_slice(&self, index: usize) -> (u64, &'a [u8]) {
+        self.entries.get_byte_slice(index)
+    }
+}
+impl<KC: crate::schema::Schema, VC: crate::schema::Schema, BC: crate::schema::Schema, const SORTED: bool> crate::schema::Schema for Maps<KC, VC, SORTED, BC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Maps", [("entries", <Vecs<(KC, VC), BC>>::schema())])
+    }
+}
+
+impl<'a, KC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>, BC: crate::FromBytes<'a> + IndexAs<u64>, const SORTED: bool> crate::FromBytes<'a> for Maps<KC, VC, SORTED, BC> {
+    const SLICE_COUNT: usize = <Vecs<(KC, VC), BC> as crate::FromBytes<'a>>::SLICE_COUNT;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self { entries: crate::FromBytes::from_bytes(bytes) }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self { entries: crate::FromBytes::from_store(store, offset) }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        <Vecs<(KC, VC), BC>>::element_sizes(sizes)
+    }
+    fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> {
+        <Vecs<(KC, VC), BC>>::validate(slices)
+    }
+    fn validate_deep(&self) -> Result<usize, String> {
+        self.entries.validate_deep()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use alloc::{vec::Vec, string::{String, ToString}, collections::BTreeMap};
+    use crate::{Borrow, Columnar, Container, Index, Len};
+    use crate::bytes::stash::Stash;
+
+    fn maps() -> Vec<BTreeMap<String, u64>> {
+        (0 .. 20u64).map(|i| (0 .. i).map(|j| (format!("key{}", j * 3), i * j)).collect()).collect()
+    }
+
+    #[test]
+    fn btree_lookup() {
+        let maps = maps();
+        let columns = Columnar::as_columns(maps.iter());
+        assert_eq!(columns.len(), maps.len());
+        for (index, map) in maps.iter().enumerate() {
+            let borrowed = columns.borrow().get(index);
+            assert_eq!(borrowed.len(), map.len());
+            for (key, value) in map.iter() {
+                assert_eq!(borrowed.get(key.as_bytes()), Some(value));
+            }
+            assert!(!borrowed.contains_key(&b"key1"[..]));
+            assert!(!borrowed.contains_key(&b"zzz"[..]));
+            assert_eq!(BTreeMap::into_owned(borrowed), *map);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_lookup() {
+        use std::collections::HashMap;
+        let maps: Vec<HashMap<u64, Vec<String>>> = (0 .. 20u64).map(|i| (0 .. i).map(|j| (j * 7, vec![j.to_string(); j as usize])).collect()).collect();
+        let columns = Columnar::as_columns(maps.iter());
+        for (index, map) in maps.iter().enumerate() {
+            let borrowed = columns.borrow().get(index);
+            for (key, value) in map.iter() {
+                let found = borrowed.get(key).unwrap();
+                assert_eq!(found.into_iter().map(|s| core::str::from_utf8(s).unwrap()).collect::<Vec<_>>(), *value);
+            }
+            assert!(!borrowed.contains_key(&1));
+            assert_eq!(borrowed.iter().count(), map.len());
+            assert_eq!(HashMap::into_owned(borrowed), *map);
+        }
+    }
+
+    #[test]
+    fn round_trip_stash() {
+        let maps = maps();
+        let columns = Columnar::as_columns(maps.iter());
+        let mut copy: <BTreeMap<String, u64> as Columnar>::Container = Default::default();
+        copy.extend_from_self(columns.borrow(), 5 .. 10);
+        assert_eq!(copy.borrow().get(2), columns.borrow().get(7));
+
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &columns.borrow()).unwrap();
+        let stash: Stash<<BTreeMap<String, u64> as Columnar>::Container, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+        assert_eq!(stash.borrow(), columns.borrow());
+        assert_eq!(stash.borrow().get(10).get(&b"key27"[..]), Some(&90));
+    }
+}
@@ -0,0 +1,374 @@
+//! Bit-packed containers for integers.
+//!
+//! Integers are grouped into blocks of `BLOCK_LEN` values, and each block is encoded relative to its
+//! least value (its "frame of reference") using just enough bits for the largest difference. A column
+//! of small or clustered numbers then costs only a few bits per value, rather than eight bytes.
+//! Signed integers are first zigzag encoded, so that values of small magnitude have few bits.
+//!
+//! As each block of `BLOCK_LEN` values occupies exactly as many `u64` words as its bit width,
+//! random access remains constant time: a block's words are located by its cumulative bound.
+//!
+//! `Packed<T>` can be used in place of `T::Container` for a struct field, with the derive attribute
+//! `#[columnar(packed)]`. The reference type is `T` itself, rather than `&T`.
+
+use core::marker::PhantomData;
+use alloc::{vec::Vec, string::String};
+
+use crate::{Borrow, Clear, Container, Index, IndexAs, Len, Push};
+
+/// The number of values in each block.
+pub const BLOCK_LEN: usize = 64;
+
+/// Integers that can be encoded as `u64` words, preserving small magnitudes.
+pub trait Packable : Copy + Default + PartialEq + Send + 'static {
+    /// Encodes `self` as a `u64`.
+    fn to_word(self) -> u64;
+    /// Decodes a `u64` produced by `to_word`.
+    fn from_word(word: u64) -> Self;
+}
+
+macro_rules! implement_packable_unsigned {
+    ($($index_type:ty),*) => { $(
+        impl Packable for $index_type {
+            #[inline(always)] fn to_word(self) -> u64 { self as u64 }
+            #[inline(always)] fn from_word(word: u64) -> Self { word as Self }
+        }
+    )* }
+}
+macro_rules! implement_packable_signed {
+    ($($index_type:ty),*) => { $(
+        impl Packable for $index_type {
+            // Zigzag encoding: 0, -1, 1, -2, 2, .. map to 0, 1, 2, 3, 4, ..
+            #[inline(always)] fn to_word(self) -> u64 { (((self as i64) << 1) ^ ((self as i64) >> 63)) as u64 }
+            #[inline(always)] fn from_word(word: u64) -> Self { ((word >> 1) as i64 ^ -((word & 1) as i64)) as Self }
+        }
+    )* }
+}
+
+implement_packable_unsigned!(u8, u16, u32, u64, usize);
+implement_packable_signed!(i8, i16, i32, i64, isize);
+
+/// Converts a reference to an owned value, for use as the `into_owned` of a `Columnar` implementation.
+#[inline(always)] pub fn into_owned<T: Packable>(other: T) -> T { other }
+/// Copies a reference into an owned value, for use as the `copy_from` of a `Columnar` implementation.
+#[inline(always)] pub fn copy_from<T: Packable>(this: &mut T, other: T) { *this = other; }
+
+/// A bit-packed, frame-of-reference container for integers.
+///
+/// Values are in complete blocks of `BLOCK_LEN` values, followed by a `tail` of up to
+/// `BLOCK_LEN - 1` values that are not yet packed. Each complete block has a least value in `mins`,
+/// and its differences from this value are packed at a fixed bit width into `words`. The bit width
+/// is the number of words the block occupies, and is recorded through the cumulative `bounds`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Packed<T, MC = Vec<u64>, BC = Vec<u64>, WC = Vec<u64>, TC = Vec<u64>> {
+    /// For each complete block, the encoded least value.
+    pub mins: MC,
+    /// For each complete block, the number of words in `words` through the end of the block.
+    pub bounds: BC,
+    /// The packed differences of each complete block from its least value.
+    pub words: WC,
+    /// The encoded values after the last complete block.
+    pub tail: TC,
+    marker: PhantomData<T>,
+}
+
+impl<T: Packable, MC: IndexAs<u64> + Len, BC: IndexAs<u64>, WC: IndexAs<u64>, TC: IndexAs<u64> + Len> Index for Packed<T, MC, BC, WC, TC> {
+    type Ref = T;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        let block = index / BLOCK_LEN;
+        if block < self.mins.len() {
+            let lower = if block == 0 { 0 } else { self.bounds.index_as(block - 1) };
+            let width = self.bounds.index_as(block) - lower;
+            let delta = if width == 0 { 0 } else {
+                let bit = (index % BLOCK_LEN) as u64 * width;
+                let word = (lower + bit / 64) as usize;
+                let shift = bit % 64;
+                let mut delta = self.words.index_as(word) >> shift;
+                if shift + width > 64 { delta |= self.words.index_as(word + 1) << (64 - shift); }
+                if width < 64 { delta & ((1 << width) - 1) } else { delta }
+            };
+            T::from_word(self.mins.index_as(block).wrapping_add(delta))
+        }
+        else {
+            T::from_word(self.tail.index_as(index - self.mins.len() * BLOCK_LEN))
+        }
+    }
+}
+impl<T: Packable> Index for &Packed<T> {
+    type Ref = T;
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref { (*self).get(index) }
+}
+
+impl<T, MC: Len, BC, WC, TC: Len> Len for Packed<T, MC, BC, WC, TC> {
+    #[inline(always)] fn len(&self) -> usize { self.mins.len() * BLOCK_LEN + self.tail.len() }
+}
+
+impl<T: Packable> Packed<T> {
+    /// Packs the values of `tail`, which must be a complete block.
+    fn seal(&mut self) {
+        debug_assert_eq!(self.tail.len(), BLOCK_LEN);
+        let min = *self.tail.iter().min().unwrap();
+        let max = *self.tail.iter().max().unwrap();
+        let width = (64 - (max - min).leading_zeros()) as usize;
+        let lower = self.words.len();
+        self.words.resize(lower + width, 0);
+        if width > 0 {
+            for (index, value) in self.tail.iter().enumerate() {
+                let delta = value - min;
+                let bit = index * width;
+                let word = lower + bit / 64;
+                let shift = bit % 64;
+                self.words[word] |= delta << shift;
+                if shift + width > 64 { self.words[word + 1] |= delta >> (64 - shift); }
+            }
+        }
+        self.mins.push(min);
+        self.bounds.push(self.words.len() as u64);
+        self.tail.clear();
+    }
+}
+
+impl<T: Packable> Push<T> for Packed<T> {
+    #[inline]
+    fn push(&mut self, item: T) {
+        self.tail.push(item.to_word());
+        if self.tail.len() == BLOCK_LEN { self.seal(); }
+    }
+}
+impl<'a, T: Packable> Push<&'a T> for Packed<T> {
+    #[inline(always)] fn push(&mut self, item: &'a T) { self.push(*item) }
+}
+
+impl<T: Packable> Borrow for Packed<T> {
+    type Ref<'a> = T;
+    type Borrowed<'a> = Packed<T, &'a [u64], &'a [u64], &'a [u64], &'a [u64]>;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        Packed {
+            mins: &self.mins[..],
+            bounds: &self.bounds[..],
+            words: &self.words[..],
+            tail: &self.tail[..],
+            marker: PhantomData,
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a { thing }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a { thing }
+}
+
+impl<T: Packable> Container for Packed<T> {
+    fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
+        let mut index = range.start;
+        // Complete blocks can be copied, if both they and `self` are block aligned.
+        if self.tail.is_empty() && index % BLOCK_LEN == 0 {
+            let first = index / BLOCK_LEN;
+            let last = core::cmp::max(first, core::cmp::min(range.end / BLOCK_LEN, other.mins.len()));
+            if first < last {
+                let lower = if first == 0 { 0 } else { other.bounds[first - 1] };
+                let upper = other.bounds[last - 1];
+                let shift = self.words.len() as u64;
+                self.words.extend_from_slice(&other.words[lower as usize .. upper as usize]);
+                self.mins.extend_from_slice(&other.mins[first .. last]);
+                for bound in &other.bounds[first .. last] {
+                    self.bounds.push(bound - lower + shift);
+                }
+                index = last * BLOCK_LEN;
+            }
+        }
+        for index in index .. range.end {
+            self.push(other.get(index));
+        }
+    }
+
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.mins.reserve(selves.clone().map(|x| x.mins.len()).sum());
+        self.bounds.reserve(selves.clone().map(|x| x.bounds.len()).sum());
+        self.words.reserve(selves.map(|x| x.words.len()).sum());
+    }
+}
+
+impl<T> Clear for Packed<T> {
+    #[inline(always)]
+    fn clear(&mut self) {
+        self.mins.clear();
+        self.bounds.clear();
+        self.words.clear();
+        self.tail.clear();
+    }
+}
+
+impl<'a, T, MC: crate::AsBytes<'a>, BC: crate::AsBytes<'a>, WC: crate::AsBytes<'a>, TC: crate::AsBytes<'a>> crate::AsBytes<'a> for Packed<T, MC, BC, WC, TC> {
+    const SLICE_COUNT: usize = MC::SLICE_COUNT + BC::SLICE_COUNT + WC::SLICE_COUNT + TC::SLICE_COUNT;
+    #[inline]
+    fn get_byte_slice(&self, mut index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        if index < MC::SLICE_COUNT { return self.mins.get_byte_slice(index); }
+        index -= MC::SLICE_COUNT;
+        if index < BC::SLICE_COUNT { return self.bounds.get_byte_slice(index); }
+        index -= BC::SLICE_COUNT;
+        if index < WC::SLICE_COUNT { return self.words.get_byte_slice(index); }
+        index -= WC::SLICE_COUNT;
+        self.tail.get_byte_slice(index)
+    }
+}
+
+impl<T, MC: crate::schema::Schema, BC: crate::schema::Schema, WC: crate::schema::Schema, TC: crate::schema::Schema> crate::schema::Schema for Packed<T, MC, BC, WC, TC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Packed", [("mins", MC::schema()), ("bounds", BC::schema()), ("words", WC::schema()), ("tail", TC::schema())])
+    }
+}
+
+impl<'a, T: Packable> crate::FromBytes<'a> for Packed<T, &'a [u64], &'a [u64], &'a [u64], &'a [u64]> {
+    const SLICE_COUNT: usize = 4;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            mins: crate::FromBytes::from_bytes(bytes),
+            bounds: crate::FromBytes::from_bytes(bytes),
+            words: crate::FromBytes::from_bytes(bytes),
+            tail: crate::FromBytes::from_bytes(bytes),
+            marker: PhantomData,
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self {
+            mins: crate::FromBytes::from_store(store, offset),
+            bounds: crate::FromBytes::from_store(store, offset),
+            words: crate::FromBytes::from_store(store, offset),
+            tail: crate::FromBytes::from_store(store, offset),
+            marker: PhantomData,
+        }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        sizes.extend_from_slice(&[8, 8, 8, 8]);
+        Ok(())
+    }
+    /// Validates element sizes, and that the blocks are consistent with `words`.
+    ///
+    /// The checks ensure that indexing will not panic.
+    fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> {
+        if slices.len() < Self::SLICE_COUNT {
+            return Err(format!("Packed: expected {} slices but got {}", Self::SLICE_COUNT, slices.len()));
+        }
+        // Each slice holds `u64` elements, and must be whole words.
+        for (i, (_, tail)) in slices[.. Self::SLICE_COUNT].iter().enumerate() {
+            if (8 - *tail as usize) % 8 != 0 {
+                return Err(format!("Packed: slice {} is not a multiple of element size 8", i));
+            }
+        }
+        validate_blocks(slices[0].0, slices[1].0, slices[2].0, slices[3].0)
+    }
+    fn validate_deep(&self) -> Result<usize, String> {
+        validate_blocks(self.mins, self.bounds, self.words, self.tail)?;
+        Ok(Len::len(self))
+    }
+}
+
+/// Checks that each block has bounds of a width of at most 64 into `words`, and that `tail` is not a complete block.
+fn validate_blocks(mins: &[u64], bounds: &[u64], words: &[u64], tail: &[u64]) -> Result<(), String> {
+    if mins.len() != bounds.len() {
+        return Err(format!("Packed: {} blocks have {} bounds", mins.len(), bounds.len()));
+    }
+    if tail.len() >= BLOCK_LEN {
+        return Err(format!("Packed: tail has {} values, but should be fewer than {}", tail.len(), BLOCK_LEN));
+    }
+    let mut lower = 0;
+    for (block, &upper) in bounds.iter().enumerate() {
+        if upper < lower || upper - lower > 64 {
+            return Err(format!("Packed: block {} has bounds {} .. {}, not a width of at most 64", block, lower, upper));
+        }
+        lower = upper;
+    }
+    if lower != words.len() as u64 {
+        return Err(format!("Packed: blocks have {} words, but found {}", lower, words.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use crate::{Borrow, Container, Index, Len, Push, Clear};
+    use crate::bytes::stash::Stash;
+    use super::{Packed, Packable, BLOCK_LEN};
+
+    #[test]
+    fn zigzag() {
+        for value in [0i64, 1, -1, 2, -2, i64::MAX, i64::MIN] {
+            assert_eq!(i64::from_word(value.to_word()), value);
+        }
+        assert_eq!((-1i32).to_word(), 1);
+        assert_eq!(2i8.to_word(), 4);
+        assert_eq!(i8::from_word(i8::MIN.to_word()), i8::MIN);
+    }
+
+    #[test]
+    fn push_and_index() {
+        // Constant, small, clustered, and full-width blocks, and a partial block.
+        let values: Vec<u64> = (0 .. 5 * BLOCK_LEN as u64 + 10).map(|i| match i / BLOCK_LEN as u64 {
+            0 => 7,
+            1 => i % 5,
+            2 => 1_000_000 + (i * 7919) % 1000,
+            3 => i.wrapping_mul(0x9E3779B97F4A7C15),
+            _ => u64::MAX - i,
+        }).collect();
+        let mut packed: Packed<u64> = Default::default();
+        for value in values.iter() { packed.push(value); }
+        assert_eq!(packed.len(), values.len());
+        assert_eq!(packed.bounds, [0, 3, 13, 77, 83]);
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(packed.borrow().get(index), *value);
+            assert_eq!(packed.get(index), *value);
+        }
+
+        let signed: Vec<i32> = (0 .. 1000).map(|i| if i % 2 == 0 { i } else { -i }).collect();
+        let mut packed: Packed<i32> = Default::default();
+        for value in signed.iter() { packed.push(value); }
+        for (index, value) in signed.iter().enumerate() {
+            assert_eq!(packed.borrow().get(index), *value);
+        }
+        packed.clear();
+        assert_eq!(packed.len(), 0);
+    }
+
+    #[test]
+    fn extend_from_self() {
+        let mut packed: Packed<u32> = Default::default();
+        for value in 0 .. 1000u32 { packed.push(value * value % 1013); }
+
+        for range in [0 .. 1000, 64 .. 640, 100 .. 900, 0 .. 10, 960 .. 1000] {
+            for prefix in [0, 64, 3] {
+                let mut target: Packed<u32> = Default::default();
+                for value in 0 .. prefix { target.push(value); }
+                target.extend_from_self(packed.borrow(), range.clone());
+                assert_eq!(target.len(), prefix as usize + range.len());
+                for (offset, index) in range.clone().enumerate() {
+                    assert_eq!(target.borrow().get(prefix as usize + offset), packed.borrow().get(index));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_stash() {
+        let mut packed: Packed<i64> = Default::default();
+        for value in 0 .. 1000i64 { packed.push(value / 3 - 100); }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &packed.borrow()).unwrap();
+        let stash: Stash<Packed<i64>, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+        assert_eq!(stash.borrow(), packed.borrow());
+
+        let mut bad = packed.clone();
+        let last = bad.bounds.len() - 1;
+        bad.bounds[last] += 1;
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &bad.borrow()).unwrap();
+        assert!(Stash::<Packed<i64>, Vec<u8>>::try_from_bytes_deep(bytes).is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Writing columnar containers as Parquet files.
+//!
+//! Parquet stores each leaf field of a nested type as a column of values, with a *definition level*
+//! for each entry recording how many of its optional and repeated ancestors are present, and a
+//! *repetition level* recording the repeated ancestor at which it starts a new list. [`Shred`] computes
+//! these from the containers directly: the bounds of `Vecs` locate the lists and where each starts,
+//! and the `RankSelect` indexes of `Options` record which values are present and where they are
+//! among the `somes`. Containers of a type map to Parquet as
+//!
+//! * integers and floats as primitive columns, with integer widths and signedness as annotations,
+//! * `bool` as a boolean column, `String` as a UTF-8 byte array column, and `()` as a boolean column of `true`,
+//! * `Vec<T>` as a three-level `LIST` group, and `Option<T>` as an optional `T`,
+//! * tuples and derived structs as groups, with fields named by their name or position,
+//! * derived enums as groups with an optional field for each variant, of which one is present,
+//! * derived enums whose variants have no fields as `ENUM` byte array columns of variant names.
+//!
+//! Parquet cannot mark a field as optional twice, so `Option<Option<T>>` is an optional group
+//! with a single optional field named `element`.
+//!
+//! The derive implements [`Shred`] for struct and enum containers, and [`write`] writes any
+//! container as a file of one row per element, with a schema that follows from the type.
+
+use alloc::{vec::Vec, string::String, sync::Arc};
+
+use ::parquet::basic::{LogicalType, Type as PhysicalType};
+use ::parquet::column::writer::ColumnWriter;
+use ::parquet::data_type::ByteArray;
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::writer::SerializedFileWriter;
+use ::parquet::schema::types::Type;
+
+pub use ::parquet::basic::Repetition;
+pub use ::parquet::schema::types::TypePtr;
+
+use crate::{Discriminant, Index, IndexAs, Len, Options, Strings, Vecs};
+use crate::sums::discriminant::{Tag, Tags};
+use crate::primitive::{Bools, Empties};
+
+/// A container whose elements can be written as Parquet columns.
+pub trait Shred {
+    /// The Parquet type of the elements, as a field named `name` with `repetition`.
+    fn parquet_type(name: &str, repetition: Repetition) -> TypePtr;
+    /// Appends a column for each leaf of the type to `columns`, with an entry for each of `slots`.
+    ///
+    /// Present elements have definition level `definition`, and their lists repetition level `repetition`.
+    /// Errors if an element cannot be represented, for example a discriminant that is not a variant.
+    fn shred(&self, slots: &[Slot], definition: i16, repetition: i16, columns: &mut Vec<Column>) -> Result<(), String>;
+}
+
+/// An entry to write in the columns of a container.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Slot {
+    /// The element at an index, and the repetition level of its first entry.
+    Value(usize, i16),
+    /// No element, as an ancestor is absent or an empty list, by its definition and repetition levels.
+    Absent(i16, i16),
+}
+
+/// The values of a leaf column, by their Parquet physical type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Values {
+    Boolean(Vec<bool>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    ByteArray(Vec<ByteArray>),
+}
+
+/// A leaf column, with values for present entries and levels for all entries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    /// The values of entries whose definition level is the largest for the column.
+    pub values: Values,
+    /// The definition level of each entry.
+    pub definitions: Vec<i16>,
+    /// The repetition level of each entry.
+    pub repetitions: Vec<i16>,
+}
+
+impl Column {
+    /// A column with the values `value` produces for `slots` present at `definition`, wrapped by `wrap`.
+    pub fn new<T>(slots: &[Slot], definition: i16, mut value: impl FnMut(usize) -> T, wrap: impl FnOnce(Vec<T>) -> Values) -> Self {
+        let mut values = Vec::with_capacity(slots.len());
+        let mut definitions = Vec::with_capacity(slots.len());
+        let mut repetitions = Vec::with_capacity(slots.len());
+        for slot in slots.iter() {
+            match *slot {
+                Slot::Value(index, repetition) => {
+                    values.push(value(index));
+                    definitions.push(definition);
+                    repetitions.push(repetition);
+                },
+                Slot::Absent(definition, repetition) => {
+                    definitions.push(definition);
+                    repetitions.push(repetition);
+                },
+            }
+        }
+        Column { values: wrap(values), definitions, repetitions }
+    }
+}
+
+/// A primitive type named `name` with `repetition`, and an optional logical type.
+pub fn primitive(name: &str, repetition: Repetition, physical: PhysicalType, logical: Option<LogicalType>) -> TypePtr {
+    let built = Type::primitive_type_builder(name, physical).with_repetition(repetition).with_logical_type(logical).build();
+    Arc::new(built.expect("parquet: invalid primitive type"))
+}
+
+/// A group type named `name` with `repetition`, an optional logical type, and `fields`.
+pub fn group(name: &str, repetition: Repetition, logical: Option<LogicalType>, fields: impl IntoIterator<Item = TypePtr>) -> TypePtr {
+    let built = Type::group_type_builder(name).with_repetition(repetition).with_logical_type(logical).with_fields(fields.into_iter().collect()).build();
+    Arc::new(built.expect("parquet: invalid group type"))
+}
+
+macro_rules! implement_parquet {
+    ($($index_type:ty, $physical:ident, $variant:ident, $as_type:ty, $logical:expr;)*) => { $(
+        impl Shred for Vec<$index_type> {
+            fn parquet_type(name: &str, repetition: Repetition) -> TypePtr { primitive(name, repetition, PhysicalType::$physical, $logical) }
+            fn shred(&self, slots: &[Slot], definition: i16, _repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+                columns.push(Column::new(slots, definition, |index| self[index] as $as_type, Values::$variant));
+                Ok(())
+            }
+        }
+        impl<'a> Shred for &'a [$index_type] {
+            fn parquet_type(name: &str, repetition: Repetition) -> TypePtr { primitive(name, repetition, PhysicalType::$physical, $logical) }
+            fn shred(&self, slots: &[Slot], definition: i16, _repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+                columns.push(Column::new(slots, definition, |index| self[index] as $as_type, Values::$variant));
+                Ok(())
+            }
+        }
+    )* }
+}
+
+implement_parquet!(
+    u8, INT32, Int32, i32, Some(LogicalType::Integer { bit_width: 8, is_signed: false });
+    u16, INT32, Int32, i32, Some(LogicalType::Integer { bit_width: 16, is_signed: false });
+    u32, INT32, Int32, i32, Some(LogicalType::Integer { bit_width: 32, is_signed: false });
+    u64, INT64, Int64, i64, Some(LogicalType::Integer { bit_width: 64, is_signed: false });
+    i8, INT32, Int32, i32, Some(LogicalType::Integer { bit_width: 8, is_signed: true });
+    i16, INT32, Int32, i32, Some(LogicalType::Integer { bit_width: 16, is_signed: true });
+    i32, INT32, Int32, i32, None;
+    i64, INT64, Int64, i64, None;
+    f32, FLOAT, Float, f32, None;
+    f64, DOUBLE, Double, f64, None;
+);
+
+impl<VC: Len + IndexAs<u64>, TC: IndexAs<u64>> Shred for Bools<VC, TC> {
+    fn parquet_type(name: &str, repetition: Repetition) -> TypePtr { primitive(name, repetition, PhysicalType::BOOLEAN, None) }
+    fn shred(&self, slots: &[Slot], definition: i16, _repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+        columns.push(Column::new(slots, definition, |index| self.get(index), Values::Boolean));
+        Ok(())
+    }
+}
+
+impl<CC> Shred for Empties<CC> {
+    fn parquet_type(name: &str, repetition: Repetition) -> TypePtr { primitive(name, repetition, PhysicalType::BOOLEAN, None) }
+    fn shred(&self, slots: &[Slot], definition: i16, _repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+        columns.push(Column::new(slots, definition, |_| true, Values::Boolean));
+        Ok(())
+    }
+}
+
+impl<BC: IndexAs<u64>, VC: core::ops::Deref<Target = [u8]>> Shred for Strings<BC, VC> {
+    fn parquet_type(name: &str, repetition: Repetition) -> TypePtr { primitive(name, repetition, PhysicalType::BYTE_ARRAY, Some(LogicalType::String)) }
+    fn shred(&self, slots: &[Slot], definition: i16, _repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+        columns.push(Column::new(slots, definition, |index| {
+            let lower = if index == 0 { 0 } else { self.bounds.index_as(index - 1) };
+            let upper = self.bounds.index_as(index);
+            ByteArray::from(self.values[lower as usize .. upper as usize].to_vec())
+        }, Values::ByteArray));
+        Ok(())
+    }
+}
+
+impl<TC: Shred, BC: IndexAs<u64>> Shred for Vecs<TC, BC> {
+    fn parquet_type(name: &str, repetition: Repetition) -> TypePtr {
+        let list = group("list", Repetition::REPEATED, None, [TC::parquet_type("element", Repetition::REQUIRED)]);
+        group(name, repetition, Some(LogicalType::List), [list])
+    }
+    fn shred(&self, slots: &[Slot], definition: i16, repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+        // Each list becomes its values, the first continuing the record of the list and the rest repeating at
+        // the level of the list, or an absent entry for an empty list.
+        let mut values = Vec::with_capacity(slots.len());
+        for slot in slots.iter() {
+            match *slot {
+                Slot::Value(index, first) => {
+                    let lower = if index == 0 { 0 } else { self.bounds.index_as(index - 1) } as usize;
+                    let upper = self.bounds.index_as(index) as usize;
+                    if lower == upper { values.push(Slot::Absent(definition, first)); }
+                    else {
+                        values.push(Slot::Value(lower, first));
+                        values.extend((lower + 1 .. upper).map(|index| Slot::Value(index, repetition + 1)));
+                    }
+                },
+                absent => values.push(absent),
+            }
+        }
+        self.values.shred(&values, definition + 1, repetition + 1, columns)
+    }
+}
+
+impl<TC: Shred, CC: Len + IndexAs<u64>, VC: Len + IndexAs<u64>, WC: IndexAs<u64>> Shred for Options<TC, CC, VC, WC> {
+    fn parquet_type(name: &str, repetition: Repetition) -> TypePtr {
+        if repetition == Repetition::REQUIRED { TC::parquet_type(name, Repetition::OPTIONAL) }
+        else { group(name, repetition, None, [TC::parquet_type("element", Repetition::OPTIONAL)]) }
+    }
+    fn shred(&self, slots: &[Slot], definition: i16, repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+        let somes = slots.iter().map(|slot| match *slot {
+            Slot::Value(index, first) if self.indexes.get(index) => Slot::Value(self.indexes.rank(index), first),
+            Slot::Value(_, first) => Slot::Absent(definition, first),
+            absent => absent,
+        }).collect::<Vec<_>>();
+        self.somes.shred(&somes, definition + 1, repetition, columns)
+    }
+}
+
+/// The slots of the elements of `variant` among its values, with other variants absent at `definition`.
+pub fn variant_slots<T, CVar, COff>(indexes: &Discriminant<CVar, COff>, slots: &[Slot], variant: T, definition: i16) -> Vec<Slot>
+where
+    T: Tag,
+    CVar: Tags<Tag = T>,
+    COff: Len + IndexAs<u64>,
+{
+    slots.iter().map(|slot| match *slot {
+        Slot::Value(index, first) => {
+            let (present, offset) = indexes.get(index);
+            if present == variant { Slot::Value(offset as usize, first) } else { Slot::Absent(definition, first) }
+        },
+        absent => absent,
+    }).collect()
+}
+
+/// The type of a column of the names of variants without fields.
+pub fn tags_type(name: &str, repetition: Repetition) -> TypePtr {
+    primitive(name, repetition, PhysicalType::BYTE_ARRAY, Some(LogicalType::Enum))
+}
+
+/// A column of the names of the variants of elements at `slots`, from their discriminants in `variant`.
+///
+/// The name of each discriminant is the entry of `names` at its position in `tags`, and other discriminants are errors.
+pub fn tags<T: Tag, CVar: IndexAs<T>>(variant: &CVar, tags: &[T], names: &[&str], slots: &[Slot], definition: i16) -> Result<Column, String> {
+    let mut positions = Vec::with_capacity(slots.len());
+    for slot in slots.iter() {
+        if let Slot::Value(index, _) = *slot {
+            let variant = variant.index_as(index);
+            positions.push(tags.iter().position(|tag| *tag == variant).ok_or_else(|| format!("parquet: discriminant {:?} is not a variant", variant))?);
+        }
+    }
+    let mut positions = positions.into_iter();
+    Ok(Column::new(slots, definition, |_| ByteArray::from(names[positions.next().unwrap()]), Values::ByteArray))
+}
+
+macro_rules! tuple_parquet {
+    ($($name:ident,$index:tt)+) => (
+        impl<$($name: Shred),*> Shred for ($($name,)*) {
+            fn parquet_type(name: &str, repetition: Repetition) -> TypePtr {
+                group(name, repetition, None, [$($name::parquet_type(stringify!($index), Repetition::REQUIRED)),*])
+            }
+            fn shred(&self, slots: &[Slot], definition: i16, repetition: i16, columns: &mut Vec<Column>) -> Result<(), String> {
+                $(self.$index.shred(slots, definition, repetition, columns)?;)*
+                Ok(())
+            }
+        }
+    )
+}
+
+tuple_parquet!(A,0);
+tuple_parquet!(A,0 B,1);
+tuple_parquet!(A,0 B,1 C,2);
+tuple_parquet!(A,0 B,1 C,2 D,3);
+tuple_parquet!(A,0 B,1 C,2 D,3 E,4);
+tuple_parquet!(A,0 B,1 C,2 D,3 E,4 F,5);
+tuple_parquet!(A,0 B,1 C,2 D,3 E,4 F,5 G,6);
+tuple_parquet!(A,0 B,1 C,2 D,3 E,4 F,5 G,6 H,7);
+tuple_parquet!(A,0 B,1 C,2 D,3 E,4 F,5 G,6 H,7 I,8);
+
+/// The schema of a file of elements of `C`, as a message named `schema`.
+///
+/// The fields of structs, tuples, and enums are the fields of the message, and other types its single field `value`,
+/// including optional structs, tuples, and enums whose group records whether they are present.
+pub fn schema<C: Shred>() -> TypePtr {
+    let root = C::parquet_type("value", Repetition::REQUIRED);
+    let info = root.get_basic_info();
+    let fields = if root.is_group() && info.logical_type().is_none() && info.repetition() == Repetition::REQUIRED { root.get_fields().to_vec() } else { alloc::vec![root] };
+    let built = Type::group_type_builder("schema").with_fields(fields).build();
+    Arc::new(built.expect("parquet: invalid message type"))
+}
+
+/// Writes the elements of `container` as a Parquet file, with one row group of a row for each element.
+pub fn write<W: std::io::Write + Send, C: Shred + Len>(writer: W, container: &C) -> Result<(), String> {
+    let error = |error: ::parquet::errors::ParquetError| format!("parquet: {}", error);
+
+    let slots = (0 .. container.len()).map(|index| Slot::Value(index, 0)).collect::<Vec<_>>();
+    let mut columns = Vec::new();
+    container.shred(&slots, 0, 0, &mut columns)?;
+
+    let mut file = SerializedFileWriter::new(writer, schema::<C>(), Arc::new(WriterProperties::default())).map_err(error)?;
+    let mut row_group = file.next_row_group().map_err(error)?;
+    for column in columns {
+        let mut writer = row_group.next_column().map_err(error)?.ok_or("parquet: more columns than the schema has leaves")?;
+        let (definitions, repetitions) = (Some(&column.definitions[..]), Some(&column.repetitions[..]));
+        let written = match (writer.untyped(), &column.values) {
+            (ColumnWriter::BoolColumnWriter(writer), Values::Boolean(values)) => writer.write_batch(values, definitions, repetitions),
+            (ColumnWriter::Int32ColumnWriter(writer), Values::Int32(values)) => writer.write_batch(values, definitions, repetitions),
+            (ColumnWriter::Int64ColumnWriter(writer), Values::Int64(values)) => writer.write_batch(values, definitions, repetitions),
+            (ColumnWriter::FloatColumnWriter(writer), Values::Float(values)) => writer.write_batch(values, definitions, repetitions),
+            (ColumnWriter::DoubleColumnWriter(writer), Values::Double(values)) => writer.write_batch(values, definitions, repetitions),
+            (ColumnWriter::ByteArrayColumnWriter(writer), Values::ByteArray(values)) => writer.write_batch(values, definitions, repetitions),
+            _ => return Err("parquet: column values do not match the physical type of the schema".into()),
+        };
+        written.map_err(error)?;
+        writer.close().map_err(error)?;
+    }
+    if row_group.next_column().map_err(error)?.is_some() {
+        return Err("parquet: fewer columns than the schema has leaves".into());
+    }
+    row_group.close().map_err(error)?;
+    file.close().map_err(error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use alloc::{vec::Vec, string::{String, ToString}};
+    use crate::{Borrow, ContainerOf};
+    use crate::common::Push;
+    use super::{Shred, Slot, Values};
+
+    #[test]
+    fn levels() {
+        let mut column: ContainerOf<Vec<Option<Vec<u32>>>> = Default::default();
+        column.push(&vec![Some(vec![1, 2]), None]);
+        column.push(&Vec::<Option<Vec<u32>>>::new());
+        column.push(&vec![Some(vec![]), Some(vec![3])]);
+
+        let mut columns = Vec::new();
+        column.borrow().shred(&[Slot::Value(0, 0), Slot::Value(1, 0), Slot::Value(2, 0)], 0, 0, &mut columns).unwrap();
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].values, Values::Int32(vec![1, 2, 3]));
+        assert_eq!(columns[0].definitions, [3, 3, 1, 0, 2, 3]);
+        assert_eq!(columns[0].repetitions, [0, 2, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn write_and_read() {
+        use ::parquet::file::reader::{FileReader, SerializedFileReader};
+
+        type T = (u64, Option<String>, Vec<Option<i32>>, bool);
+        let mut column: ContainerOf<T> = Default::default();
+        for i in 0 .. 100u64 {
+            column.push(&(
+                i,
+                if i % 3 == 0 { None } else { Some(format!("{}", i)) },
+                (0 .. i % 3).map(|j| if j % 2 == 0 { Some(j as i32) } else { None }).collect::<Vec<_>>(),
+                i % 2 == 0,
+            ));
+        }
+
+        let path = std::env::temp_dir().join(format!("columnar-parquet-{}.parquet", std::process::id()));
+        super::write(std::fs::File::create(&path).unwrap(), &column.borrow()).unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 100);
+        let rows = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().to_string()).collect::<Vec<String>>();
+        assert_eq!(rows[0], "{0: 0, 1: null, 2: [], 3: true}");
+        assert_eq!(rows[2], "{0: 2, 1: \"2\", 2: [0, null], 3: true}");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn optional_root() {
+        use ::parquet::file::reader::{FileReader, SerializedFileReader};
+
+        // An optional tuple keeps its group, whose absence its definition levels record.
+        type T = Option<(u32, String)>;
+        let schema = super::schema::<ContainerOf<T>>();
+        assert_eq!(schema.get_fields().len(), 1);
+        assert_eq!(schema.get_fields()[0].get_basic_info().repetition(), super::Repetition::OPTIONAL);
+
+        let mut column: ContainerOf<T> = Default::default();
+        column.push(&Some((1, "a".to_string())));
+        column.push(&None::<(u32, String)>);
+        column.push(&Some((3, "c".to_string())));
+
+        let path = std::env::temp_dir().join(format!("columnar-parquet-optional-{}.parquet", std::process::id()));
+        super::write(std::fs::File::create(&path).unwrap(), &column.borrow()).unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let rows = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().to_string()).collect::<Vec<String>>();
+        assert_eq!(rows, ["{value: {0: 1, 1: \"a\"}}", "{value: null}", "{value: {0: 3, 1: \"c\"}}"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -41,6 +41,13 @@ macro_rules! implement_columnable {
                 sizes.push(core::mem::size_of::<$index_type>());
                 Ok(())
             }
+            fn validate_deep(&self) -> Result<usize, String> { Ok(<[$index_type]>::len(self)) }
+        }
+        impl crate::schema::Schema for Vec<$index_type> {
+            fn schema() -> crate::schema::Node { crate::schema::Node::slice::<$index_type>() }
+        }
+        impl<'a> crate::schema::Schema for &'a [$index_type] {
+            fn schema() -> crate::schema::Node { crate::schema::Node::slice::<$index_type>() }
         }
         impl<'a, const N: usize> crate::AsBytes<'a> for &'a [[$index_type; N]] {
             const SLICE_COUNT: usize = 1;
@@ -70,6 +77,13 @@ macro_rules! implement_columnable {
                 sizes.push(core::mem::size_of::<$index_type>() * N);
                 Ok(())
             }
+            fn validate_deep(&self) -> Result<usize, String> { Ok(<[[$index_type; N]]>::len(self)) }
+        }
+        impl<const N: usize> crate::schema::Schema for Vec<[$index_type; N]> {
+            fn schema() -> crate::schema::Node { crate::schema::Node::slice::<[$index_type; N]>() }
+        }
+        impl<'a, const N: usize> crate::schema::Schema for &'a [[$index_type; N]] {
+            fn schema() -> crate::schema::Node { crate::schema::Node::slice::<[$index_type; N]>() }
         }
     )* }
 }
@@ -84,6 +98,7 @@ pub use sizes::{Usizes, Isizes};
 /// Columnar stores for `usize` and `isize`, stored as 64 bits.
 mod sizes {
 
+    use alloc::string::String;
     use crate::*;
     use crate::common::{BorrowIndexAs, PushIndexAs};
 
@@ -114,6 +129,10 @@ mod sizes {
         fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
             self.values.extend_from_self(other.values, range)
         }
+        #[inline(always)]
+        fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.values.gather(other.values, indices)
+        }
 
         fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
             self.values.reserve_for(selves.map(|x| x.values))
@@ -151,7 +170,10 @@ mod sizes {
         }
     }
 
-    impl<'a, CV: crate::FromBytes<'a>> crate::FromBytes<'a> for crate::primitive::Usizes<CV> {
+    impl<CV: crate::schema::Schema> crate::schema::Schema for crate::primitive::Usizes<CV> {
+        fn schema() -> crate::schema::Node { crate::schema::Node::container("Usizes", [("values", CV::schema())]) }
+    }
+    impl<'a, CV: crate::FromBytes<'a> + IndexAs<u64>> crate::FromBytes<'a> for crate::primitive::Usizes<CV> {
         const SLICE_COUNT: usize = CV::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -161,6 +183,15 @@ mod sizes {
         fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
             Self { values: CV::from_store(store, offset) }
         }
+        fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { CV::element_sizes(sizes) }
+        fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { CV::validate(slices) }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let len = self.values.validate_deep()?;
+            if let Some(index) = (0 .. len).find(|i| usize::try_from(self.values.index_as(*i)).is_err()) {
+                return Err(format!("Usizes: value at {} does not fit in `usize`", index));
+            }
+            Ok(len)
+        }
     }
 
 
@@ -191,6 +222,10 @@ mod sizes {
         fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
             self.values.extend_from_self(other.values, range)
         }
+        #[inline(always)]
+        fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.values.gather(other.values, indices)
+        }
 
         fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
             self.values.reserve_for(selves.map(|x| x.values))
@@ -228,7 +263,10 @@ mod sizes {
         }
     }
 
-    impl<'a, CV: crate::FromBytes<'a>> crate::FromBytes<'a> for crate::primitive::Isizes<CV> {
+    impl<CV: crate::schema::Schema> crate::schema::Schema for crate::primitive::Isizes<CV> {
+        fn schema() -> crate::schema::Node { crate::schema::Node::container("Isizes", [("values", CV::schema())]) }
+    }
+    impl<'a, CV: crate::FromBytes<'a> + IndexAs<i64>> crate::FromBytes<'a> for crate::primitive::Isizes<CV> {
         const SLICE_COUNT: usize = CV::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -238,6 +276,15 @@ mod sizes {
         fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
             Self { values: CV::from_store(store, offset) }
         }
+        fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { CV::element_sizes(sizes) }
+        fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { CV::validate(slices) }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let len = self.values.validate_deep()?;
+            if let Some(index) = (0 .. len).find(|i| isize::try_from(self.values.index_as(*i)).is_err()) {
+                return Err(format!("Isizes: value at {} does not fit in `isize`", index));
+            }
+            Ok(len)
+        }
     }
 }
 
@@ -245,6 +292,7 @@ pub use chars::{Chars};
 /// Columnar store for `char`, stored as a `u32`.
 mod chars {
 
+    use alloc::string::String;
     use crate::*;
     use crate::common::{BorrowIndexAs, PushIndexAs};
 
@@ -277,6 +325,10 @@ mod chars {
         fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
             self.values.extend_from_self(other.values, range)
         }
+        #[inline(always)]
+        fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.values.gather(other.values, indices)
+        }
 
         fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
             self.values.reserve_for(selves.map(|x| x.values))
@@ -310,7 +362,10 @@ mod chars {
         }
     }
 
-    impl<'a, CV: crate::FromBytes<'a>> crate::FromBytes<'a> for Chars<CV> {
+    impl<CV: crate::schema::Schema> crate::schema::Schema for Chars<CV> {
+        fn schema() -> crate::schema::Node { crate::schema::Node::container("Chars", [("values", CV::schema())]) }
+    }
+    impl<'a, CV: crate::FromBytes<'a> + IndexAs<Encoded>> crate::FromBytes<'a> for Chars<CV> {
         const SLICE_COUNT: usize = CV::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -320,6 +375,15 @@ mod chars {
         fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
             Self { values: CV::from_store(store, offset) }
         }
+        fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { CV::element_sizes(sizes) }
+        fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { CV::validate(slices) }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let len = self.values.validate_deep()?;
+            if let Some(index) = (0 .. len).find(|i| char::from_u32(self.values.index_as(*i)).is_none()) {
+                return Err(format!("Chars: value at {} is not a `char`", index));
+            }
+            Ok(len)
+        }
     }
 }
 
@@ -327,6 +391,7 @@ pub use larges::{U128s, I128s};
 /// Columnar stores for `u128` and `i128`, stored as [u8; 16] bits.
 mod larges {
 
+    use alloc::string::String;
     use crate::*;
     use crate::common::{BorrowIndexAs, PushIndexAs};
 
@@ -359,6 +424,10 @@ mod larges {
         fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
             self.values.extend_from_self(other.values, range)
         }
+        #[inline(always)]
+        fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.values.gather(other.values, indices)
+        }
 
         fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
             self.values.reserve_for(selves.map(|x| x.values))
@@ -392,6 +461,9 @@ mod larges {
         }
     }
 
+    impl<CV: crate::schema::Schema> crate::schema::Schema for U128s<CV> {
+        fn schema() -> crate::schema::Node { crate::schema::Node::container("U128s", [("values", CV::schema())]) }
+    }
     impl<'a, CV: crate::FromBytes<'a>> crate::FromBytes<'a> for U128s<CV> {
         const SLICE_COUNT: usize = CV::SLICE_COUNT;
         #[inline(always)]
@@ -402,6 +474,9 @@ mod larges {
         fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
             Self { values: CV::from_store(store, offset) }
         }
+        fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { CV::element_sizes(sizes) }
+        fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { CV::validate(slices) }
+        fn validate_deep(&self) -> Result<usize, String> { self.values.validate_deep() }
     }
 
     #[derive(Copy, Clone, Default)]
@@ -431,6 +506,10 @@ mod larges {
         fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
             self.values.extend_from_self(other.values, range)
         }
+        #[inline(always)]
+        fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.values.gather(other.values, indices)
+        }
 
         fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
             self.values.reserve_for(selves.map(|x| x.values))
@@ -464,6 +543,9 @@ mod larges {
         }
     }
 
+    impl<CV: crate::schema::Schema> crate::schema::Schema for I128s<CV> {
+        fn schema() -> crate::schema::Node { crate::schema::Node::container("I128s", [("values", CV::schema())]) }
+    }
     impl<'a, CV: crate::FromBytes<'a>> crate::FromBytes<'a> for I128s<CV> {
         const SLICE_COUNT: usize = CV::SLICE_COUNT;
         #[inline(always)]
@@ -474,6 +556,9 @@ mod larges {
         fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
             Self { values: CV::from_store(store, offset) }
         }
+        fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> { CV::element_sizes(sizes) }
+        fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> { CV::validate(slices) }
+        fn validate_deep(&self) -> Result<usize, String> { self.values.validate_deep() }
     }
 }
 
@@ -487,6 +572,7 @@ pub mod offsets {
 
     pub use array::Fixeds;
     pub use stride::Strides;
+    pub use delta::Deltas;
 
     /// An offset container that encodes a constant spacing in its type.
     ///
@@ -522,6 +608,10 @@ pub mod offsets {
             fn extend_from_self(&mut self, _other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
                 self.count += range.len() as u64;
             }
+            #[inline(always)]
+            fn gather(&mut self, _other: Self::Borrowed<'_>, indices: &[usize]) {
+                self.count += indices.len() as u64;
+            }
 
             fn reserve_for<'a, I>(&mut self, _selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone { }
         }
@@ -535,13 +625,13 @@ pub mod offsets {
             #[inline(always)]
             fn get(&self, index: usize) -> Self::Ref { (index as u64 + 1) * K }
         }
-        impl<'a, const K: u64, CC> Index for &'a Fixeds<K, CC> {
+        impl<const K: u64, CC> Index for &Fixeds<K, CC> {
             type Ref = u64;
             #[inline(always)]
             fn get(&self, index: usize) -> Self::Ref { (index as u64 + 1) * K }
         }
 
-        impl<'a, const K: u64, T> Push<T> for Fixeds<K> {
+        impl<const K: u64, T> Push<T> for Fixeds<K> {
             // TODO: check for overflow?
             #[inline(always)]
             fn push(&mut self, _item: T) { self.count += 1; }
@@ -564,6 +654,9 @@ pub mod offsets {
                 (8, bytemuck::cast_slice(core::slice::from_ref(self.count)))
             }
         }
+        impl<const K: u64, CC> crate::schema::Schema for Fixeds<K, CC> {
+            fn schema() -> crate::schema::Node { crate::schema::Node::container("Fixeds", [("count", crate::schema::Node::slice::<u64>())]) }
+        }
         impl<'a, const K: u64> crate::FromBytes<'a> for Fixeds<K, &'a u64> {
             const SLICE_COUNT: usize = 1;
             #[inline(always)]
@@ -586,6 +679,12 @@ pub mod offsets {
                 }
                 Ok(())
             }
+            fn validate_deep(&self) -> Result<usize, String> {
+                if self.count.checked_mul(K).is_none() {
+                    return Err(format!("Fixeds: count {} overflows with stride {}", self.count, K));
+                }
+                usize::try_from(*self.count).map_err(|_| format!("Fixeds: count {} does not fit in `usize`", self.count))
+            }
         }
 
         use super::Strides;
@@ -676,6 +775,11 @@ pub mod offsets {
                 }
             }
         }
+        impl<BC: crate::schema::Schema, HC> crate::schema::Schema for Strides<BC, HC> {
+            fn schema() -> crate::schema::Node {
+                crate::schema::Node::container("Strides", [("head", crate::schema::Node::slice::<u64>()), ("bounds", BC::schema())])
+            }
+        }
         impl<'a, BC: FromBytes<'a>> FromBytes<'a> for Strides<BC, &'a [u64]> {
             const SLICE_COUNT: usize = 1 + BC::SLICE_COUNT;
             #[inline(always)]
@@ -701,6 +805,16 @@ pub mod offsets {
                 }
                 BC::validate(&slices[1..])
             }
+            fn validate_deep(&self) -> Result<usize, String> {
+                let (stride, length) = (self.head[0], self.head[1]);
+                if stride.checked_mul(length).is_none() {
+                    return Err(format!("Strides: stride {} and length {} overflow", stride, length));
+                }
+                let bounds = self.bounds.validate_deep()?;
+                usize::try_from(length).ok()
+                    .and_then(|length| length.checked_add(bounds))
+                    .ok_or_else(|| format!("Strides: length {} with {} bounds overflows `usize`", length, bounds))
+            }
         }
 
         impl Strides {
@@ -763,9 +877,212 @@ pub mod offsets {
         }
     }
 
+    /// An offset container that bit-packs offsets relative to per-block anchors.
+    ///
+    /// Offsets are grouped into blocks of `packed::BLOCK_LEN`. Within a complete block, each offset
+    /// is predicted by a line from the block's first offset with slope equal to the block's least
+    /// difference between consecutive offsets. The residuals from the prediction are non-decreasing,
+    /// and are stored in a `Packed<u64>` that anchors each block at its least residual and uses the
+    /// narrowest bit width for the rest. Lists of equal lengths have residuals of zero, which take
+    /// no space at all, and short lists of varied lengths take a few bits each.
+    mod delta {
+
+        use alloc::{vec::Vec, string::String};
+        use crate::{Container, Borrow, Index, IndexAs, Push, Clear, AsBytes, FromBytes};
+        use crate::packed::{Packed, BLOCK_LEN};
+
+        /// Columnar store for non-decreasing `u64` offsets, bit-packed in blocks.
+        ///
+        /// Each complete block has an entry in `slopes`, and its residuals in `residuals`.
+        /// Offsets after the last complete block are in `tail`, and are not yet packed.
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[derive(Copy, Clone, Debug, Default, PartialEq)]
+        pub struct Deltas<SC = Vec<u64>, RC = Packed<u64>, TC = Vec<u64>> {
+            /// For each complete block, the least difference between consecutive offsets.
+            pub slopes: SC,
+            /// For each offset in a complete block, its difference from `slope * (index % BLOCK_LEN)`.
+            pub residuals: RC,
+            /// The offsets after the last complete block.
+            pub tail: TC,
+        }
+
+        impl Borrow for Deltas {
+            type Ref<'a> = u64;
+            type Borrowed<'a> = Deltas<&'a [u64], <Packed<u64> as Borrow>::Borrowed<'a>, &'a [u64]>;
+            #[inline(always)]
+            fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+                Deltas {
+                    slopes: &self.slopes[..],
+                    residuals: self.residuals.borrow(),
+                    tail: &self.tail[..],
+                }
+            }
+            #[inline(always)]
+            fn reborrow<'b, 'a: 'b>(item: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a {
+                Deltas { slopes: item.slopes, residuals: Packed::<u64>::reborrow(item.residuals), tail: item.tail }
+            }
+            #[inline(always)] fn reborrow_ref<'b, 'a: 'b>(item: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a { item }
+        }
+
+        impl Container for Deltas {
+            fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
+                let mut index = range.start;
+                // Complete blocks can be copied, if both they and `self` are block aligned.
+                if self.tail.is_empty() && index % BLOCK_LEN == 0 {
+                    let first = index / BLOCK_LEN;
+                    let last = core::cmp::max(first, core::cmp::min(range.end / BLOCK_LEN, other.slopes.len()));
+                    if first < last {
+                        self.slopes.extend_from_slice(&other.slopes[first .. last]);
+                        self.residuals.extend_from_self(other.residuals, first * BLOCK_LEN .. last * BLOCK_LEN);
+                        index = last * BLOCK_LEN;
+                    }
+                }
+                for index in index .. range.end {
+                    self.push(other.get(index));
+                }
+            }
+            fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+                self.slopes.reserve_for(selves.clone().map(|x| x.slopes));
+                self.residuals.reserve_for(selves.map(|x| x.residuals));
+            }
+        }
+
+        impl<'a> Push<&'a u64> for Deltas { #[inline(always)] fn push(&mut self, item: &'a u64) { self.push(*item) } }
+        impl Push<u64> for Deltas {
+            #[inline]
+            fn push(&mut self, item: u64) {
+                self.tail.push(item);
+                if self.tail.len() == BLOCK_LEN { self.seal(); }
+            }
+        }
+        impl Clear for Deltas {
+            #[inline(always)]
+            fn clear(&mut self) {
+                self.slopes.clear();
+                self.residuals.clear();
+                self.tail.clear();
+            }
+        }
+
+        impl Deltas {
+            /// Packs the offsets of `tail`, which must be a complete block.
+            fn seal(&mut self) {
+                debug_assert_eq!(self.tail.len(), BLOCK_LEN);
+                let slope = self.tail.windows(2).map(|w| w[1].wrapping_sub(w[0])).min().unwrap_or(0);
+                for (index, offset) in self.tail.iter().enumerate() {
+                    self.residuals.push(offset.wrapping_sub(slope.wrapping_mul(index as u64)));
+                }
+                self.slopes.push(slope);
+                self.tail.clear();
+            }
+        }
+
+        impl<SC: crate::Len, RC, TC: crate::Len> crate::Len for Deltas<SC, RC, TC> {
+            #[inline(always)]
+            fn len(&self) -> usize { self.slopes.len() * BLOCK_LEN + self.tail.len() }
+        }
+        impl<SC: IndexAs<u64> + crate::Len, RC: Index<Ref = u64>, TC: IndexAs<u64>> Index for Deltas<SC, RC, TC> {
+            type Ref = u64;
+            #[inline(always)]
+            fn get(&self, index: usize) -> Self::Ref {
+                let block = index / BLOCK_LEN;
+                if block < self.slopes.len() {
+                    let slope = self.slopes.index_as(block);
+                    self.residuals.get(index).wrapping_add(slope.wrapping_mul((index % BLOCK_LEN) as u64))
+                }
+                else {
+                    self.tail.index_as(index - self.slopes.len() * BLOCK_LEN)
+                }
+            }
+        }
+        impl<SC: IndexAs<u64> + crate::Len, RC: Index<Ref = u64>, TC: IndexAs<u64>> Deltas<SC, RC, TC> {
+            /// The lower and upper bounds of the `index`-th list, when used as `Vecs` bounds.
+            #[inline(always)]
+            pub fn bounds(&self, index: usize) -> (usize, usize) {
+                let lower = if index == 0 { 0 } else { self.get(index - 1) as usize };
+                (lower, self.get(index) as usize)
+            }
+        }
+
+        impl<'a, SC: AsBytes<'a>, RC: AsBytes<'a>, TC: AsBytes<'a>> AsBytes<'a> for Deltas<SC, RC, TC> {
+            const SLICE_COUNT: usize = SC::SLICE_COUNT + RC::SLICE_COUNT + TC::SLICE_COUNT;
+            #[inline]
+            fn get_byte_slice(&self, index: usize) -> (u64, &'a [u8]) {
+                debug_assert!(index < Self::SLICE_COUNT);
+                if index < SC::SLICE_COUNT {
+                    self.slopes.get_byte_slice(index)
+                } else if index < SC::SLICE_COUNT + RC::SLICE_COUNT {
+                    self.residuals.get_byte_slice(index - SC::SLICE_COUNT)
+                } else {
+                    self.tail.get_byte_slice(index - SC::SLICE_COUNT - RC::SLICE_COUNT)
+                }
+            }
+        }
+        impl<SC: crate::schema::Schema, RC: crate::schema::Schema, TC: crate::schema::Schema> crate::schema::Schema for Deltas<SC, RC, TC> {
+            fn schema() -> crate::schema::Node {
+                crate::schema::Node::container("Deltas", [("slopes", SC::schema()), ("residuals", RC::schema()), ("tail", TC::schema())])
+            }
+        }
+        impl<'a> FromBytes<'a> for Deltas<&'a [u64], <Packed<u64> as Borrow>::Borrowed<'a>, &'a [u64]> {
+            const SLICE_COUNT: usize = 2 + <<Packed<u64> as Borrow>::Borrowed<'a> as FromBytes<'a>>::SLICE_COUNT;
+            #[inline(always)]
+            fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+                Self {
+                    slopes: FromBytes::from_bytes(bytes),
+                    residuals: FromBytes::from_bytes(bytes),
+                    tail: FromBytes::from_bytes(bytes),
+                }
+            }
+            #[inline(always)]
+            fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+                Self {
+                    slopes: FromBytes::from_store(store, offset),
+                    residuals: FromBytes::from_store(store, offset),
+                    tail: FromBytes::from_store(store, offset),
+                }
+            }
+            fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+                sizes.push(8);
+                <<Packed<u64> as Borrow>::Borrowed<'a>>::element_sizes(sizes)?;
+                sizes.push(8);
+                Ok(())
+            }
+            /// Validates element sizes, and that each complete block has a slope and packed residuals.
+            fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> {
+                let count = <Self as FromBytes<'a>>::SLICE_COUNT;
+                if slices.len() < count {
+                    return Err(format!("Deltas: expected {} slices but got {}", count, slices.len()));
+                }
+                let residuals = &slices[1 .. count - 1];
+                <<Packed<u64> as Borrow>::Borrowed<'a>>::validate(residuals)?;
+                for (i, (_, tail)) in [slices[0], slices[count - 1]].iter().enumerate() {
+                    if (8 - *tail as usize) % 8 != 0 {
+                        return Err(format!("Deltas: slice {} is not a multiple of element size 8", i));
+                    }
+                }
+                // The residuals have one block for each slope, and no unpacked residuals.
+                let (slopes, mins, packed_tail, tail) = (slices[0].0, residuals[0].0, residuals[3].0, slices[count - 1].0);
+                if slopes.len() != mins.len() || !packed_tail.is_empty() {
+                    return Err(format!("Deltas: {} slopes but {} residual blocks and {} unpacked residuals", slopes.len(), mins.len(), packed_tail.len()));
+                }
+                if tail.len() >= BLOCK_LEN {
+                    return Err(format!("Deltas: tail has {} offsets, but should be fewer than {}", tail.len(), BLOCK_LEN));
+                }
+                Ok(())
+            }
+            fn validate_deep(&self) -> Result<usize, String> {
+                self.residuals.validate_deep()?;
+                if self.slopes.len() != self.residuals.mins.len() || !self.residuals.tail.is_empty() || self.tail.len() >= BLOCK_LEN {
+                    return Err(format!("Deltas: {} slopes, {} residual blocks, {} unpacked residuals, and {} tail offsets", self.slopes.len(), self.residuals.mins.len(), self.residuals.tail.len(), self.tail.len()));
+                }
+                Ok(crate::Len::len(self))
+            }
+        }
+    }
+
     #[cfg(test)]
     mod test {
-        use alloc::vec::Vec;
+        use alloc::{vec::Vec, string::String};
         #[test]
         fn round_trip() {
 
@@ -796,6 +1113,49 @@ pub mod offsets {
             cols.push(&[0, 0]);
             assert!(TryInto::<Fixeds<3>>::try_into(cols.bounds).is_err());
         }
+
+        #[test]
+        fn deltas() {
+
+            use crate::common::{Index, Push, Len};
+            use crate::{Borrow, Container, Vecs, Strings};
+            use crate::bytes::stash::Stash;
+            use crate::primitive::offsets::Deltas;
+
+            // Lists of a regular length take no space for their bounds.
+            let mut cols = Vecs::<Vec<u64>, Deltas>::default();
+            for i in 0 .. 1000u64 { cols.push(&[i, i + 1, i + 2]); }
+            assert_eq!(cols.bounds.residuals.words.len(), 0);
+            for i in 0 .. 1000 {
+                assert_eq!(cols.borrow().get(i).len(), 3);
+                assert_eq!(cols.bounds.bounds(i), (3 * i, 3 * i + 3));
+            }
+
+            // Strings of varied lengths take a fraction of the space of `u64` offsets.
+            let words: Vec<String> = (0 .. 1000u64).map(|i| "x".repeat(((i * 7919) % 13) as usize)).collect();
+            let mut strings = Strings::<Deltas>::default();
+            for word in words.iter() { strings.push(word); }
+            assert_eq!(strings.len(), words.len());
+            assert!(4 * strings.bounds.residuals.words.len() < strings.len());
+            for (index, word) in words.iter().enumerate() {
+                assert_eq!(strings.borrow().get(index), word.as_bytes());
+            }
+
+            // Copying ranges, including complete blocks.
+            let mut copy = Strings::<Deltas>::default();
+            copy.extend_from_self(strings.borrow(), 0 .. 700);
+            copy.extend_from_self(strings.borrow(), 700 .. 1000);
+            assert_eq!(copy.bounds.borrow().into_index_iter().collect::<Vec<_>>(), strings.bounds.borrow().into_index_iter().collect::<Vec<_>>());
+            assert_eq!(copy, strings);
+
+            // Serialization and validation.
+            let mut bytes: Vec<u8> = Vec::new();
+            crate::bytes::indexed::write(&mut bytes, &strings.borrow()).unwrap();
+            let stash: Stash<Strings<Deltas>, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+            for (index, word) in words.iter().enumerate() {
+                assert_eq!(stash.borrow().get(index), word.as_bytes());
+            }
+        }
     }
 }
 
@@ -834,6 +1194,10 @@ mod empty {
         fn extend_from_self(&mut self, _other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
             self.count += range.len() as u64;
         }
+        #[inline(always)]
+        fn gather(&mut self, _other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.count += indices.len() as u64;
+        }
 
         fn reserve_for<'a, I>(&mut self, _selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone { }
     }
@@ -888,6 +1252,9 @@ mod empty {
             (8, bytemuck::cast_slice(core::slice::from_ref(self.count)))
         }
     }
+    impl<CC> crate::schema::Schema for crate::primitive::Empties<CC> {
+        fn schema() -> crate::schema::Node { crate::schema::Node::container("Empties", [("count", crate::schema::Node::slice::<u64>())]) }
+    }
     impl<'a> crate::FromBytes<'a> for crate::primitive::Empties<&'a u64> {
         const SLICE_COUNT: usize = 1;
         #[inline(always)]
@@ -910,6 +1277,9 @@ mod empty {
             }
             Ok(())
         }
+        fn validate_deep(&self) -> Result<usize, String> {
+            usize::try_from(*self.count).map_err(|_| format!("Empties: count {} does not fit in `usize`", self.count))
+        }
     }
 }
 
@@ -983,6 +1353,11 @@ mod boolean {
         }
     }
 
+    impl<VC: crate::schema::Schema, TC> crate::schema::Schema for crate::primitive::Bools<VC, TC> {
+        fn schema() -> crate::schema::Node {
+            crate::schema::Node::container("Bools", [("values", VC::schema()), ("tail", crate::schema::Node::slice::<u64>())])
+        }
+    }
     impl<'a, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for crate::primitive::Bools<VC, &'a [u64]> {
         const SLICE_COUNT: usize = VC::SLICE_COUNT + 1;
         #[inline(always)]
@@ -1014,6 +1389,15 @@ mod boolean {
             }
             Ok(())
         }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let words = self.values.validate_deep()?;
+            let last_bits = self.tail[1];
+            if last_bits >= 64 {
+                return Err(format!("Bools: {} bits in the last word, but at most 63 expected", last_bits));
+            }
+            words.checked_mul(64).and_then(|bits| bits.checked_add(last_bits as usize))
+                .ok_or_else(|| format!("Bools: {} words overflow `usize` bits", words))
+        }
     }
 
     impl<VC: Len, TC: IndexAs<u64>> Len for Bools<VC, TC> {
@@ -1075,7 +1459,7 @@ pub use duration::Durations;
 /// A columnar store for `core::time::Duration`.
 mod duration {
 
-    use alloc::vec::Vec;
+    use alloc::{vec::Vec, string::String};
     use core::time::Duration;
     use crate::{Container, Len, Index, IndexAs, Push, Clear, Borrow};
 
@@ -1120,6 +1504,11 @@ mod duration {
             self.seconds.extend_from_self(other.seconds, range.clone());
             self.nanoseconds.extend_from_self(other.nanoseconds, range);
         }
+        #[inline(always)]
+        fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+            self.seconds.gather(other.seconds, indices);
+            self.nanoseconds.gather(other.nanoseconds, indices);
+        }
 
         fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
             self.seconds.reserve_for(selves.clone().map(|x| x.seconds));
@@ -1139,7 +1528,12 @@ mod duration {
             }
         }
     }
-    impl<'a, SC: crate::FromBytes<'a>, NC: crate::FromBytes<'a>> crate::FromBytes<'a> for crate::primitive::Durations<SC, NC> {
+    impl<SC: crate::schema::Schema, NC: crate::schema::Schema> crate::schema::Schema for crate::primitive::Durations<SC, NC> {
+        fn schema() -> crate::schema::Node {
+            crate::schema::Node::container("Durations", [("seconds", SC::schema()), ("nanoseconds", NC::schema())])
+        }
+    }
+    impl<'a, SC: crate::FromBytes<'a>, NC: crate::FromBytes<'a> + IndexAs<u32>> crate::FromBytes<'a> for crate::primitive::Durations<SC, NC> {
         const SLICE_COUNT: usize = SC::SLICE_COUNT + NC::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -1155,6 +1549,21 @@ mod duration {
                 nanoseconds: NC::from_store(store, offset),
             }
         }
+        fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+            SC::element_sizes(sizes)?;
+            NC::element_sizes(sizes)?;
+            Ok(())
+        }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let len = self.seconds.validate_deep()?;
+            if self.nanoseconds.validate_deep()? != len {
+                return Err(format!("Durations: {} seconds but a different number of nanoseconds", len));
+            }
+            if let Some(index) = (0 .. len).find(|i| self.nanoseconds.index_as(*i) >= 1_000_000_000) {
+                return Err(format!("Durations: nanoseconds at {} are not less than one second", index));
+            }
+            Ok(len)
+        }
     }
 
     impl<SC: Len, NC> Len for Durations<SC, NC> {
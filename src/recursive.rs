@@ -0,0 +1,336 @@
+//! Support for recursive types, whose values contain values of the same type.
+//!
+//! A type like `struct Tree { data: u64, kids: Vec<Tree> }` cannot store its children in a nested
+//! container, as that container would contain itself. Instead, `#[derive(Columnar)]` stores the nodes
+//! of all values, children included, in one container whose recursive fields hold the indexes of the
+//! children among the nodes. Children are pushed before their parents, so links only point backwards,
+//! and [`Recursive`] records which of the nodes are the values that were pushed.
+//!
+//! References navigate links lazily. Fields of type `Box<Self>`, `Vec<Self>`, and `Option<Box<Self>>`
+//! are presented as a [`Child`], as [`Children`], and as an `Option<Child>`, which find their nodes when asked.
+use alloc::{vec::Vec, string::String};
+
+use crate::{AsBytes, Borrow, Clear, Container, FromBytes, Index, IndexAs, Len, Push, Slice};
+use crate::common::{BorrowIndexAs, PushIndexAs};
+
+/// A container for values of a recursive type, whose nodes are in `nodes` and whose values are the nodes at `roots`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Recursive<C, R = Vec<u64>> {
+    /// The nodes of all values, with children before their parents.
+    pub nodes: C,
+    /// The index of the node of each value.
+    pub roots: R,
+}
+
+impl<C: Borrow, R: BorrowIndexAs<u64>> Borrow for Recursive<C, R> {
+    type Ref<'a> = C::Ref<'a>;
+    type Borrowed<'a> = Recursive<C::Borrowed<'a>, R::Borrowed<'a>>;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        Recursive {
+            nodes: self.nodes.borrow(),
+            roots: self.roots.borrow(),
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a {
+        Recursive {
+            nodes: C::reborrow(thing.nodes),
+            roots: R::reborrow(thing.roots),
+        }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a { C::reborrow_ref(thing) }
+}
+
+// Values are copied node by node, as the links of `other` would not be correct in `self`.
+impl<C: Container + Len, R: PushIndexAs<u64>> Container for Recursive<C, R> {
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.nodes.reserve_for(selves.clone().map(|x| x.nodes));
+        self.roots.reserve_for(selves.map(|x| x.roots));
+    }
+}
+
+impl<C, R: Len> Len for Recursive<C, R> {
+    #[inline(always)] fn len(&self) -> usize { self.roots.len() }
+}
+
+impl<C: Clear, R: Clear> Clear for Recursive<C, R> {
+    #[inline(always)]
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.roots.clear();
+    }
+}
+
+impl<C: Index, R: IndexAs<u64>> Index for Recursive<C, R> {
+    type Ref = C::Ref;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        self.nodes.get(self.roots.index_as(index) as usize)
+    }
+}
+impl<'a, C, R: IndexAs<u64>> Index for &'a Recursive<C, R> where &'a C: Index {
+    type Ref = <&'a C as Index>::Ref;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        (&self.nodes).get(self.roots.index_as(index) as usize)
+    }
+}
+
+/// Values are pushed as nodes, which first push their children.
+impl<T, C: Push<T> + Len, R: for<'a> Push<&'a u64>> Push<T> for Recursive<C, R> {
+    #[inline]
+    fn push(&mut self, item: T) {
+        let root = push(&mut self.nodes, item);
+        self.roots.push(&root);
+    }
+}
+
+impl<'a, C: AsBytes<'a>, R: AsBytes<'a>> AsBytes<'a> for Recursive<C, R> {
+    const SLICE_COUNT: usize = C::SLICE_COUNT + R::SLICE_COUNT;
+    #[inline]
+    fn get_byte_slice(&self, index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        if index < C::SLICE_COUNT {
+            self.nodes.get_byte_slice(index)
+        } else {
+            self.roots.get_byte_slice(index - C::SLICE_COUNT)
+        }
+    }
+}
+
+impl<'a, C: FromBytes<'a>, R: FromBytes<'a> + IndexAs<u64>> FromBytes<'a> for Recursive<C, R> {
+    const SLICE_COUNT: usize = C::SLICE_COUNT + R::SLICE_COUNT;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            nodes: FromBytes::from_bytes(bytes),
+            roots: FromBytes::from_bytes(bytes),
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self {
+            nodes: C::from_store(store, offset),
+            roots: R::from_store(store, offset),
+        }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        C::element_sizes(sizes)?;
+        R::element_sizes(sizes)?;
+        Ok(())
+    }
+    fn validate_deep(&self) -> Result<usize, String> {
+        let nodes = self.nodes.validate_deep()?;
+        let roots = self.roots.validate_deep()?;
+        for index in 0 .. roots {
+            let root = self.roots.index_as(index);
+            if root >= nodes as u64 {
+                return Err(format!("Recursive: root {} at {} is not among {} nodes", root, index, nodes));
+            }
+        }
+        Ok(roots)
+    }
+}
+
+impl<C: crate::schema::Schema, R: crate::schema::Schema> crate::schema::Schema for Recursive<C, R> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Recursive", [("nodes", C::schema()), ("roots", R::schema())])
+    }
+}
+
+/// Links from a node to its children, as presented by the container of a recursive field.
+///
+/// The containers of `Box<Self>`, `Vec<Self>`, and `Option<Box<Self>>` fields store the indexes
+/// of children as `u64`, `Vec<u64>`, and `Option<u64>`, whose references all present their links.
+pub trait Links: Copy {
+    /// The number of links.
+    fn count(&self) -> usize;
+    /// The index of the node the `index`-th link points to.
+    fn link(&self, index: usize) -> u64;
+}
+impl Links for u64 {
+    #[inline(always)] fn count(&self) -> usize { 1 }
+    #[inline(always)] fn link(&self, _index: usize) -> u64 { *self }
+}
+impl Links for &u64 {
+    #[inline(always)] fn count(&self) -> usize { 1 }
+    #[inline(always)] fn link(&self, _index: usize) -> u64 { **self }
+}
+impl<L: Links> Links for Option<L> {
+    #[inline(always)] fn count(&self) -> usize { self.as_ref().map_or(0, |links| links.count()) }
+    #[inline(always)] fn link(&self, index: usize) -> u64 { self.as_ref().expect("no link").link(index) }
+}
+impl<S: IndexAs<u64> + Copy> Links for Slice<S> {
+    #[inline(always)] fn count(&self) -> usize { self.len() }
+    #[inline(always)] fn link(&self, index: usize) -> u64 { self.slice.index_as(self.lower + index) }
+}
+
+/// Pushes `item` into `nodes`, and returns the index of its node.
+#[inline]
+pub fn push<T, C: Push<T> + Len>(nodes: &mut C, item: T) -> u64 {
+    nodes.push(item);
+    nodes.len() as u64 - 1
+}
+
+/// Pushes each of `items` into `nodes`, and returns the indexes of their nodes.
+#[inline]
+pub fn push_all<T, C: Push<T> + Len>(nodes: &mut C, items: impl IntoIterator<Item=T>) -> Vec<u64> {
+    items.into_iter().map(|item| push(nodes, item)).collect()
+}
+
+/// Checks that the links of each of the first `count` nodes point to earlier nodes.
+pub fn validate_links<C: Index>(name: &str, links: &C, count: usize) -> Result<(), String> where C::Ref: Links {
+    for index in 0 .. count {
+        validate_node(name, index, links.get(index))?;
+    }
+    Ok(())
+}
+
+/// Checks that the links of the node at `index` point to earlier nodes.
+pub fn validate_node(name: &str, index: usize, links: impl Links) -> Result<(), String> {
+    for position in 0 .. links.count() {
+        let link = links.link(position);
+        if link >= index as u64 {
+            return Err(format!("{}: node {} links to node {}, which does not precede it", name, index, link));
+        }
+    }
+    Ok(())
+}
+
+/// A reference to the child node at `index` among `nodes`.
+#[derive(Copy, Clone)]
+pub struct Child<C> {
+    pub nodes: C,
+    pub index: u64,
+}
+
+impl<C> Child<C> {
+    /// The child that `links` points to, which must hold exactly one link.
+    #[inline(always)]
+    pub fn new(nodes: C, links: impl Links) -> Self {
+        debug_assert_eq!(links.count(), 1);
+        Self { nodes, index: links.link(0) }
+    }
+    /// The child that `links` points to, if it holds a link.
+    #[inline(always)]
+    pub fn optional(nodes: C, links: impl Links) -> Option<Self> {
+        if links.count() > 0 { Some(Self::new(nodes, links)) } else { None }
+    }
+    /// Maps the nodes to another type, for example to reborrow them.
+    #[inline(always)]
+    pub fn map<D>(self, f: impl FnOnce(C) -> D) -> Child<D> {
+        Child { nodes: f(self.nodes), index: self.index }
+    }
+}
+
+impl<C: Index> Child<C> {
+    /// A reference to the child node.
+    #[inline(always)]
+    pub fn get(&self) -> C::Ref {
+        self.nodes.get(self.index as usize)
+    }
+}
+
+// Children print as their indexes, as printing their nodes would need their references to print.
+impl<C> core::fmt::Debug for Child<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Child").field(&self.index).finish()
+    }
+}
+
+/// References to the child nodes that `links` points to among `nodes`.
+#[derive(Copy, Clone)]
+pub struct Children<C, L> {
+    pub nodes: C,
+    pub links: L,
+}
+
+impl<C, L> Children<C, L> {
+    /// The children that `links` points to.
+    #[inline(always)]
+    pub fn new(nodes: C, links: L) -> Self {
+        Self { nodes, links }
+    }
+    /// Maps the nodes and the links to other types, for example to reborrow them.
+    #[inline(always)]
+    pub fn map<D, M>(self, f: impl FnOnce(C) -> D, g: impl FnOnce(L) -> M) -> Children<D, M> {
+        Children { nodes: f(self.nodes), links: g(self.links) }
+    }
+}
+
+impl<C: Index, L: Links> Children<C, L> {
+    /// Converts the children into an iterator.
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter(self) -> crate::common::IterOwn<Self> {
+        self.into_index_iter()
+    }
+}
+
+impl<C, L: Links> Len for Children<C, L> {
+    #[inline(always)] fn len(&self) -> usize { self.links.count() }
+}
+
+impl<C: Index, L: Links> Index for Children<C, L> {
+    type Ref = C::Ref;
+    #[inline(always)]
+    fn get(&self, index: usize) -> Self::Ref {
+        self.nodes.get(self.links.link(index) as usize)
+    }
+}
+
+impl<C, L: Links> core::fmt::Debug for Children<C, L> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Children").field(&(0 .. self.links.count()).map(|index| self.links.link(index)).collect::<Vec<_>>()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+    use crate::{AsBytes, Borrow, Container, FromBytes, Index, Len, Push, Slice};
+    use super::{Child, Children, Links, Recursive};
+
+    #[test]
+    fn links() {
+        assert_eq!((3u64.count(), 3u64.link(0)), (1, 3));
+        assert_eq!((Some(&4u64).count(), Some(&4u64).link(0)), (1, 4));
+        assert_eq!(None::<u64>.count(), 0);
+        let slice = Slice::new(1, 3, &[5u64, 6, 7, 8][..]);
+        assert_eq!((0 .. slice.count()).map(|index| slice.link(index)).collect::<Vec<_>>(), [6, 7]);
+    }
+
+    #[test]
+    fn navigate() {
+        let nodes = &[10u64, 20, 30, 40][..];
+        assert_eq!(*Child::new(nodes, 2u64).get(), 30);
+        assert!(Child::optional(nodes, None::<u64>).is_none());
+        assert_eq!(*Child::optional(nodes, Some(&1u64)).unwrap().get(), 20);
+        let children = Children::new(nodes, Slice::new(0, 3, &[3u64, 0, 1][..]));
+        assert_eq!(children.len(), 3);
+        assert_eq!(children.into_iter().copied().collect::<Vec<_>>(), [40, 10, 20]);
+        assert_eq!(format!("{:?}", children), "Children([3, 0, 1])");
+    }
+
+    #[test]
+    fn roots() {
+        let mut recursive: Recursive<Vec<u64>> = Default::default();
+        recursive.push(&5u64);
+        recursive.push(&6u64);
+        recursive.nodes.push(7u64);
+        assert_eq!(recursive.len(), 2);
+        assert_eq!(recursive.borrow().get(1), &6);
+
+        let mut copy: Recursive<Vec<u64>> = Default::default();
+        copy.extend_from_self(recursive.borrow(), 1 .. 2);
+        assert_eq!((copy.nodes, copy.roots), (vec![6], vec![0]));
+
+        let borrowed = recursive.borrow();
+        let rebuilt = Recursive::<&[u64], &[u64]>::from_bytes(&mut borrowed.as_bytes().map(|(_, bytes)| bytes));
+        assert_eq!(rebuilt.validate_deep(), Ok(2));
+        let roots = [0u64, 3];
+        assert!(Recursive { roots: &roots[..], ..rebuilt }.validate_deep().is_err());
+    }
+}
@@ -0,0 +1,634 @@
+//! Roaring bitmap (and similar) containers.
+
+use alloc::{vec::Vec, string::String};
+
+use crate::{Borrow, Clear, Container, Len, Push, Vecs};
+use crate::common::index::CopyAs;
+
+/// The number of bits in each block.
+pub const BLOCK_BITS: usize = 1 << 16;
+/// The number of `u64` words in a bitmap block.
+const BITMAP_WORDS: usize = BLOCK_BITS / 64;
+/// The largest number of set bits an array block holds before becoming a bitmap.
+///
+/// At this size the array (two bytes per set bit) is as large as the bitmap.
+const ARRAY_LIMIT: usize = BITMAP_WORDS * 4;
+
+/// Block kind: a bitmap of `BITMAP_WORDS` words in `bitmaps`.
+pub const BITMAP: u8 = 0;
+/// Block kind: a sorted list of set positions in `arrays`.
+pub const ARRAY: u8 = 1;
+/// Block kind: a sorted list of inclusive `[start, last]` runs of set positions in `runs`.
+pub const RUN: u8 = 2;
+
+/// A container for `bool` that uses techniques from Roaring bitmaps.
+///
+/// These techniques are to block the bits into blocks of 2^16 bits,
+/// and to encode each block based on its density. Either a bitmap
+/// for dense blocks or a list of set bits for sparse blocks.
+/// Additionally, run blocks encode runs of set bits, and are chosen
+/// when a block is complete and they are the most compact encoding.
+///
+/// Each block has an entry in `kinds`, `offsets`, and `counts`. The offset
+/// locates the block in the container for its kind, and the counts are the
+/// cumulative number of set bits *after* each block, which supports `rank`
+/// and `select`. Only the last block may be incomplete, and it is always
+/// either an array or a bitmap; it is the last entry in its kind's container.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RoaringBits<KC = Vec<u8>, OC = Vec<u64>, CC = Vec<u64>, MC = Vec<u64>, AC = Vecs<Vec<u16>>, RC = Vecs<Vec<[u16; 2]>>, LC = u64> {
+    /// For each block, one of `BITMAP`, `ARRAY`, or `RUN`.
+    pub kinds: KC,
+    /// For each block, its position in the container for its kind.
+    ///
+    /// For bitmaps this is the index of the bitmap, rather than of its first word.
+    pub offsets: OC,
+    /// For each block, the number of set bits up through the end of the block.
+    pub counts: CC,
+    /// Bitmap blocks, each `BLOCK_BITS / 64` words.
+    pub bitmaps: MC,
+    /// Array blocks, each a sorted list of set positions within the block.
+    pub arrays: AC,
+    /// Run blocks, each a sorted list of disjoint inclusive `[start, last]` ranges.
+    pub runs: RC,
+    /// The total number of bits.
+    pub len: LC,
+}
+
+impl Borrow for RoaringBits {
+    type Ref<'a> = bool;
+    type Borrowed<'a> = RoaringBits<&'a [u8], &'a [u64], &'a [u64], &'a [u64], Vecs<&'a [u16], &'a [u64]>, Vecs<&'a [[u16; 2]], &'a [u64]>, &'a u64>;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        RoaringBits {
+            kinds: &self.kinds[..],
+            offsets: &self.offsets[..],
+            counts: &self.counts[..],
+            bitmaps: &self.bitmaps[..],
+            arrays: self.arrays.borrow(),
+            runs: self.runs.borrow(),
+            len: &self.len,
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where Self: 'a {
+        RoaringBits {
+            kinds: thing.kinds,
+            offsets: thing.offsets,
+            counts: thing.counts,
+            bitmaps: thing.bitmaps,
+            arrays: Vecs::<Vec<u16>>::reborrow(thing.arrays),
+            runs: Vecs::<Vec<[u16; 2]>>::reborrow(thing.runs),
+            len: thing.len,
+        }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a { thing }
+}
+
+impl Container for RoaringBits {
+    /// Copies complete blocks wholesale when both `self` and `range` are block aligned.
+    fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
+        let mut index = range.start;
+        while index < range.end {
+            let aligned = self.len as usize % BLOCK_BITS == 0 && index % BLOCK_BITS == 0;
+            let block_end = core::cmp::min(index + BLOCK_BITS, other.len());
+            // Incomplete blocks must remain open to pushes, which run blocks are not.
+            let complete = block_end - index == BLOCK_BITS || other.kinds[index / BLOCK_BITS] != RUN;
+            if aligned && complete && block_end <= range.end {
+                self.push_block(other, index / BLOCK_BITS);
+                index = block_end;
+            }
+            else {
+                let upper = core::cmp::min((index / BLOCK_BITS + 1) * BLOCK_BITS, range.end);
+                for i in index .. upper { self.push(crate::Index::get(&other, i)); }
+                index = upper;
+            }
+        }
+    }
+
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        let blocks = selves.clone().map(|x| x.kinds.len()).sum();
+        self.kinds.reserve(blocks);
+        self.offsets.reserve(blocks);
+        self.counts.reserve(blocks);
+        self.bitmaps.reserve(selves.clone().map(|x| x.bitmaps.len()).sum());
+        self.arrays.reserve_for(selves.clone().map(|x| x.arrays));
+        self.runs.reserve_for(selves.map(|x| x.runs));
+    }
+}
+
+impl RoaringBits {
+    /// The number of set bits.
+    #[inline(always)]
+    pub fn ones(&self) -> usize { self.borrow().ones() }
+    /// The number of set bits *strictly* preceding `index`.
+    #[inline(always)]
+    pub fn rank(&self, index: usize) -> usize { self.borrow().rank(index) }
+    /// The index of the set bit preceded by exactly `rank` set bits, should one exist.
+    #[inline(always)]
+    pub fn select(&self, rank: usize) -> Option<usize> { self.borrow().select(rank) }
+
+    /// Sets position `pos` of the last block, which must be incomplete or just opened.
+    fn set_last(&mut self, pos: u16) {
+        if *self.kinds.last().unwrap() == ARRAY {
+            let lower = self.arrays.values.len() - self.last_array_len();
+            if self.arrays.values.len() - lower == ARRAY_LIMIT {
+                // The array would exceed the size of a bitmap, so convert the block to one.
+                let mut bitmap = [0u64; BITMAP_WORDS];
+                for &p in &self.arrays.values[lower..] {
+                    bitmap[p as usize / 64] |= 1 << (p % 64);
+                }
+                self.arrays.values.truncate(lower);
+                self.arrays.bounds.pop();
+                *self.kinds.last_mut().unwrap() = BITMAP;
+                *self.offsets.last_mut().unwrap() = (self.bitmaps.len() / BITMAP_WORDS) as u64;
+                self.bitmaps.extend_from_slice(&bitmap);
+            }
+            else {
+                self.arrays.values.push(pos);
+                *self.arrays.bounds.last_mut().unwrap() += 1;
+                return;
+            }
+        }
+        let lower = self.bitmaps.len() - BITMAP_WORDS;
+        self.bitmaps[lower + pos as usize / 64] |= 1 << (pos % 64);
+    }
+
+    /// The number of entries in the last array block, which must be the last block.
+    fn last_array_len(&self) -> usize {
+        let bounds = &self.arrays.bounds;
+        let upper = bounds[bounds.len() - 1];
+        let lower = if bounds.len() > 1 { bounds[bounds.len() - 2] } else { 0 };
+        (upper - lower) as usize
+    }
+
+    /// Re-encodes the last block as runs, if that would be more compact.
+    ///
+    /// Called when the last block becomes complete; incomplete blocks are not re-encoded.
+    fn seal_last(&mut self) {
+        let kind = *self.kinds.last().unwrap();
+        let runs = {
+            let borrowed = self.borrow();
+            let block = borrowed.kinds.len() - 1;
+            let mut runs: Vec<[u16; 2]> = Vec::new();
+            borrowed.for_each_one(block, |pos| {
+                match runs.last_mut() {
+                    Some(run) if run[1] as usize + 1 == pos as usize => { run[1] = pos; }
+                    _ => { runs.push([pos, pos]); }
+                }
+            });
+            runs
+        };
+        let bytes = match kind {
+            BITMAP => 8 * BITMAP_WORDS,
+            _ => 2 * self.last_array_len(),
+        };
+        if 4 * runs.len() < bytes {
+            match kind {
+                BITMAP => { self.bitmaps.truncate(self.bitmaps.len() - BITMAP_WORDS); },
+                _ => {
+                    let lower = self.arrays.values.len() - self.last_array_len();
+                    self.arrays.values.truncate(lower);
+                    self.arrays.bounds.pop();
+                },
+            }
+            *self.kinds.last_mut().unwrap() = RUN;
+            *self.offsets.last_mut().unwrap() = self.runs.len() as u64;
+            self.runs.push_iter(runs.iter());
+        }
+    }
+
+    /// Appends block `block` of `other` to `self`, which must be block aligned.
+    fn push_block(&mut self, other: <Self as Borrow>::Borrowed<'_>, block: usize) {
+        let count = other.counts[block] - if block > 0 { other.counts[block - 1] } else { 0 };
+        let offset = other.offsets[block] as usize;
+        let kind = other.kinds[block];
+        match kind {
+            BITMAP => {
+                self.offsets.push((self.bitmaps.len() / BITMAP_WORDS) as u64);
+                self.bitmaps.extend_from_slice(&other.bitmaps[offset * BITMAP_WORDS ..][.. BITMAP_WORDS]);
+            },
+            ARRAY => {
+                self.offsets.push(self.arrays.len() as u64);
+                self.arrays.extend_from_self(other.arrays, offset .. offset + 1);
+            },
+            _ => {
+                self.offsets.push(self.runs.len() as u64);
+                self.runs.extend_from_self(other.runs, offset .. offset + 1);
+            },
+        }
+        self.kinds.push(kind);
+        self.counts.push(self.counts.last().copied().unwrap_or(0) + count);
+        let bits = core::cmp::min(BLOCK_BITS, other.len() - block * BLOCK_BITS);
+        self.len += bits as u64;
+    }
+}
+
+impl<'a> RoaringBits<&'a [u8], &'a [u64], &'a [u64], &'a [u64], Vecs<&'a [u16], &'a [u64]>, Vecs<&'a [[u16; 2]], &'a [u64]>, &'a u64> {
+    /// The number of set bits.
+    #[inline(always)]
+    pub fn ones(&self) -> usize {
+        self.counts.last().copied().unwrap_or(0) as usize
+    }
+
+    /// The number of set bits *strictly* preceding `index`.
+    ///
+    /// The `index` may be as large as `self.len()`, in which case the result is `self.ones()`.
+    pub fn rank(&self, index: usize) -> usize {
+        if index >= self.len() { return self.ones(); }
+        let block = index / BLOCK_BITS;
+        let pos = index % BLOCK_BITS;
+        let prior = if block > 0 { self.counts[block - 1] as usize } else { 0 };
+        let offset = self.offsets[block] as usize;
+        let within = match self.kinds[block] {
+            BITMAP => {
+                let words = &self.bitmaps[offset * BITMAP_WORDS ..][.. BITMAP_WORDS];
+                let whole: usize = words[.. pos / 64].iter().map(|w| w.count_ones() as usize).sum();
+                whole + (words[pos / 64] & ((1 << (pos % 64)) - 1)).count_ones() as usize
+            },
+            ARRAY => self.array(offset).partition_point(|&p| (p as usize) < pos),
+            _ => {
+                self.run(offset)
+                    .iter()
+                    .take_while(|run| (run[0] as usize) < pos)
+                    .map(|run| core::cmp::min(run[1] as usize + 1, pos) - run[0] as usize)
+                    .sum()
+            },
+        };
+        prior + within
+    }
+
+    /// The index of the set bit preceded by exactly `rank` set bits, should one exist.
+    ///
+    /// This is the inverse of `rank`, in that `self.rank(self.select(r).unwrap()) == r`.
+    pub fn select(&self, rank: usize) -> Option<usize> {
+        let block = self.counts.partition_point(|&c| c as usize <= rank);
+        if block == self.counts.len() { return None; }
+        let mut remaining = rank - if block > 0 { self.counts[block - 1] as usize } else { 0 };
+        let offset = self.offsets[block] as usize;
+        let pos = match self.kinds[block] {
+            BITMAP => {
+                let words = &self.bitmaps[offset * BITMAP_WORDS ..][.. BITMAP_WORDS];
+                let mut pos = 0;
+                for (index, word) in words.iter().enumerate() {
+                    let ones = word.count_ones() as usize;
+                    if remaining < ones {
+                        let mut word = *word;
+                        for _ in 0 .. remaining { word &= word - 1; }
+                        pos = 64 * index + word.trailing_zeros() as usize;
+                        break;
+                    }
+                    remaining -= ones;
+                }
+                pos
+            },
+            ARRAY => self.array(offset)[remaining] as usize,
+            _ => {
+                let mut pos = 0;
+                for run in self.run(offset) {
+                    let length = (run[1] - run[0]) as usize + 1;
+                    if remaining < length {
+                        pos = run[0] as usize + remaining;
+                        break;
+                    }
+                    remaining -= length;
+                }
+                pos
+            },
+        };
+        Some(block * BLOCK_BITS + pos)
+    }
+
+    /// The set positions of array block `offset`.
+    #[inline(always)]
+    fn array(&self, offset: usize) -> &'a [u16] {
+        let lower = if offset == 0 { 0 } else { self.arrays.bounds[offset - 1] as usize };
+        let upper = self.arrays.bounds[offset] as usize;
+        &self.arrays.values[lower .. upper]
+    }
+    /// The runs of run block `offset`.
+    #[inline(always)]
+    fn run(&self, offset: usize) -> &'a [[u16; 2]] {
+        let lower = if offset == 0 { 0 } else { self.runs.bounds[offset - 1] as usize };
+        let upper = self.runs.bounds[offset] as usize;
+        &self.runs.values[lower .. upper]
+    }
+    /// Calls `action` on each set position within `block`, in increasing order.
+    fn for_each_one(&self, block: usize, mut action: impl FnMut(u16)) {
+        let offset = self.offsets[block] as usize;
+        match self.kinds[block] {
+            BITMAP => {
+                let words = &self.bitmaps[offset * BITMAP_WORDS ..][.. BITMAP_WORDS];
+                for (index, word) in words.iter().enumerate() {
+                    let mut word = *word;
+                    while word != 0 {
+                        action((64 * index) as u16 + word.trailing_zeros() as u16);
+                        word &= word - 1;
+                    }
+                }
+            },
+            ARRAY => { for &pos in self.array(offset) { action(pos); } },
+            _ => { for run in self.run(offset) { for pos in run[0] ..= run[1] { action(pos); } } },
+        }
+    }
+}
+
+impl<KC, OC, CC, MC, AC, RC, LC: CopyAs<u64>> Len for RoaringBits<KC, OC, CC, MC, AC, RC, LC> {
+    #[inline(always)] fn len(&self) -> usize { self.len.copy_as() as usize }
+}
+
+impl<'a> crate::Index for RoaringBits<&'a [u8], &'a [u64], &'a [u64], &'a [u64], Vecs<&'a [u16], &'a [u64]>, Vecs<&'a [[u16; 2]], &'a [u64]>, &'a u64> {
+    type Ref = bool;
+    #[inline]
+    fn get(&self, index: usize) -> Self::Ref {
+        let block = index / BLOCK_BITS;
+        let pos = index % BLOCK_BITS;
+        let offset = self.offsets[block] as usize;
+        match self.kinds[block] {
+            BITMAP => (self.bitmaps[offset * BITMAP_WORDS + pos / 64] >> (pos % 64)) & 1 == 1,
+            ARRAY => self.array(offset).binary_search(&(pos as u16)).is_ok(),
+            _ => {
+                let runs = self.run(offset);
+                let after = runs.partition_point(|run| run[0] as usize <= pos);
+                after > 0 && pos <= runs[after - 1][1] as usize
+            },
+        }
+    }
+}
+impl crate::Index for &RoaringBits {
+    type Ref = bool;
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref { crate::Index::get(&self.borrow(), index) }
+}
+
+impl Push<bool> for RoaringBits {
+    #[inline]
+    fn push(&mut self, bit: bool) {
+        let pos = self.len as usize % BLOCK_BITS;
+        if pos == 0 {
+            if !self.kinds.is_empty() { self.seal_last(); }
+            // Open a new block as an empty array.
+            self.kinds.push(ARRAY);
+            self.offsets.push(self.arrays.len() as u64);
+            self.arrays.bounds.push(self.arrays.values.len() as u64);
+            self.counts.push(self.counts.last().copied().unwrap_or(0));
+        }
+        if bit {
+            self.set_last(pos as u16);
+            *self.counts.last_mut().unwrap() += 1;
+        }
+        self.len += 1;
+    }
+}
+impl<'a> Push<&'a bool> for RoaringBits {
+    #[inline(always)]
+    fn push(&mut self, bit: &'a bool) { self.push(*bit) }
+}
+
+impl Clear for RoaringBits {
+    #[inline(always)]
+    fn clear(&mut self) {
+        self.kinds.clear();
+        self.offsets.clear();
+        self.counts.clear();
+        self.bitmaps.clear();
+        self.arrays.clear();
+        self.runs.clear();
+        self.len = 0;
+    }
+}
+
+impl<'a> crate::AsBytes<'a> for RoaringBits<&'a [u8], &'a [u64], &'a [u64], &'a [u64], Vecs<&'a [u16], &'a [u64]>, Vecs<&'a [[u16; 2]], &'a [u64]>, &'a u64> {
+    const SLICE_COUNT: usize = 9;
+    #[inline]
+    fn get_byte_slice(&self, index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        match index {
+            0 => self.kinds.get_byte_slice(0),
+            1 => self.offsets.get_byte_slice(0),
+            2 => self.counts.get_byte_slice(0),
+            3 => self.bitmaps.get_byte_slice(0),
+            4 | 5 => self.arrays.get_byte_slice(index - 4),
+            6 | 7 => self.runs.get_byte_slice(index - 6),
+            _ => (8, bytemuck::cast_slice(core::slice::from_ref(self.len))),
+        }
+    }
+}
+impl<KC: crate::schema::Schema, OC: crate::schema::Schema, CC: crate::schema::Schema, MC: crate::schema::Schema, AC: crate::schema::Schema, RC: crate::schema::Schema, LC> crate::schema::Schema for RoaringBits<KC, OC, CC, MC, AC, RC, LC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("RoaringBits", [
+            ("kinds", KC::schema()),
+            ("offsets", OC::schema()),
+            ("counts", CC::schema()),
+            ("bitmaps", MC::schema()),
+            ("arrays", AC::schema()),
+            ("runs", RC::schema()),
+            ("len", crate::schema::Node::slice::<u64>()),
+        ])
+    }
+}
+impl<'a> crate::FromBytes<'a> for RoaringBits<&'a [u8], &'a [u64], &'a [u64], &'a [u64], Vecs<&'a [u16], &'a [u64]>, Vecs<&'a [[u16; 2]], &'a [u64]>, &'a u64> {
+    const SLICE_COUNT: usize = 9;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            kinds: crate::FromBytes::from_bytes(bytes),
+            offsets: crate::FromBytes::from_bytes(bytes),
+            counts: crate::FromBytes::from_bytes(bytes),
+            bitmaps: crate::FromBytes::from_bytes(bytes),
+            arrays: crate::FromBytes::from_bytes(bytes),
+            runs: crate::FromBytes::from_bytes(bytes),
+            len: &bytemuck::try_cast_slice(bytes.next().expect("Iterator exhausted prematurely")).unwrap()[0],
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        let kinds = crate::FromBytes::from_store(store, offset);
+        let offsets = crate::FromBytes::from_store(store, offset);
+        let counts = crate::FromBytes::from_store(store, offset);
+        let bitmaps = crate::FromBytes::from_store(store, offset);
+        let arrays = crate::FromBytes::from_store(store, offset);
+        let runs = crate::FromBytes::from_store(store, offset);
+        let (w, _) = store.get(*offset); *offset += 1;
+        debug_assert!(!w.is_empty(), "RoaringBits::from_store: empty len slice");
+        Self { kinds, offsets, counts, bitmaps, arrays, runs, len: w.first().unwrap_or(&0) }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        sizes.extend_from_slice(&[1, 8, 8, 8, 8, 2, 8, 4, 8]);
+        Ok(())
+    }
+    /// Validates element sizes, and that the blocks are consistent with each other and their contents.
+    ///
+    /// The checks ensure that indexing, `rank`, and `select` will not panic; they do not check
+    /// that arrays and runs are sorted, which would only produce incorrect answers.
+    fn validate(slices: &[(&[u64], u8)]) -> Result<(), String> {
+        if slices.len() < Self::SLICE_COUNT {
+            return Err(format!("RoaringBits: expected {} slices but got {}", Self::SLICE_COUNT, slices.len()));
+        }
+        let mut sizes = Vec::new();
+        Self::element_sizes(&mut sizes)?;
+        for (i, (elem_size, (words, tail))) in sizes.iter().zip(slices).enumerate() {
+            let byte_len = words.len() * 8 - ((8 - *tail as usize) % 8);
+            if byte_len % elem_size != 0 {
+                return Err(format!("RoaringBits: slice {} has {} bytes, not a multiple of element size {}", i, byte_len, elem_size));
+            }
+        }
+        if slices[8].0.is_empty() {
+            return Err("RoaringBits: len slice must be non-empty".into());
+        }
+        // Reconstruct with the lifetime of `slices`, rather than `'a`.
+        let this = <RoaringBits as Borrow>::Borrowed::from_bytes(&mut slices.iter().map(|(words, tail)| {
+            let bytes: &[u8] = bytemuck::cast_slice(words);
+            &bytes[.. bytes.len() - ((8 - *tail as usize) % 8)]
+        }));
+        this.validate_deep().map(|_| ())
+    }
+    /// Validates that the blocks are consistent with each other and their contents.
+    fn validate_deep(&self) -> Result<usize, String> {
+        let blocks = self.len().div_ceil(BLOCK_BITS);
+        if self.kinds.len() != blocks || self.offsets.len() != blocks || self.counts.len() != blocks {
+            return Err(format!("RoaringBits: {} bits require {} blocks, but found {} kinds, {} offsets, and {} counts", self.len(), blocks, self.kinds.len(), self.offsets.len(), self.counts.len()));
+        }
+        if self.bitmaps.len() % BITMAP_WORDS != 0 {
+            return Err(format!("RoaringBits: {} bitmap words is not a multiple of {}", self.bitmaps.len(), BITMAP_WORDS));
+        }
+        for (name, bounds, values) in [("arrays", self.arrays.bounds, self.arrays.values.len()), ("runs", self.runs.bounds, self.runs.values.len())] {
+            if bounds.windows(2).any(|w| w[0] > w[1]) || bounds.last().is_some_and(|&b| b as usize > values) {
+                return Err(format!("RoaringBits: {} bounds are not increasing and within {} values", name, values));
+            }
+        }
+        let mut prior = 0;
+        for block in 0 .. blocks {
+            let offset = self.offsets[block] as usize;
+            let limit = match self.kinds[block] {
+                BITMAP => self.bitmaps.len() / BITMAP_WORDS,
+                ARRAY => self.arrays.len(),
+                RUN => self.runs.len(),
+                kind => return Err(format!("RoaringBits: block {} has unknown kind {}", block, kind)),
+            };
+            if offset >= limit {
+                return Err(format!("RoaringBits: block {} has offset {} but its kind has {} blocks", block, offset, limit));
+            }
+            let count = self.counts[block];
+            if count < prior || count - prior > BLOCK_BITS as u64 {
+                return Err(format!("RoaringBits: block {} has cumulative count {} after {}", block, count, prior));
+            }
+            if self.kinds[block] == ARRAY && self.array(offset).len() as u64 != count - prior {
+                return Err(format!("RoaringBits: array block {} has {} entries but count {}", block, self.array(offset).len(), count - prior));
+            }
+            if self.kinds[block] == RUN && self.run(offset).iter().any(|run| run[0] > run[1]) {
+                return Err(format!("RoaringBits: run block {} has a run that ends before it starts", block));
+            }
+            prior = count;
+        }
+        Ok(Len::len(self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use crate::{Borrow, Container, Index, Len, Push, AsBytes, FromBytes};
+    use super::{RoaringBits, BLOCK_BITS, BITMAP, ARRAY, RUN};
+
+    /// Bits that are sparse in the first block, dense in the second, runs in the third, and partial in the fourth.
+    fn bits() -> Vec<bool> {
+        (0 .. BLOCK_BITS).map(|i| i % 1000 == 7)
+            .chain((0 .. BLOCK_BITS).map(|i| i % 3 != 0))
+            .chain((0 .. BLOCK_BITS).map(|i| (i / 5000) % 2 == 1))
+            .chain((0 .. 1234).map(|i| i % 2 == 0))
+            .collect()
+    }
+
+    #[test]
+    fn push_and_index() {
+        let bits = bits();
+        let mut roaring = RoaringBits::default();
+        for bit in bits.iter() { roaring.push(bit); }
+
+        assert_eq!(roaring.len(), bits.len());
+        assert_eq!(&roaring.kinds[..], &[ARRAY, BITMAP, RUN, ARRAY]);
+        let borrowed = roaring.borrow();
+        for (index, bit) in bits.iter().enumerate() {
+            assert_eq!(borrowed.get(index), *bit);
+            assert_eq!((&roaring).get(index), *bit);
+        }
+    }
+
+    #[test]
+    fn rank_select() {
+        let bits = bits();
+        let roaring = {
+            let mut roaring = RoaringBits::default();
+            roaring.extend(bits.iter().copied());
+            roaring
+        };
+        assert_eq!(roaring.ones(), bits.iter().filter(|b| **b).count());
+
+        let mut rank = 0;
+        for (index, bit) in bits.iter().enumerate() {
+            assert_eq!(roaring.rank(index), rank);
+            if *bit {
+                assert_eq!(roaring.select(rank), Some(index));
+                rank += 1;
+            }
+        }
+        assert_eq!(roaring.rank(bits.len()), rank);
+        assert_eq!(roaring.select(rank), None);
+    }
+
+    #[test]
+    fn extend_from_self() {
+        let bits = bits();
+        let mut roaring = RoaringBits::default();
+        roaring.extend(bits.iter().copied());
+
+        // Aligned ranges copy blocks; unaligned ranges push bits.
+        for range in [0 .. bits.len(), BLOCK_BITS .. 3 * BLOCK_BITS, 5 .. 2 * BLOCK_BITS + 17, 2 * BLOCK_BITS .. bits.len()] {
+            let mut copy = RoaringBits::default();
+            copy.extend_from_self(roaring.borrow(), range.clone());
+            assert_eq!(copy.len(), range.len());
+            for (index, bit) in bits[range.clone()].iter().enumerate() {
+                assert_eq!((&copy).get(index), *bit);
+            }
+            assert_eq!(copy.ones(), bits[range].iter().filter(|b| **b).count());
+        }
+    }
+
+    #[test]
+    fn round_trip_bytes() {
+        let bits = bits();
+        let mut roaring = RoaringBits::default();
+        roaring.extend(bits.iter().copied());
+
+        let mut store = Vec::new();
+        crate::bytes::indexed::encode(&mut store, &roaring.borrow());
+        crate::bytes::indexed::validate::<<RoaringBits as Borrow>::Borrowed<'_>>(&store).unwrap();
+
+        let borrowed = roaring.borrow();
+        let decoded = <RoaringBits as Borrow>::Borrowed::from_bytes(&mut borrowed.as_bytes().map(|(_, bytes)| bytes));
+        assert_eq!(decoded, borrowed);
+        let ds = crate::bytes::indexed::DecodedStore::new(&store);
+        let stored = <RoaringBits as Borrow>::Borrowed::from_store(&ds, &mut 0);
+        assert_eq!(stored, borrowed);
+        assert_eq!(stored.select(100), roaring.select(100));
+    }
+
+    #[test]
+    fn validate_rejects_bad_blocks() {
+        let mut roaring = RoaringBits::default();
+        roaring.extend((0 .. 100_000).map(|i| i % 7 == 0));
+
+        let mut bad = roaring.clone();
+        bad.kinds[0] = 3;
+        let mut store = Vec::new();
+        crate::bytes::indexed::encode(&mut store, &bad.borrow());
+        assert!(crate::bytes::indexed::validate::<<RoaringBits as Borrow>::Borrowed<'_>>(&store).is_err());
+
+        let mut bad = roaring.clone();
+        bad.len += BLOCK_BITS as u64;
+        let mut store = Vec::new();
+        crate::bytes::indexed::encode(&mut store, &bad.borrow());
+        assert!(crate::bytes::indexed::validate::<<RoaringBits as Borrow>::Borrowed<'_>>(&store).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! A container that stores runs of equal adjacent values once, with the position each run ends.
+//!
+//! This is more efficient than a list of `T` when values repeat in long runs, as with sorted
+//! columns or slowly changing values like timestamps at coarse granularity. Unlike `Repeats`,
+//! which spends bits on every element, the cost is proportional to the number of runs. Access
+//! to an element requires a binary search over the run ends.
+use alloc::{vec::Vec, string::String};
+
+use crate::{Push, Index, Len, Clear, Borrow, Container, IndexAs};
+
+/// A container that encodes runs of equal adjacent values as the value and where the run ends.
+///
+/// The `values` container holds the value of each run, and adjacent runs have distinct values.
+/// The `ends` container holds for each run the number of elements through the end of the run.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Runs<TC, EC = Vec<u64>> {
+    /// The value of each run.
+    pub values: TC,
+    /// The cumulative number of elements at the end of each run.
+    pub ends: EC,
+}
+
+impl<TC, EC: IndexAs<u64> + Len> Runs<TC, EC> {
+    /// The number of runs.
+    #[inline(always)] pub fn runs(&self) -> usize { self.ends.len() }
+    /// The run containing the element at `index`.
+    #[inline]
+    pub fn run(&self, index: usize) -> usize {
+        let (mut lower, mut upper) = (0, self.ends.len());
+        while lower < upper {
+            let middle = lower + (upper - lower) / 2;
+            if self.ends.index_as(middle) as usize <= index { lower = middle + 1; }
+            else { upper = middle; }
+        }
+        lower
+    }
+    /// The range of elements in the run `run`.
+    #[inline(always)]
+    pub fn run_range(&self, run: usize) -> core::ops::Range<usize> {
+        let lower = if run == 0 { 0 } else { self.ends.index_as(run - 1) as usize };
+        lower .. self.ends.index_as(run) as usize
+    }
+}
+
+impl<TC: Len> Runs<TC> {
+    /// Pushes `count` copies of `item`, extending the last run if its value equals `item`.
+    #[inline]
+    pub fn push_run<T>(&mut self, item: T, count: usize)
+    where
+        TC: Push<T>,
+        for<'a> &'a TC: Index,
+        for<'a> <&'a TC as Index>::Ref : PartialEq<T>,
+    {
+        if count > 0 {
+            let len = self.len() as u64;
+            if (&self.values).last().map(|x| x.eq(&item)) == Some(true) {
+                let last = self.ends.len() - 1;
+                self.ends[last] = len + count as u64;
+            } else {
+                self.values.push(item);
+                self.ends.push(len + count as u64);
+            }
+        }
+    }
+}
+
+impl<T, TC: Push<T> + Len> Push<T> for Runs<TC>
+where
+    for<'a> &'a TC: Index,
+    for<'a> <&'a TC as Index>::Ref : PartialEq<T>,
+{
+    #[inline(always)]
+    fn push(&mut self, item: T) { self.push_run(item, 1) }
+}
+
+impl<TC, EC: IndexAs<u64> + Len> Len for Runs<TC, EC> {
+    #[inline(always)] fn len(&self) -> usize {
+        if self.ends.is_empty() { 0 } else { self.ends.index_as(self.ends.len() - 1) as usize }
+    }
+}
+
+impl<TC: Index, EC: IndexAs<u64> + Len> Index for Runs<TC, EC> {
+    type Ref = TC::Ref;
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref {
+        self.values.get(self.run(index))
+    }
+}
+
+impl<'a, TC> Index for &'a Runs<TC>
+where
+    &'a TC: Index,
+{
+    type Ref = <&'a TC as Index>::Ref;
+    #[inline(always)] fn get(&self, index: usize) -> Self::Ref {
+        (&self.values).get(self.run(index))
+    }
+}
+
+impl<TC: Borrow> Borrow for Runs<TC> {
+    type Ref<'a> = TC::Ref<'a> where TC: 'a;
+    type Borrowed<'a> = Runs<TC::Borrowed<'a>, &'a [u64]> where TC: 'a;
+    #[inline(always)]
+    fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
+        Runs {
+            values: self.values.borrow(),
+            ends: &self.ends[..],
+        }
+    }
+    #[inline(always)]
+    fn reborrow<'b, 'a: 'b>(thing: Self::Borrowed<'a>) -> Self::Borrowed<'b> where TC: 'a {
+        Runs {
+            values: TC::reborrow(thing.values),
+            ends: thing.ends,
+        }
+    }
+    #[inline(always)]
+    fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> where Self: 'a {
+        TC::reborrow_ref(thing)
+    }
+}
+
+impl<TC: Container + Len> Container for Runs<TC>
+where
+    for<'a> &'a TC: Index,
+    for<'a, 'b> <&'a TC as Index>::Ref: PartialEq<TC::Ref<'b>>,
+{
+    fn extend_from_self(&mut self, other: Self::Borrowed<'_>, range: core::ops::Range<usize>) {
+        if !range.is_empty() {
+            // The first run may merge with our last run, and may be only partly in `range`.
+            let first = other.run(range.start);
+            let last = other.run(range.end - 1);
+            let first_end = core::cmp::min(other.run_range(first).end, range.end);
+            self.push_run(other.values.get(first), first_end - range.start);
+            // Subsequent runs are distinct from their predecessors, and can be copied in bulk.
+            if first < last {
+                // Ends are rebased from `first_end` in `other` to our length, which may be smaller.
+                let base = self.len() as u64;
+                self.values.extend_from_self(other.values, first + 1 .. last + 1);
+                for run in first + 1 .. last {
+                    self.ends.push(other.ends[run] - first_end as u64 + base);
+                }
+                self.ends.push((range.end - first_end) as u64 + base);
+            }
+        }
+    }
+
+    fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+        self.values.reserve_for(selves.clone().map(|x| x.values));
+        self.ends.reserve_for(selves.map(|x| x.ends));
+    }
+}
+
+impl<TC: Clear> Clear for Runs<TC> {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.ends.clear();
+    }
+}
+
+impl<'a, TC: crate::AsBytes<'a>, EC: crate::AsBytes<'a>> crate::AsBytes<'a> for Runs<TC, EC> {
+    const SLICE_COUNT: usize = TC::SLICE_COUNT + EC::SLICE_COUNT;
+    #[inline]
+    fn get_byte_slice(&self, index: usize) -> (u64, &'a [u8]) {
+        debug_assert!(index < Self::SLICE_COUNT);
+        if index < TC::SLICE_COUNT {
+            self.values.get_byte_slice(index)
+        } else {
+            self.ends.get_byte_slice(index - TC::SLICE_COUNT)
+        }
+    }
+}
+
+impl<TC: crate::schema::Schema, EC: crate::schema::Schema> crate::schema::Schema for Runs<TC, EC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Runs", [("values", TC::schema()), ("ends", EC::schema())])
+    }
+}
+
+impl<'a, TC: crate::FromBytes<'a>, EC: crate::FromBytes<'a> + IndexAs<u64>> crate::FromBytes<'a> for Runs<TC, EC> {
+    const SLICE_COUNT: usize = TC::SLICE_COUNT + EC::SLICE_COUNT;
+    #[inline(always)]
+    fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
+        Self {
+            values: crate::FromBytes::from_bytes(bytes),
+            ends: crate::FromBytes::from_bytes(bytes),
+        }
+    }
+    #[inline(always)]
+    fn from_store(store: &crate::bytes::indexed::DecodedStore<'a>, offset: &mut usize) -> Self {
+        Self {
+            values: TC::from_store(store, offset),
+            ends: EC::from_store(store, offset),
+        }
+    }
+    fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
+        TC::element_sizes(sizes)?;
+        EC::element_sizes(sizes)?;
+        Ok(())
+    }
+    fn validate_deep(&self) -> Result<usize, String> {
+        let runs = self.ends.validate_deep()?;
+        let values = self.values.validate_deep()?;
+        if values != runs {
+            return Err(format!("Runs: {} ends for {} values", runs, values));
+        }
+        let len = if runs == 0 { 0 } else { self.ends.index_as(runs - 1) };
+        let len = usize::try_from(len).map_err(|_| format!("Runs: length {} does not fit in `usize`", len))?;
+        crate::common::validate_bounds("Runs", &self.ends, runs, len)?;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use alloc::vec::Vec;
+    use crate::common::{Push, Index, Len, Clear};
+    use crate::{Borrow, Container, Strings};
+    use crate::bytes::stash::Stash;
+    use super::Runs;
+
+    fn values() -> Vec<u64> {
+        (0 .. 1000u64).map(|i| (i / 100) % 3).collect()
+    }
+
+    fn runs() -> Runs<Vec<u64>> {
+        let mut runs: Runs<Vec<u64>> = Default::default();
+        for value in values() { runs.push(&value); }
+        runs
+    }
+
+    #[test]
+    fn push_and_index() {
+        let runs = runs();
+        assert_eq!(runs.len(), 1000);
+        assert_eq!(runs.runs(), 10);
+        for (index, value) in values().iter().enumerate() {
+            assert_eq!(runs.get(index), *value);
+            assert_eq!(runs.borrow().get(index), value);
+        }
+        assert_eq!(runs.run(250), 2);
+        assert_eq!(runs.run_range(2), 200 .. 300);
+
+        let mut strings: Runs<Strings> = Default::default();
+        for word in ["a", "a", "b", "a", "a", "a"] { strings.push(word.as_bytes()); }
+        assert_eq!(strings.runs(), 3);
+        assert_eq!(strings.ends, [2, 3, 6]);
+        assert_eq!(strings.borrow().get(4), b"a");
+    }
+
+    #[test]
+    fn extend_and_clear() {
+        let source = runs();
+        let values = values();
+
+        // Ranges that start and end within runs, and that merge with the prior run.
+        let mut other: Runs<Vec<u64>> = Default::default();
+        other.push(&2);
+        other.extend_from_self(source.borrow(), 250 .. 650);
+        other.extend_from_self(source.borrow(), 620 .. 630);
+        other.extend_from_self(source.borrow(), 0 .. 0);
+        assert_eq!(other.len(), 411);
+        assert_eq!(other.runs(), 5);
+        let mut expected = vec![2];
+        expected.extend_from_slice(&values[250 .. 650]);
+        expected.extend_from_slice(&values[620 .. 630]);
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(other.borrow().get(index), value);
+        }
+
+        // Gathering uses `extend_from_self` and so also coalesces runs.
+        let mut gathered: Runs<Vec<u64>> = Default::default();
+        gathered.gather(source.borrow(), &[0, 1, 2, 150, 151, 999]);
+        assert_eq!(gathered.ends, [3, 5, 6]);
+
+        other.clear();
+        assert_eq!(other.len(), 0);
+        assert_eq!(other.runs(), 0);
+    }
+
+    #[test]
+    fn extend_into_shorter() {
+        // The target holds fewer elements than the range starts at, which must not underflow;
+        // run with `CARGO_PROFILE_TEST_OVERFLOW_CHECKS=true` to check under overflow checks.
+        let source = runs();
+        let values = values();
+        let mut other: Runs<Vec<u64>> = Default::default();
+        other.extend_from_self(source.borrow(), 850 .. 1000);
+        assert_eq!(other.len(), 150);
+        assert_eq!(other.ends, [50, 150]);
+        for (index, value) in values[850 .. 1000].iter().enumerate() {
+            assert_eq!(other.borrow().get(index), value);
+        }
+    }
+
+    #[test]
+    fn round_trip_stash() {
+        let runs = runs();
+        let mut bytes: Vec<u8> = Vec::new();
+        crate::bytes::indexed::write(&mut bytes, &runs.borrow()).unwrap();
+        let stash: Stash<Runs<Vec<u64>>, Vec<u8>> = Stash::try_from_bytes(bytes).unwrap();
+        assert_eq!(stash.borrow(), runs.borrow());
+        assert_eq!(stash.borrow().get(555), &2);
+    }
+}
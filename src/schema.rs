@@ -0,0 +1,262 @@
+//! Runtime descriptions of what the byte slices of a container mean.
+//!
+//! The `AsBytes` and `FromBytes` traits present a container as a number of byte slices, but not
+//! what each slice holds. A [`Schema`] describes a container as a tree whose leaves are its slices,
+//! in order, with the names of the fields, variants, and container parts that lead to each slice,
+//! and the type of its elements. This allows tools to print, compare, and check encoded data.
+//!
+//! The schema describes the layout of the borrowed container, which both owned and borrowed forms
+//! share; for example, `Vecs<Vec<u8>>` and `Vecs<&[u8], &[u64]>` have the same schema.
+use alloc::{vec::Vec, string::String, boxed::Box};
+
+/// A container whose byte slices can be described at runtime.
+pub trait Schema {
+    /// A description of the slices of the container, in the order presented by `AsBytes`.
+    fn schema() -> Node;
+}
+
+/// A description of a container, as a tree whose leaves are its byte slices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    /// A byte slice of elements, by the name and size in bytes of the element type.
+    Slice(&'static str, usize),
+    /// A container type, whose slices are those of its named parts in order.
+    Container(&'static str, Vec<(&'static str, Node)>),
+    /// A struct, whose slices are those of its named fields in order.
+    ///
+    /// The fields of tuple structs are named by their position.
+    Struct(&'static str, Vec<(&'static str, Node)>),
+    /// A tuple, whose slices are those of its fields in order.
+    Tuple(Vec<Node>),
+    /// An enum, whose slices are those of its named variants in order, followed by those of
+    /// the discriminant that records the variant of each element.
+    Enum(&'static str, Vec<(&'static str, Node)>, Box<Node>),
+}
+
+impl Node {
+    /// A byte slice of `T` elements.
+    pub fn slice<T>() -> Self { Node::Slice(core::any::type_name::<T>(), core::mem::size_of::<T>()) }
+    /// A container type with named parts.
+    pub fn container(name: &'static str, parts: impl IntoIterator<Item = (&'static str, Node)>) -> Self {
+        Node::Container(name, parts.into_iter().collect())
+    }
+    /// A struct with named fields.
+    pub fn structure(name: &'static str, fields: impl IntoIterator<Item = (&'static str, Node)>) -> Self {
+        Node::Struct(name, fields.into_iter().collect())
+    }
+    /// A tuple with unnamed fields.
+    pub fn tuple(fields: impl IntoIterator<Item = Node>) -> Self {
+        Node::Tuple(fields.into_iter().collect())
+    }
+    /// An enum with named variants, and the discriminant that follows them.
+    pub fn enumeration(name: &'static str, variants: impl IntoIterator<Item = (&'static str, Node)>, discriminant: Node) -> Self {
+        Node::Enum(name, variants.into_iter().collect(), Box::new(discriminant))
+    }
+
+    /// The number of byte slices, which should equal the `SLICE_COUNT` of the borrowed container.
+    pub fn slice_count(&self) -> usize {
+        match self {
+            Node::Slice(..) => 1,
+            Node::Container(_, parts) | Node::Struct(_, parts) => parts.iter().map(|(_, node)| node.slice_count()).sum(),
+            Node::Tuple(fields) => fields.iter().map(Node::slice_count).sum(),
+            Node::Enum(_, variants, discriminant) => variants.iter().map(|(_, node)| node.slice_count()).sum::<usize>() + discriminant.slice_count(),
+        }
+    }
+
+    /// For each byte slice in order, its path of names from the root, the element type, and the element size.
+    ///
+    /// Path components are separated by `.`, with tuple fields named by position and enum
+    /// discriminants named `indexes`. The root itself contributes no component.
+    pub fn slices(&self) -> Vec<(String, &'static str, usize)> {
+        let mut slices = Vec::new();
+        self.collect_slices(String::new(), &mut slices);
+        slices
+    }
+
+    fn collect_slices(&self, path: String, slices: &mut Vec<(String, &'static str, usize)>) {
+        let join = |name: &str| if path.is_empty() { String::from(name) } else { format!("{}.{}", path, name) };
+        match self {
+            Node::Slice(element, size) => slices.push((path, element, *size)),
+            Node::Container(_, parts) | Node::Struct(_, parts) => {
+                for (name, node) in parts.iter() { node.collect_slices(join(name), slices); }
+            },
+            Node::Tuple(fields) => {
+                for (index, node) in fields.iter().enumerate() { node.collect_slices(join(&format!("{}", index)), slices); }
+            },
+            Node::Enum(_, variants, discriminant) => {
+                for (name, node) in variants.iter() { node.collect_slices(join(name), slices); }
+                discriminant.collect_slices(join("indexes"), slices);
+            },
+        }
+    }
+
+    /// Describes the first slice at which `self` and `other` differ, or returns `None` if they agree.
+    ///
+    /// Slices are compared by their paths and element types, which also reveals renamed fields and variants.
+    pub fn diff(&self, other: &Node) -> Option<String> {
+        let (these, those) = (self.slices(), other.slices());
+        for (index, (this, that)) in these.iter().zip(those.iter()).enumerate() {
+            if this != that {
+                return Some(format!("slice {}: `{}` ({}) versus `{}` ({})", index, this.0, this.1, that.0, that.1));
+            }
+        }
+        if these.len() != those.len() {
+            return Some(format!("{} slices versus {} slices", these.len(), those.len()));
+        }
+        None
+    }
+
+    /// Checks that `store` is an [`indexed`](crate::bytes::indexed) encoding with the slices of `self`.
+    ///
+    /// This performs the structural and element size checks of [`indexed::validate`](crate::bytes::indexed::validate),
+    /// but reports errors by the path and element type of the slice at fault.
+    pub fn validate(&self, store: &[u64]) -> Result<(), String> {
+        let slices = self.slices();
+        crate::bytes::indexed::validate_structure(store, slices.len())?;
+        let decoded = crate::bytes::indexed::DecodedStore::new(store);
+        for (index, (path, element, size)) in slices.iter().enumerate() {
+            let (words, tail) = decoded.get(index);
+            let bytes = words.len() * 8 - ((8 - tail as usize) % 8);
+            if *size > 0 && bytes % size != 0 {
+                return Err(format!("slice {} (`{}` of {}) has {} bytes, not a multiple of {}", index, path, element, bytes, size));
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_indent(&self, f: &mut core::fmt::Formatter<'_>, indent: usize) -> core::fmt::Result {
+        let parts = |f: &mut core::fmt::Formatter<'_>, parts: &[(&'static str, Node)]| -> core::fmt::Result {
+            for (name, node) in parts.iter() {
+                write!(f, "\n{:indent$}{}: ", "", name, indent = indent + 2)?;
+                node.fmt_indent(f, indent + 2)?;
+            }
+            Ok(())
+        };
+        match self {
+            Node::Slice(element, _) => write!(f, "[{}]", element),
+            Node::Container(name, fields) | Node::Struct(name, fields) => {
+                write!(f, "{}", name)?;
+                parts(f, fields)
+            },
+            Node::Tuple(fields) => {
+                write!(f, "tuple")?;
+                for (index, node) in fields.iter().enumerate() {
+                    write!(f, "\n{:indent$}{}: ", "", index, indent = indent + 2)?;
+                    node.fmt_indent(f, indent + 2)?;
+                }
+                Ok(())
+            },
+            Node::Enum(name, variants, discriminant) => {
+                write!(f, "{}", name)?;
+                parts(f, variants)?;
+                write!(f, "\n{:indent$}indexes: ", "", indent = indent + 2)?;
+                discriminant.fmt_indent(f, indent + 2)
+            },
+        }
+    }
+}
+
+/// A type whose encodings remain readable as fields are added to and removed from it.
+///
+/// Data written by [`versioned::write`](crate::bytes::versioned::write) record the version of the
+/// type and the path and element size of each slice. When [read](crate::bytes::versioned::read)
+/// as a later version of the type, slices of removed fields are skipped, and fields absent from the
+/// data are presented as columns of default values, if [`default_field`](Self::default_field) allows.
+///
+/// The derived implementation for structs allows fields marked `#[columnar(default)]` to be absent
+/// from any data, and fields marked `#[columnar(added_in = N)]` to be absent from data written by
+/// versions before `N`. The version of the type is the largest such `N`.
+pub trait Evolve: crate::Columnar {
+    /// The version of the type, recorded with the data it writes.
+    const VERSION: u64 = 0;
+    /// The `indexed` encoding of `len` default values for `field`, if it may be absent from data written at `version`.
+    ///
+    /// The field is named by the first component of the paths of its slices in the schema.
+    fn default_field(_field: &str, _version: u64, _len: usize) -> Option<Vec<u64>> { None }
+}
+
+/// The `indexed` encoding of a container `C` of `len` default values of `T`.
+pub fn defaults<C, T>(len: usize) -> Vec<u64>
+where
+    C: crate::ContainerBytes + for<'a> crate::Push<&'a T>,
+    T: Default,
+{
+    let mut column = C::default();
+    let item = T::default();
+    for _ in 0 .. len { column.push(&item); }
+    let mut store = Vec::new();
+    crate::bytes::indexed::encode(&mut store, &crate::Borrow::borrow(&column));
+    store
+}
+
+/// Displays the tree with one line per node, indenting the parts of each node.
+impl core::fmt::Display for Node {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_indent(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use alloc::{vec::Vec, string::String};
+    use crate::{Borrow, ContainerOf, FromBytes};
+    use super::{Schema, Node};
+
+    fn check<C>() where for<'a> C: Schema + Borrow<Borrowed<'a>: Schema + FromBytes<'a>> {
+        assert_eq!(C::schema(), <C::Borrowed<'_>>::schema());
+        assert_eq!(C::schema().slice_count(), <C::Borrowed<'_> as FromBytes>::SLICE_COUNT);
+    }
+
+    #[test]
+    fn slice_counts() {
+        check::<ContainerOf<u64>>();
+        check::<ContainerOf<(u8, bool, char)>>();
+        check::<ContainerOf<(usize, isize, u128, i128)>>();
+        check::<ContainerOf<Vec<String>>>();
+        check::<ContainerOf<Option<Result<u32, ()>>>>();
+        check::<ContainerOf<core::time::Duration>>();
+        check::<ContainerOf<[u16; 4]>>();
+        check::<crate::lookback::Repeats<Vec<u64>>>();
+        check::<crate::lookback::Lookbacks<crate::Strings>>();
+        check::<crate::roaring::RoaringBits>();
+        check::<crate::adts::art::ArtNodes>();
+        check::<crate::adts::tree::Trees<Vec<u32>>>();
+        #[cfg(feature = "serde")]
+        check::<crate::json::Jsons>();
+    }
+
+    #[test]
+    fn paths_and_validation() {
+        use crate::common::Push;
+
+        type C = ContainerOf<(u64, Option<String>)>;
+        let schema = C::schema();
+        let paths: Vec<String> = schema.slices().into_iter().map(|(path, _, _)| path).collect();
+        assert_eq!(paths, [
+            "0",
+            "1.indexes.counts", "1.indexes.values.values", "1.indexes.values.tail",
+            "1.somes.bounds", "1.somes.values",
+        ]);
+
+        let mut column: C = Default::default();
+        column.push(&(3, Some(String::from("three"))));
+        column.push(&(4, None::<String>));
+        let mut store = Vec::new();
+        crate::bytes::indexed::encode(&mut store, &column.borrow());
+        assert!(schema.validate(&store).is_ok());
+
+        // Read the bytes as a type whose second slice has larger elements.
+        let other = ContainerOf::<(u64, Option<Vec<u32>>)>::schema();
+        assert!(other.diff(&schema).is_some());
+        assert!(other.validate(&store).unwrap_err().contains("`1.somes.values` of u32"));
+        assert!(schema.diff(&schema).is_none());
+    }
+
+    #[test]
+    fn display() {
+        let schema = ContainerOf::<(u64, Vec<u8>)>::schema();
+        assert_eq!(format!("{}", schema), "tuple\n  0: [u64]\n  1: Vecs\n    bounds: [u64]\n    values: [u8]");
+        assert_eq!(Node::slice::<u32>(), Node::Slice("u32", 4));
+    }
+}
@@ -0,0 +1,207 @@
+//! Binary search and merging for sorted columns.
+//!
+//! The [`Search`] trait provides binary search methods for any `Index + Len` type,
+//! for example the borrowed forms of containers, and `Slice`s of them. Most methods
+//! require that the `Ref` type be `Ord`, which derived reference types can provide
+//! with `#[columnar(derive(PartialEq, Eq, PartialOrd, Ord))]`.
+//!
+//! The [`merge`] function merges sorted borrowed containers into an output container,
+//! copying contiguous runs from each input with `Container::extend_from_self`.
+
+use core::cmp::Ordering;
+use core::ops::Range;
+
+use crate::{Container, Index, Len};
+
+/// Binary search methods for sorted indexable types.
+///
+/// Each method assumes that the elements are sorted with respect to the comparison it uses,
+/// and if this is not the case the results are unspecified but will not panic.
+/// The methods shadow the inherent `partition_point` of slices and vectors when in scope.
+pub trait Search : Index + Len {
+    /// The number of leading elements for which `pred` returns true.
+    ///
+    /// The elements should be partitioned by `pred`: all elements for which it returns true
+    /// precede all elements for which it returns false.
+    #[inline]
+    fn partition_point(&self, pred: impl FnMut(Self::Ref) -> bool) -> usize {
+        partition_point_in(self, 0 .. self.len(), pred)
+    }
+    /// The index of the first element not less than `value`.
+    #[inline]
+    fn lower_bound(&self, value: &Self::Ref) -> usize where Self::Ref: Ord {
+        self.partition_point(|x| x.cmp(value) == Ordering::Less)
+    }
+    /// The index of the first element greater than `value`.
+    #[inline]
+    fn upper_bound(&self, value: &Self::Ref) -> usize where Self::Ref: Ord {
+        self.partition_point(|x| x.cmp(value) != Ordering::Greater)
+    }
+    /// The range of elements equal to `value`.
+    #[inline]
+    fn equal_range(&self, value: &Self::Ref) -> Range<usize> where Self::Ref: Ord {
+        self.equal_range_by(|x| x.cmp(value))
+    }
+    /// The range of elements for which `cmp` returns `Ordering::Equal`.
+    ///
+    /// The elements should be ordered such that `cmp` returns `Less`, then `Equal`, then `Greater`.
+    /// This allows searching with keys of types other than `Self::Ref`, for example a field of a struct.
+    #[inline]
+    fn equal_range_by(&self, mut cmp: impl FnMut(Self::Ref) -> Ordering) -> Range<usize> {
+        let lower = self.partition_point(|x| cmp(x) == Ordering::Less);
+        let upper = partition_point_in(self, lower .. self.len(), |x| cmp(x) != Ordering::Greater);
+        lower .. upper
+    }
+}
+
+impl<S: Index + Len + ?Sized> Search for S { }
+
+/// The first index in `range` for which `pred` returns false, or `range.end` if none.
+#[inline]
+fn partition_point_in<S: Index + ?Sized>(source: &S, range: Range<usize>, mut pred: impl FnMut(S::Ref) -> bool) -> usize {
+    let Range { start: mut lower, end: mut upper } = range;
+    while lower < upper {
+        let middle = lower + (upper - lower) / 2;
+        if pred(source.get(middle)) { lower = middle + 1; }
+        else { upper = middle; }
+    }
+    lower
+}
+
+/// The first index in `range` for which `pred` returns false, searching outward from `range.start`.
+///
+/// This takes time logarithmic in the distance from `range.start` to the result, rather than in the
+/// length of the range, and is appropriate when the result is expected to be close to the start.
+#[inline]
+fn gallop_in<S: Index + ?Sized>(source: &S, range: Range<usize>, mut pred: impl FnMut(S::Ref) -> bool) -> usize {
+    let Range { start, end } = range;
+    let mut step = 1;
+    let mut lower = start;
+    while lower + step < end && pred(source.get(lower + step)) {
+        lower += step;
+        step <<= 1;
+    }
+    // All elements through `lower` satisfy `pred` (if `lower > start`), and `lower + step` does not if in range.
+    partition_point_in(source, lower .. core::cmp::min(lower + step, end), pred)
+}
+
+/// Merges the sorted `inputs` into `output`.
+///
+/// Elements are pushed to `output` in sorted order, with equal elements ordered by their input, and
+/// then by their position within the input. Runs of elements from a single input are copied with
+/// `extend_from_self`, which is often substantially faster than pushing the elements individually.
+///
+/// # Examples
+///
+/// ```
+/// use columnar::{Borrow, Index, Strings};
+/// use columnar::search::merge;
+///
+/// let mut input0: Strings = Default::default();
+/// let mut input1: Strings = Default::default();
+/// for s in ["apple", "cherry", "date"] { columnar::Push::push(&mut input0, s); }
+/// for s in ["banana", "cherry", "eggplant", "fig"] { columnar::Push::push(&mut input1, s); }
+///
+/// let mut output: Strings = Default::default();
+/// merge(&[input0.borrow(), input1.borrow()], &mut output);
+///
+/// let merged: Vec<_> = output.borrow().into_index_iter().collect();
+/// assert_eq!(merged, [&b"apple"[..], b"banana", b"cherry", b"cherry", b"date", b"eggplant", b"fig"]);
+/// ```
+pub fn merge<'a, C: Container>(inputs: &[C::Borrowed<'a>], output: &mut C) where C::Ref<'a>: Ord {
+    output.reserve_for(inputs.iter().copied());
+    // The next position to read from each input.
+    let mut cursors: alloc::vec::Vec<usize> = alloc::vec![0; inputs.len()];
+    loop {
+        // Find the input with the least next element, breaking ties in favor of earlier inputs.
+        let mut least: Option<(usize, C::Ref<'a>)> = None;
+        for (index, input) in inputs.iter().enumerate() {
+            if cursors[index] < input.len() {
+                let head = input.get(cursors[index]);
+                if least.as_ref().map(|(_, l)| head < *l).unwrap_or(true) {
+                    least = Some((index, head));
+                }
+            }
+        }
+        let Some((index, _)) = least else { return };
+
+        // Bound the run from `index` by the least next element of the other inputs: the run may include
+        // elements equal to heads of later inputs, but not equal to heads of earlier inputs.
+        let mut bound: Option<(usize, C::Ref<'a>)> = None;
+        for (other, input) in inputs.iter().enumerate() {
+            if other != index && cursors[other] < input.len() {
+                let head = input.get(cursors[other]);
+                if bound.as_ref().map(|(_, b)| head < *b).unwrap_or(true) {
+                    bound = Some((other, head));
+                }
+            }
+        }
+
+        let input = &inputs[index];
+        let start = cursors[index];
+        let end = match bound {
+            None => input.len(),
+            Some((other, head)) if other > index => gallop_in(input, start + 1 .. input.len(), |x| x <= head),
+            Some((_, head)) => gallop_in(input, start + 1 .. input.len(), |x| x < head),
+        };
+        output.extend_from_self(*input, start .. end);
+        cursors[index] = end;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use crate::{Borrow, Index, Len, Push, Strings, Vecs};
+    use super::{Search, merge};
+
+    #[test]
+    fn bounds() {
+        let mut column: Vec<u64> = Vec::new();
+        for i in 0 .. 100u64 { column.push(i / 3); }
+        assert_eq!(column.lower_bound(&5), 15);
+        assert_eq!(column.upper_bound(&5), 18);
+        assert_eq!(column.equal_range(&5), 15 .. 18);
+        assert_eq!(column.equal_range(&100), 100 .. 100);
+        assert_eq!(Search::partition_point(&column, |x| x < 10), 30);
+
+        let mut strings: Strings = Default::default();
+        for s in ["a", "b", "b", "c", "e"] { strings.push(s); }
+        let borrowed = strings.borrow();
+        assert_eq!(borrowed.lower_bound(&&b"b"[..]), 1);
+        assert_eq!(borrowed.upper_bound(&&b"b"[..]), 3);
+        assert_eq!(borrowed.equal_range(&&b"d"[..]), 4 .. 4);
+
+        // Slices of containers search relative to their bounds.
+        let slice = crate::Slice::new(2, 5, borrowed);
+        assert_eq!(slice.lower_bound(&&b"c"[..]), 1);
+        assert_eq!(slice.equal_range_by(|s| s[0].cmp(&b'b')), 0 .. 1);
+    }
+
+    #[test]
+    fn merge_runs() {
+        let mut inputs: Vec<Vecs<Vec<u64>>> = Vec::new();
+        let mut expected = Vec::new();
+        for input in 0 .. 4u64 {
+            let mut container: Vecs<Vec<u64>> = Default::default();
+            for i in 0 .. 100u64 {
+                // Each input holds long runs of values other inputs lack, and some values in common.
+                let values = if (i / 10) % 4 == input { vec![vec![i]] } else if i % 7 == 0 { vec![vec![i], vec![i, input]] } else { continue };
+                for value in values {
+                    container.push(&value);
+                    expected.push(value);
+                }
+            }
+            inputs.push(container);
+        }
+        expected.sort();
+
+        let borrowed: Vec<_> = inputs.iter().map(|x| x.borrow()).collect();
+        let mut output: Vecs<Vec<u64>> = Default::default();
+        merge(&borrowed, &mut output);
+        assert_eq!(output.len(), expected.len());
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(output.borrow().get(index).into_iter().copied().collect::<Vec<_>>(), *value);
+        }
+    }
+}
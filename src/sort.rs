@@ -0,0 +1,126 @@
+//! Sorting and permuting containers.
+//!
+//! Columnar containers cannot rearrange their elements in place, as elements may not have a fixed size
+//! nor even be stored contiguously. Instead, we determine a permutation from the borrowed form of a
+//! container, and then gather the elements in that order into a new container of the same type,
+//! using [`Container::gather`].
+//!
+//! The `permutation*` functions work on any `Index + Len` type, and produce the permutation only.
+//! The remaining functions produce the permutation and then apply it to a container.
+
+use core::cmp::Ordering;
+use alloc::vec::Vec;
+
+use crate::{Container, Index, Len};
+
+/// The permutation that stably sorts the elements of `source` by `compare`.
+///
+/// The result lists indices into `source` in sorted order.
+pub fn permutation_by<S: Index + Len>(source: S, mut compare: impl FnMut(S::Ref, S::Ref) -> Ordering) -> Vec<usize> {
+    let mut permutation: Vec<usize> = (0 .. source.len()).collect();
+    permutation.sort_by(|x, y| compare(source.get(*x), source.get(*y)));
+    permutation
+}
+
+/// The permutation that stably sorts the elements of `source` by the key `key`.
+///
+/// The key is computed once for each element.
+pub fn permutation_by_key<S: Index + Len, K: Ord>(source: S, mut key: impl FnMut(S::Ref) -> K) -> Vec<usize> {
+    let keys: Vec<K> = (0 .. source.len()).map(|i| key(source.get(i))).collect();
+    let mut permutation: Vec<usize> = (0 .. source.len()).collect();
+    permutation.sort_by(|x, y| keys[*x].cmp(&keys[*y]));
+    permutation
+}
+
+/// The permutation that stably sorts the elements of `source`.
+pub fn permutation<S: Index + Len>(source: S) -> Vec<usize> where S::Ref: Ord {
+    permutation_by(source, |x, y| x.cmp(&y))
+}
+
+/// Rearranges `container` so that its `i`-th element is the element previously at `indices[i]`.
+///
+/// The indices need not be a permutation: they may omit or repeat elements, which are then
+/// respectively dropped or duplicated.
+pub fn permute<C: Container>(container: &mut C, indices: &[usize]) {
+    let mut permuted = C::default();
+    if indices.len() == container.borrow().len() {
+        permuted.reserve_for(core::iter::once(container.borrow()));
+    }
+    permuted.gather(container.borrow(), indices);
+    *container = permuted;
+}
+
+/// Stably sorts `container` by `compare`.
+pub fn sort_by<C: Container>(container: &mut C, compare: impl for<'a> FnMut(C::Ref<'a>, C::Ref<'a>) -> Ordering) {
+    let permutation = permutation_by(container.borrow(), compare);
+    permute(container, &permutation);
+}
+
+/// Stably sorts `container` by the key `key`, which is computed once for each element.
+pub fn sort_by_key<C: Container, K: Ord>(container: &mut C, key: impl for<'a> FnMut(C::Ref<'a>) -> K) {
+    let permutation = permutation_by_key(container.borrow(), key);
+    permute(container, &permutation);
+}
+
+/// Stably sorts `container`.
+pub fn sort<C: Container>(container: &mut C) where for<'a> C::Ref<'a>: Ord {
+    sort_by(container, |x, y| x.cmp(&y));
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use alloc::string::{String, ToString};
+    use crate::{Borrow, Columnar, Container, Index, Len, Push, Strings, Vecs};
+
+    #[test]
+    fn sort_strings() {
+        let words = ["delta", "alpha", "charlie", "bravo", "alpha"];
+        let mut strings: Strings = Default::default();
+        for word in words { strings.push(word); }
+
+        super::sort(&mut strings);
+        let mut expected = words.to_vec();
+        expected.sort();
+        let sorted: Vec<&str> = strings.borrow().into_index_iter().map(|s| core::str::from_utf8(s).unwrap()).collect();
+        assert_eq!(sorted, expected);
+
+        super::sort_by(&mut strings, |x, y| y.cmp(x));
+        expected.reverse();
+        let sorted: Vec<&str> = strings.borrow().into_index_iter().map(|s| core::str::from_utf8(s).unwrap()).collect();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn sort_tuples() {
+        let records: Vec<(u64, String, Vec<u8>)> = (0 .. 100u64).map(|i| ((i * 37) % 10, (i % 7).to_string(), vec![i as u8; (i % 5) as usize])).collect();
+        let mut columns = Columnar::as_columns(records.iter());
+
+        // Sort by the first field, stably, which keeps the original order within each key.
+        super::sort_by_key(&mut columns, |(key, _, _)| *key);
+        let mut expected = records.clone();
+        expected.sort_by_key(|(key, _, _)| *key);
+        assert_eq!(columns.len(), expected.len());
+        for (index, record) in expected.iter().enumerate() {
+            assert_eq!(<(u64, String, Vec<u8>)>::into_owned(columns.borrow().get(index)), *record);
+        }
+    }
+
+    #[test]
+    fn gather() {
+        let mut lists: Vecs<Vec<u64>> = Default::default();
+        for i in 0 .. 10u64 { lists.push(&(0 .. i).collect::<Vec<_>>()); }
+
+        // Runs, repeats, and omissions.
+        let indices = [2, 3, 4, 9, 0, 0, 7];
+        let mut gathered: Vecs<Vec<u64>> = Default::default();
+        gathered.gather(lists.borrow(), &indices);
+        assert_eq!(gathered.len(), indices.len());
+        for (index, source) in indices.iter().enumerate() {
+            assert_eq!(gathered.borrow().get(index), lists.borrow().get(*source));
+        }
+
+        super::permute(&mut lists, &indices);
+        assert_eq!(lists, gathered);
+    }
+}
@@ -108,7 +108,13 @@ impl<'a, BC: crate::AsBytes<'a>, VC: crate::AsBytes<'a>> crate::AsBytes<'a> for
         }
     }
 }
-impl<'a, BC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for Strings<BC, VC> {
+impl<BC: crate::schema::Schema, VC: crate::schema::Schema> crate::schema::Schema for Strings<BC, VC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Strings", [("bounds", BC::schema()), ("values", VC::schema())])
+    }
+}
+
+impl<'a, BC: crate::FromBytes<'a> + IndexAs<u64>, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for Strings<BC, VC> {
     const SLICE_COUNT: usize = BC::SLICE_COUNT + VC::SLICE_COUNT;
     #[inline(always)]
     fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -129,6 +135,11 @@ impl<'a, BC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>> crate::FromBytes<'a
         VC::element_sizes(sizes)?;
         Ok(())
     }
+    fn validate_deep(&self) -> Result<usize, String> {
+        let bounds = self.bounds.validate_deep()?;
+        crate::common::validate_bounds("Strings", &self.bounds, bounds, self.values.validate_deep()?)?;
+        Ok(bounds)
+    }
 }
 
 impl<BC: Len, VC> Len for Strings<BC, VC> {
@@ -60,7 +60,12 @@ pub mod rank_select {
             }
         }
     }
-    impl<'a, CC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for RankSelect<CC, VC, &'a [u64]> {
+    impl<CC: crate::schema::Schema, VC: crate::schema::Schema, WC> crate::schema::Schema for RankSelect<CC, VC, WC> {
+        fn schema() -> crate::schema::Node {
+            crate::schema::Node::container("RankSelect", [("counts", CC::schema()), ("values", <Bools<VC, WC>>::schema())])
+        }
+    }
+    impl<'a, CC: crate::FromBytes<'a> + IndexAs<u64>, VC: crate::FromBytes<'a> + IndexAs<u64>> crate::FromBytes<'a> for RankSelect<CC, VC, &'a [u64]> {
         const SLICE_COUNT: usize = CC::SLICE_COUNT + <crate::primitive::Bools<VC, &'a [u64]>>::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -81,6 +86,24 @@ pub mod rank_select {
             <crate::primitive::Bools<VC, &'a [u64]>>::element_sizes(sizes)?;
             Ok(())
         }
+        /// Validates that there is a count for each complete block of 1024 bits, and that each is correct.
+        fn validate_deep(&self) -> Result<usize, String> {
+            let bits = self.values.validate_deep()?;
+            let counts = self.counts.validate_deep()?;
+            if counts != bits / 1024 {
+                return Err(format!("RankSelect: {} bits require {} counts, but found {}", bits, bits / 1024, counts));
+            }
+            let mut count = 0;
+            for chunk in 0 .. counts {
+                for word in 16 * chunk .. 16 * (chunk + 1) {
+                    count += self.values.values.index_as(word).count_ones() as u64;
+                }
+                if self.counts.index_as(chunk) != count {
+                    return Err(format!("RankSelect: count {} is {}, but should be {}", chunk, self.counts.index_as(chunk), count));
+                }
+            }
+            Ok(bits)
+        }
     }
 
 
@@ -143,6 +166,9 @@ pub mod rank_select {
         pub fn len(&self) -> usize {
             self.values.len()
         }
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
     }
 
     // This implementation probably only works for `Vec<u64>` and `Vec<u64>`, but we could fix that.
@@ -275,7 +301,12 @@ pub mod result {
             }
         }
     }
-    impl<'a, SC: crate::FromBytes<'a>, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for Results<SC, TC, CC, VC, &'a [u64]> {
+    impl<SC: crate::schema::Schema, TC: crate::schema::Schema, CC: crate::schema::Schema, VC: crate::schema::Schema, WC> crate::schema::Schema for Results<SC, TC, CC, VC, WC> {
+        fn schema() -> crate::schema::Node {
+            crate::schema::Node::container("Results", [("indexes", <RankSelect<CC, VC, WC>>::schema()), ("oks", SC::schema()), ("errs", TC::schema())])
+        }
+    }
+    impl<'a, SC: crate::FromBytes<'a>, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a> + IndexAs<u64> + Len, VC: crate::FromBytes<'a> + IndexAs<u64> + Len> crate::FromBytes<'a> for Results<SC, TC, CC, VC, &'a [u64]> {
         const SLICE_COUNT: usize = <RankSelect<CC, VC, &'a [u64]>>::SLICE_COUNT + SC::SLICE_COUNT + TC::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -299,6 +330,15 @@ pub mod result {
             TC::element_sizes(sizes)?;
             Ok(())
         }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let len = self.indexes.validate_deep()?;
+            let oks = self.indexes.rank(len);
+            let (found_oks, found_errs) = (self.oks.validate_deep()?, self.errs.validate_deep()?);
+            if found_oks != oks || found_errs != len - oks {
+                return Err(format!("Results: {} oks and {} errs expected, but found {} and {}", oks, len - oks, found_oks, found_errs));
+            }
+            Ok(len)
+        }
     }
 
     impl<SC, TC, CC, VC: Len, WC: IndexAs<u64>> Len for Results<SC, TC, CC, VC, WC> {
@@ -430,7 +470,7 @@ pub mod result {
             assert_eq!(column.len(), 200);
 
             for i in 0..100 {
-                assert_eq!(column.get(2*i+0), Ok(i as u64));
+                assert_eq!(column.get(2*i), Ok(i as u64));
                 assert_eq!(column.get(2*i+1), Err(i as u64));
             }
 
@@ -443,10 +483,36 @@ pub mod result {
             assert_eq!(column.len(), 200);
 
             for i in 0..100 {
-                assert_eq!(column.get(2*i+0), Ok(i as u64));
+                assert_eq!(column.get(2*i), Ok(i as u64));
                 assert_eq!(column.get(2*i+1), Err(i as u8));
             }
         }
+
+        #[test]
+        fn validate_deep() {
+
+            use crate::common::Push;
+            use crate::{Borrow, FromBytes};
+
+            let mut column: crate::ContainerOf<Result<u64, u64>> = Default::default();
+            for i in 0..3000 {
+                if i % 3 == 0 { column.push(Ok::<u64, u64>(i)); }
+                else { column.push(Err::<u64, u64>(i)); }
+            }
+            assert_eq!(column.borrow().validate_deep(), Ok(3000));
+
+            // Running counts must match the bits they summarize.
+            let mut counts = column.indexes.counts.clone();
+            counts[1] += 1;
+            let mut borrowed = column.borrow();
+            borrowed.indexes.counts = &counts[..];
+            assert!(borrowed.validate_deep().is_err());
+
+            // Each variant must have as many values as its bits indicate.
+            let mut borrowed = column.borrow();
+            borrowed.oks = &borrowed.oks[1..];
+            assert!(borrowed.validate_deep().is_err());
+        }
     }
 }
 
@@ -541,7 +607,12 @@ pub mod option {
         }
     }
 
-    impl <'a, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a>, VC: crate::FromBytes<'a>> crate::FromBytes<'a> for Options<TC, CC, VC, &'a [u64]> {
+    impl<TC: crate::schema::Schema, CC: crate::schema::Schema, VC: crate::schema::Schema, WC> crate::schema::Schema for Options<TC, CC, VC, WC> {
+        fn schema() -> crate::schema::Node {
+            crate::schema::Node::container("Options", [("indexes", <RankSelect<CC, VC, WC>>::schema()), ("somes", TC::schema())])
+        }
+    }
+    impl <'a, TC: crate::FromBytes<'a>, CC: crate::FromBytes<'a> + IndexAs<u64> + Len, VC: crate::FromBytes<'a> + IndexAs<u64> + Len> crate::FromBytes<'a> for Options<TC, CC, VC, &'a [u64]> {
         const SLICE_COUNT: usize = <RankSelect<CC, VC, &'a [u64]>>::SLICE_COUNT + TC::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -562,6 +633,14 @@ pub mod option {
             TC::element_sizes(sizes)?;
             Ok(())
         }
+        fn validate_deep(&self) -> Result<usize, String> {
+            let len = self.indexes.validate_deep()?;
+            let somes = self.indexes.rank(len);
+            if self.somes.validate_deep()? != somes {
+                return Err(format!("Options: {} somes expected, but found a different number", somes));
+            }
+            Ok(len)
+        }
     }
 
     impl<T, CC, VC: Len, WC: IndexAs<u64>> Len for Options<T, CC, VC, WC> {
@@ -664,7 +743,7 @@ pub mod option {
             // Type annotation is important to avoid some inference overflow.
             let store: Options<Vec<i32>> = Columnar::into_columns((0..100).map(Some));
             assert_eq!(store.len(), 100);
-            assert!((&store).index_iter().zip(0..100).all(|(a, b)| a == Some(&b)));
+            assert!(store.index_iter().zip(0..100).all(|(a, b)| a == Some(&b)));
         }
 
         #[test]
@@ -672,7 +751,7 @@ pub mod option {
             let store = Columnar::into_columns((0..100).map(|_x| None::<i32>));
             assert_eq!(store.len(), 100);
             let foo = &store;
-            assert!(foo.index_iter().zip(0..100).all(|(a, _b)| a == None));
+            assert!(foo.index_iter().zip(0..100).all(|(a, _b)| a.is_none()));
         }
 
         #[test]
@@ -680,7 +759,7 @@ pub mod option {
             // Type annotation is important to avoid some inference overflow.
             let store: Options<Vec<i32>>  = Columnar::into_columns((0..100).map(|x| if x % 2 == 0 { Some(x) } else { None }));
             assert_eq!(store.len(), 100);
-            assert!((&store).index_iter().zip(0..100).all(|(a, b)| a == if b % 2 == 0 { Some(&b) } else { None }));
+            assert!(store.index_iter().zip(0..100).all(|(a, b)| a == if b % 2 == 0 { Some(&b) } else { None }));
         }
     }
 }
@@ -690,6 +769,38 @@ pub mod discriminant {
     use alloc::{vec::Vec, string::String};
     use crate::{Clear, Container, Len, Index, IndexAs, Borrow};
 
+    /// Unsigned integers that can record the variant of each element of a `Discriminant`.
+    ///
+    /// Tags wider than `u8` allow more than 256 variants, or explicit discriminants that do not fit in a byte.
+    pub trait Tag: Copy + Eq + core::fmt::Debug + Send + 'static {
+        /// The largest tag.
+        const MAX: u64;
+        /// The tag as a `u64`.
+        fn into_u64(self) -> u64;
+        /// The tag from a `u64`, which must be at most `MAX`.
+        fn from_u64(tag: u64) -> Self;
+    }
+
+    macro_rules! implement_tag {
+        ($($index_type:ty),*) => { $(
+            impl Tag for $index_type {
+                const MAX: u64 = <$index_type>::MAX as u64;
+                #[inline(always)] fn into_u64(self) -> u64 { self as u64 }
+                #[inline(always)] fn from_u64(tag: u64) -> Self { tag as $index_type }
+            }
+        )* }
+    }
+
+    implement_tag!(u8, u16, u32, u64);
+
+    /// Containers of the tags of a `Discriminant`, which determine the type of its tags.
+    pub trait Tags: Len + IndexAs<Self::Tag> {
+        /// The type of the tags.
+        type Tag: Tag;
+    }
+    impl<T: Tag> Tags for Vec<T> { type Tag = T; }
+    impl<T: Tag> Tags for &[T] { type Tag = T; }
+
     /// Tracks variant discriminants and offsets for enum containers.
     ///
     /// Uses two arrays (`variant` and `offset`) with three states:
@@ -697,8 +808,11 @@ pub mod discriminant {
     /// - **Homogeneous**: `variant` is empty, `offset` holds `[tag, count]` where
     ///   `tag = variant_index + 1`. All elements share a single variant with
     ///   identity offsets (element `i` maps to offset `i`).
-    /// - **Heterogeneous**: `variant` has per-element discriminants (`u8`),
+    /// - **Heterogeneous**: `variant` has per-element discriminants (a `Tag`, by default `u8`),
     ///   `offset` has per-element offsets into variant containers (`u64`).
+    ///
+    /// The discriminants are usually the positions of the variants, but derived containers for
+    /// enums with explicit discriminants record those values instead.
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Debug, Default, PartialEq)]
     pub struct Discriminant<CVar = Vec<u8>, COff = Vec<u64>> {
@@ -714,18 +828,37 @@ pub mod discriminant {
         /// Push a variant discriminant and the offset into its variant container.
         #[inline]
         pub fn push(&mut self, variant: u8, offset: u64) {
-            let tag = variant as u64 + 1;
+            crate::Push::push(self, (variant, offset))
+        }
+
+        /// Pre-allocate for the given borrowed discriminants.
+        pub fn reserve_for<'a>(&mut self, selves: impl Iterator<Item = Discriminant<&'a [u8], &'a [u64]>> + Clone) {
+            Container::reserve_for(self, selves)
+        }
+    }
+
+    /// Pushes a variant discriminant and the offset into its variant container, for tags of any width.
+    impl<T: Tag> crate::Push<(T, u64)> for Discriminant<Vec<T>> {
+        #[inline]
+        fn push(&mut self, (variant, offset): (T, u64)) {
+            // The largest `u64` tag has no homogeneous form, and is always stored heterogeneously.
+            let tag = variant.into_u64().checked_add(1);
             if self.variant.is_empty() {
                 if self.offset.is_empty() {
-                    // Empty → start homogeneous: offset = [tag, 1].
-                    self.offset.push(tag);
-                    self.offset.push(1);
-                } else if self.offset[0] == tag {
+                    if let Some(tag) = tag {
+                        // Empty → start homogeneous: offset = [tag, 1].
+                        self.offset.push(tag);
+                        self.offset.push(1);
+                    } else {
+                        self.variant.push(variant);
+                        self.offset.push(offset);
+                    }
+                } else if Some(self.offset[0]) == tag {
                     // Same variant; stay homogeneous, increment count.
                     self.offset[1] += 1;
                 } else {
                     // Different variant; transition to heterogeneous.
-                    let prev = (self.offset[0] - 1) as u8;
+                    let prev = T::from_u64(self.offset[0] - 1);
                     let count = self.offset[1];
                     self.variant.reserve(count as usize + 1);
                     self.offset.clear();
@@ -743,12 +876,6 @@ pub mod discriminant {
                 self.offset.push(offset);
             }
         }
-
-        /// Pre-allocate for the given borrowed discriminants.
-        pub fn reserve_for<'a>(&mut self, selves: impl Iterator<Item = Discriminant<&'a [u8], &'a [u64]>> + Clone) {
-            self.variant.reserve_for(selves.clone().map(|x| x.variant));
-            self.offset.reserve_for(selves.map(|x| x.offset));
-        }
     }
 
     impl<CVar: Len, COff: Len> Discriminant<CVar, COff> {
@@ -759,22 +886,80 @@ pub mod discriminant {
         }
         /// Returns `Some(variant)` if all elements share a single variant.
         #[inline]
-        pub fn homogeneous(&self) -> Option<u8> where COff: IndexAs<u64> {
+        pub fn homogeneous(&self) -> Option<CVar::Tag> where CVar: Tags, COff: IndexAs<u64> {
             if self.variant.is_empty() && self.offset.len() >= 2 {
-                Some((self.offset.index_as(0) - 1) as u8)
+                Some(CVar::Tag::from_u64(self.offset.index_as(0) - 1))
             } else {
                 None
             }
         }
         /// Returns `(variant, offset)` for the element at `index`.
         #[inline(always)]
-        pub fn get(&self, index: usize) -> (u8, u64) where CVar: IndexAs<u8>, COff: IndexAs<u64> {
+        pub fn get(&self, index: usize) -> (CVar::Tag, u64) where CVar: Tags, COff: IndexAs<u64> {
             if self.is_heterogeneous() {
                 (self.variant.index_as(index), self.offset.index_as(index))
             } else {
                 let tag: u64 = self.offset.index_as(0);
-                ((tag - 1) as u8, index as u64)
+                (CVar::Tag::from_u64(tag - 1), index as u64)
+            }
+        }
+    }
+
+    impl<CVar: Tags, COff: Len + IndexAs<u64>> Discriminant<CVar, COff> {
+        /// Validates the form of the discriminants, and returns their length.
+        ///
+        /// The form is either empty, homogeneous with a `[tag, count]` offset, or heterogeneous
+        /// with as many offsets as variants.
+        pub fn validate_form(&self) -> Result<usize, String> {
+            if self.is_heterogeneous() {
+                if self.variant.len() != self.offset.len() {
+                    return Err(format!("Discriminant: {} variants but {} offsets", self.variant.len(), self.offset.len()));
+                }
+                Ok(self.variant.len())
+            } else {
+                match self.offset.len() {
+                    0 => Ok(0),
+                    2 => {
+                        let (tag, count) = (self.offset.index_as(0), self.offset.index_as(1));
+                        if tag == 0 || tag - 1 > CVar::Tag::MAX {
+                            return Err(format!("Discriminant: homogeneous tag {} is not a variant plus one", tag));
+                        }
+                        usize::try_from(count).map_err(|_| format!("Discriminant: count {} does not fit in `usize`", count))
+                    },
+                    len => Err(format!("Discriminant: {} offsets without variants, but expected 0 or 2", len)),
+                }
+            }
+        }
+        /// Validates the form of the discriminants, and that each references an element of its variant.
+        ///
+        /// The `lens` argument contains the length of each variant's container, and the method
+        /// returns the number of discriminants. This is used to validate derived enum containers.
+        pub fn validate_variants(&self, lens: &[usize]) -> Result<usize, String> {
+            self.validate_positions(lens, |variant| usize::try_from(variant.into_u64()).ok())
+        }
+        /// Validates the discriminants as `validate_variants` does, for variants whose discriminants are `tags`.
+        ///
+        /// Discriminants that are not in `tags` are rejected. This is used to validate derived enum
+        /// containers for enums with explicit discriminants.
+        pub fn validate_tags(&self, tags: &[CVar::Tag], lens: &[usize]) -> Result<usize, String> {
+            self.validate_positions(lens, |variant| tags.iter().position(|tag| *tag == variant))
+        }
+        /// Validates the discriminants, whose variants are located among `lens` by `position`.
+        fn validate_positions(&self, lens: &[usize], position: impl Fn(CVar::Tag) -> Option<usize>) -> Result<usize, String> {
+            let len = self.validate_form()?;
+            if let Some(variant) = self.homogeneous() {
+                if !position(variant).and_then(|position| lens.get(position)).is_some_and(|variant_len| *variant_len >= len) {
+                    return Err(format!("Discriminant: {} elements of variant {:?} but only {:?} available", len, variant, position(variant).and_then(|position| lens.get(position))));
+                }
+            } else {
+                for index in 0 .. len {
+                    let (variant, offset): (CVar::Tag, u64) = (self.variant.index_as(index), self.offset.index_as(index));
+                    if !position(variant).and_then(|position| lens.get(position)).is_some_and(|variant_len| offset < *variant_len as u64) {
+                        return Err(format!("Discriminant: element {} has variant {:?} and offset {}, which does not exist", index, variant, offset));
+                    }
+                }
             }
+            Ok(len)
         }
     }
 
@@ -788,22 +973,22 @@ pub mod discriminant {
     }
 
     // Index for the borrowed form: returns (variant, offset).
-    impl<'a> Index for Discriminant<&'a [u8], &'a [u64]> {
-        type Ref = (u8, u64);
+    impl<'a, T: Tag> Index for Discriminant<&'a [T], &'a [u64]> {
+        type Ref = (T, u64);
         #[inline(always)]
-        fn get(&self, index: usize) -> (u8, u64) {
+        fn get(&self, index: usize) -> (T, u64) {
             if self.is_heterogeneous() {
-                (self.variant.index_as(index), self.offset.index_as(index))
+                (self.variant[index], self.offset[index])
             } else {
-                ((self.offset[0] - 1) as u8, index as u64)
+                (T::from_u64(self.offset[0] - 1), index as u64)
             }
         }
     }
 
     // Borrow
-    impl Borrow for Discriminant {
-        type Ref<'a> = (u8, u64);
-        type Borrowed<'a> = Discriminant<&'a [u8], &'a [u64]>;
+    impl<T: Tag> Borrow for Discriminant<Vec<T>> {
+        type Ref<'a> = (T, u64);
+        type Borrowed<'a> = Discriminant<&'a [T], &'a [u64]>;
         #[inline(always)]
         fn borrow<'a>(&'a self) -> Self::Borrowed<'a> {
             Discriminant {
@@ -822,6 +1007,13 @@ pub mod discriminant {
         fn reborrow_ref<'b, 'a: 'b>(thing: Self::Ref<'a>) -> Self::Ref<'b> { thing }
     }
 
+    impl<T: Tag> Container for Discriminant<Vec<T>> {
+        fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
+            self.variant.reserve_for(selves.clone().map(|x| x.variant));
+            self.offset.reserve_for(selves.map(|x| x.offset));
+        }
+    }
+
     impl<CVar: Clear, COff: Clear> Clear for Discriminant<CVar, COff> {
         #[inline(always)]
         fn clear(&mut self) {
@@ -845,9 +1037,15 @@ pub mod discriminant {
         }
     }
 
+    impl<CVar: crate::schema::Schema, COff: crate::schema::Schema> crate::schema::Schema for Discriminant<CVar, COff> {
+        fn schema() -> crate::schema::Node {
+            crate::schema::Node::container("Discriminant", [("variant", CVar::schema()), ("offset", COff::schema())])
+        }
+    }
+
     // FromBytes for borrowed form
-    impl<'a> crate::FromBytes<'a> for Discriminant<&'a [u8], &'a [u64]> {
-        const SLICE_COUNT: usize = <&'a [u8]>::SLICE_COUNT + <&'a [u64]>::SLICE_COUNT;
+    impl<'a, T: Tag> crate::FromBytes<'a> for Discriminant<&'a [T], &'a [u64]> where &'a [T]: crate::FromBytes<'a> {
+        const SLICE_COUNT: usize = <&'a [T]>::SLICE_COUNT + <&'a [u64]>::SLICE_COUNT;
         #[inline(always)]
         fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
             let variant = crate::FromBytes::from_bytes(bytes);
@@ -861,10 +1059,11 @@ pub mod discriminant {
             Self { variant, offset: offset_field }
         }
         fn element_sizes(sizes: &mut Vec<usize>) -> Result<(), String> {
-            <&[u8]>::element_sizes(sizes)?;
+            <&[T]>::element_sizes(sizes)?;
             <&[u64]>::element_sizes(sizes)?;
             Ok(())
         }
+        fn validate_deep(&self) -> Result<usize, String> { self.validate_form() }
     }
 
     #[cfg(test)]
@@ -935,5 +1134,23 @@ pub mod discriminant {
             assert_eq!(b.get(1), (1, 0));
             assert_eq!(b.get(2), (0, 1));
         }
+
+        #[test]
+        fn validate_variants() {
+            use crate::{Borrow, FromBytes};
+            let mut d = super::Discriminant::default();
+            d.push(0, 0);
+            d.push(1, 0);
+            d.push(0, 1);
+            assert_eq!(d.borrow().validate_variants(&[2, 1]), Ok(3));
+            assert!(d.borrow().validate_variants(&[1, 1]).is_err());
+            assert!(d.borrow().validate_variants(&[2]).is_err());
+            // Heterogeneous variants and offsets must have equal lengths.
+            let b = super::Discriminant { variant: &[0u8, 1][..], offset: &[0u64][..] };
+            assert!(b.validate_deep().is_err());
+            // Homogeneous offsets must hold a valid tag.
+            let b = super::Discriminant { variant: &[] as &[u8], offset: &[0u64, 3][..] };
+            assert!(b.validate_deep().is_err());
+        }
     }
 }
@@ -49,6 +49,12 @@ macro_rules! tuple_impl {
                 let ($($name2,)*) = other;
                 $( $name.extend_from_self($name2, range.clone()); )*
             }
+            #[inline(always)]
+            fn gather(&mut self, other: Self::Borrowed<'_>, indices: &[usize]) {
+                let ($($name,)*) = self;
+                let ($($name2,)*) = other;
+                $( $name.gather($name2, indices); )*
+            }
 
             fn reserve_for<'a, I>(&mut self, selves: I) where Self: 'a, I: Iterator<Item = Self::Borrowed<'a>> + Clone {
                 let ($($name,)*) = self;
@@ -73,6 +79,9 @@ macro_rules! tuple_impl {
                 panic!("get_byte_slice: index out of bounds")
             }
         }
+        impl<$($name: crate::schema::Schema),*> crate::schema::Schema for ($($name,)*) {
+            fn schema() -> crate::schema::Node { crate::schema::Node::tuple([$($name::schema()),*]) }
+        }
         impl<'a, $($name: crate::FromBytes<'a>),*> crate::FromBytes<'a> for ($($name,)*) {
             const SLICE_COUNT: usize = 0 $(+ $name::SLICE_COUNT)*;
             #[inline(always)]
@@ -91,6 +100,11 @@ macro_rules! tuple_impl {
                 $($name::element_sizes(sizes)?;)*
                 Ok(())
             }
+            #[allow(non_snake_case)]
+            fn validate_deep(&self) -> Result<usize, String> {
+                let ($($name,)*) = self;
+                crate::common::validate_lengths("tuple", &[$($name.validate_deep()?),*])
+            }
         }
 
         impl<$($name: Len),*> Len for ($($name,)*) {
@@ -178,7 +192,7 @@ mod test {
         assert_eq!(column.len(), 200);
 
         for i in 0..100u64 {
-            assert_eq!((&column).get((2*i+0) as usize), (&i, &(i as u8), i.to_string().as_bytes()));
+            assert_eq!((&column).get((2*i) as usize), (&i, &(i as u8), i.to_string().as_bytes()));
             assert_eq!((&column).get((2*i+1) as usize), (&i, &(i as u8), &b""[..]));
         }
 
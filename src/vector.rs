@@ -131,7 +131,13 @@ impl<'a, TC: crate::AsBytes<'a>, BC: crate::AsBytes<'a>> crate::AsBytes<'a> for
         }
     }
 }
-impl<'a, TC: crate::FromBytes<'a>, BC: crate::FromBytes<'a>> crate::FromBytes<'a> for Vecs<TC, BC> {
+impl<TC: crate::schema::Schema, BC: crate::schema::Schema> crate::schema::Schema for Vecs<TC, BC> {
+    fn schema() -> crate::schema::Node {
+        crate::schema::Node::container("Vecs", [("bounds", BC::schema()), ("values", TC::schema())])
+    }
+}
+
+impl<'a, TC: crate::FromBytes<'a>, BC: crate::FromBytes<'a> + IndexAs<u64>> crate::FromBytes<'a> for Vecs<TC, BC> {
     const SLICE_COUNT: usize = BC::SLICE_COUNT + TC::SLICE_COUNT;
     #[inline(always)]
     fn from_bytes(bytes: &mut impl Iterator<Item=&'a [u8]>) -> Self {
@@ -152,6 +158,11 @@ impl<'a, TC: crate::FromBytes<'a>, BC: crate::FromBytes<'a>> crate::FromBytes<'a
         TC::element_sizes(sizes)?;
         Ok(())
     }
+    fn validate_deep(&self) -> Result<usize, String> {
+        let bounds = self.bounds.validate_deep()?;
+        crate::common::validate_bounds("Vecs", &self.bounds, bounds, self.values.validate_deep()?)?;
+        Ok(bounds)
+    }
 }
 
 impl<TC: Len> Vecs<TC> {
@@ -9,6 +9,7 @@ extern crate alloc;
 use alloc::vec;
 
 /// Exercise core columnar types without `std`.
+#[allow(clippy::vec_init_then_push)]
 pub fn smoke_test() {
     use columnar::{Push, Len, Index, Borrow};
 